@@ -1,6 +1,6 @@
 #!/bin/bash -e
 
-rm -f challenge* response* new_challenge* new_response* new_new_challenge_* processed* initial_ceremony* response_list* combined* seed* chunk*
+rm -f challenge* response* new_challenge* new_response* new_new_challenge_* processed* initial_ceremony* response_list* combined* seed* chunk* beacon.checkpoints
 
 export RUSTFLAGS="-C target-feature=+bmi2,+adx"
 CARGO_VER=""
@@ -13,7 +13,7 @@ if [ "$PROVING_SYSTEM" == "groth16" ]; then
 else
   MAX_CHUNK_INDEX=$((2-1)) # we have 2 chunks, since we have a total of 2^11-1 powers
 fi
-CURVE="bw6"
+CURVE=${CURVE:-"bw6"} # e.g. CURVE=bls12_381
 SEED1=$(tr -dc 'A-F0-9' < /dev/urandom | head -c32)
 echo $SEED1 > seed1
 SEED2=$(tr -dc 'A-F0-9' < /dev/urandom | head -c32)
@@ -64,8 +64,9 @@ done
 echo "Aggregating..."
 $phase1_combine combine --response-list-fname response_list --combined-fname combined
 echo "Apply beacon..."
-$phase1_full beacon --challenge-fname combined --response-fname response_beacon --beacon-hash 0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620
+$phase1_full beacon --challenge-fname combined --response-fname response_beacon --beacon-hash 0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620 --iterations-exp 10
 echo "Verifying..."
+$phase1_full verify-beacon --challenge-fname combined --response-fname response_beacon --beacon-hash 0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620 --iterations-exp 10 --allow-low-iterations
 $phase1_full verify-and-transform-pok-and-correctness --challenge-fname combined --challenge-hash-fname combined.verified.hash --response-fname response_beacon --response-hash-fname response_beacon.verified.hash --new-challenge-fname response_beacon_new_challenge --new-challenge-hash-fname response_beacon_new_challenge.verified.hash
 $phase1_full verify-and-transform-ratios --response-fname response_beacon_new_challenge
 
@@ -99,7 +100,7 @@ for i in $(seq $(($MAX_CHUNK_INDEX/2 + 1)) $MAX_CHUNK_INDEX); do
 done
 
 $phase1_combine combine --response-list-fname response_list_split --combined-fname combined_split
-$phase1_full beacon --challenge-fname combined_split --challenge-hash-fname challenge_$i.hash --response-fname response_beacon_split --response-hash-fname response_$i.hash --beacon-hash 0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620
+$phase1_full beacon --challenge-fname combined_split --challenge-hash-fname challenge_$i.hash --response-fname response_beacon_split --response-hash-fname response_$i.hash --beacon-hash 0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620 --iterations-exp 10
 $phase1_full verify-and-transform-pok-and-correctness --challenge-fname combined_split --challenge-hash-fname combined_split.verified.hash --response-fname response_beacon_split --response-hash-fname response_beacon_split.verified.hash --new-challenge-fname response_beacon_new_challenge_split --new-challenge-hash-fname response_beacon_new_challenge_split.verified.hash
 $phase1_full verify-and-transform-ratios --response-fname response_beacon_new_challenge_split
 echo "Done!"
\ No newline at end of file
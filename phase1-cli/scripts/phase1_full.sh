@@ -1,11 +1,11 @@
 #!/bin/bash -e
 
-rm -f challenge* response* new_challenge* new_response* processed* initial_ceremony* response_list* combined* seed* *.hash
+rm -f challenge* response* new_challenge* new_response* processed* initial_ceremony* response_list* combined* seed* *.hash beacon.checkpoints
 
 PROVING_SYSTEM=$1
 POWER=10
 BATCH=64
-CURVE="bw6"
+CURVE=${CURVE:-"bw6"} # e.g. CURVE=bls12_381
 SEED=`tr -dc 'A-F0-9' < /dev/urandom | head -c32`
 echo $SEED > seed1
 
@@ -27,8 +27,9 @@ check_hash response
 echo "Verifying..."
 $phase1 verify-and-transform-pok-and-correctness --challenge-fname challenge --challenge-hash-fname challenge.verified.hash --response-fname response --response-hash-fname response.verified.hash --new-challenge-fname new_challenge --new-challenge-hash-fname new_challenge.verified.hash
 echo "Applying beacon..."
-$phase1 beacon --challenge-fname new_challenge --challenge-hash-fname new_challenge.hash --response-fname new_response --response-hash-fname new_response.hash --beacon-hash 0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620
+$phase1 beacon --challenge-fname new_challenge --challenge-hash-fname new_challenge.hash --response-fname new_response --response-hash-fname new_response.hash --beacon-hash 0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620 --iterations-exp 10
 echo "Verifying..."
+$phase1 verify-beacon --challenge-fname new_challenge --response-fname new_response --beacon-hash 0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620 --iterations-exp 10 --allow-low-iterations
 $phase1 verify-and-transform-pok-and-correctness --challenge-fname new_challenge --challenge-hash-fname new_challenge.verified.hash --response-fname new_response --response-hash-fname new_response.verified.hash --new-challenge-fname new_challenge_2 --new-challenge-hash-fname new_challenge_2.verified.hash
 $phase1 verify-and-transform-ratios --response-fname new_challenge_2
 echo "Done!"
@@ -0,0 +1,396 @@
+use phase1::{Phase1, Phase1Parameters, PublicKey};
+use setup_utils::{
+    blank_hash,
+    calculate_hash,
+    print_hash,
+    write_to_file,
+    CeremonyManifest,
+    CheckForCorrectness,
+    Error,
+    Result,
+    SubgroupCheckMode,
+    UseCompression,
+    VerificationError,
+};
+
+use ark_ec::pairing::Pairing as Engine;
+
+use memmap::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+use tracing::info;
+
+use crate::file_headers::InputFile;
+
+const CHALLENGE_IS_COMPRESSED: UseCompression = UseCompression::No;
+const CONTRIBUTION_IS_COMPRESSED: UseCompression = UseCompression::Yes;
+
+/// The outcome of the audit of a contribution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditStatus {
+    Passed,
+    Failed,
+    /// Not verified, since an earlier contribution failed and the chain is broken
+    Skipped,
+}
+
+/// The audit of a single contribution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContributionAudit {
+    /// The response file of the contribution
+    pub response: String,
+    pub status: AuditStatus,
+    /// The hex encoded BLAKE2b hash of the response, if it could be read
+    pub response_hash: Option<String>,
+    /// The hex encoded BLAKE2b hash of the contributor's public key, if it could be read
+    pub public_key_hash: Option<String>,
+    /// Why the contribution failed
+    pub error: Option<String>,
+}
+
+/// The report of the audit of a full ceremony
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// The hex encoded BLAKE2b hash of the initial challenge
+    pub challenge_hash: String,
+    pub contributions: Vec<ContributionAudit>,
+    /// The hex encoded BLAKE2b hash of the challenge following the last contribution, if every
+    /// contribution passed
+    pub final_challenge_hash: Option<String>,
+}
+
+/// Verifies every full contribution of a ceremony in order, starting from the initial challenge,
+/// and writes a JSON report of the outcome for every contributor to `report_filename`.
+///
+/// The initial challenge must be the accumulator created by `new`, which is compared to one
+/// created again from `parameters`. Each response is checked against the challenge it follows with
+/// `Phase1::verification`, and the challenge it produces is checked with
+/// `Phase1::aggregate_verification` before the next response is checked against it. The challenges
+/// are only kept in memory. After the first failure, the remaining contributions are skipped and
+/// the error is returned once the report is written.
+pub fn audit<T: Engine + Sync>(
+    challenge_filename: &str,
+    response_list_filename: &str,
+    report_filename: &str,
+    check_input_correctness: CheckForCorrectness,
+    check_output_correctness: CheckForCorrectness,
+    subgroup_check_mode: SubgroupCheckMode,
+    parameters: &Phase1Parameters<T>,
+) -> Result<AuditReport> {
+    info!(
+        "Will audit the contributions to an accumulator for 2^{} powers of tau",
+        parameters.total_size_in_log2
+    );
+
+    let response_filenames = BufReader::new(File::open(response_list_filename)?)
+        .lines()
+        .collect::<std::io::Result<Vec<_>>>()?;
+    if response_filenames.is_empty() {
+        return Err(Error::InvalidResponseList(format!(
+            "{} lists no responses",
+            response_list_filename
+        )));
+    }
+    for (i, response_filename) in response_filenames.iter().enumerate() {
+        if response_filename.trim().is_empty() {
+            return Err(Error::InvalidResponseList(format!(
+                "line {} of {} is empty",
+                i + 1,
+                response_list_filename
+            )));
+        }
+    }
+
+    let initial_challenge = InputFile::open(
+        challenge_filename,
+        parameters.accumulator_size,
+        &parameters.file_header(CHALLENGE_IS_COMPRESSED),
+    )?;
+    check_initial_challenge(initial_challenge.contents(), parameters)?;
+    let mut challenge = MmapMut::map_anon(parameters.accumulator_size)?;
+    challenge.copy_from_slice(initial_challenge.contents());
+    let mut new_challenge = MmapMut::map_anon(parameters.accumulator_size)?;
+
+    let challenge_hash = calculate_hash(&challenge);
+    info!("Hash of the initial challenge:");
+    print_hash(&challenge_hash);
+
+    let mut report = AuditReport {
+        challenge_hash: hex::encode(challenge_hash),
+        contributions: vec![],
+        final_challenge_hash: None,
+    };
+    let mut first_error = None;
+    for (i, response_filename) in response_filenames.iter().enumerate() {
+        let mut contribution = ContributionAudit {
+            response: response_filename.clone(),
+            status: AuditStatus::Skipped,
+            response_hash: None,
+            public_key_hash: None,
+            error: None,
+        };
+        if first_error.is_none() {
+            info!("Auditing contribution {} in {}", i, response_filename);
+            let res = audit_contribution(
+                &challenge,
+                &mut new_challenge,
+                response_filename,
+                &mut contribution,
+                check_input_correctness,
+                check_output_correctness,
+                subgroup_check_mode,
+                parameters,
+            );
+            match res {
+                Ok(()) => {
+                    info!("Contribution {} passed", i);
+                    contribution.status = AuditStatus::Passed;
+                    std::mem::swap(&mut challenge, &mut new_challenge);
+                }
+                Err(e) => {
+                    info!("Contribution {} failed: {}", i, e);
+                    contribution.status = AuditStatus::Failed;
+                    contribution.error = Some(e.to_string());
+                    first_error = Some(e);
+                }
+            }
+        }
+        report.contributions.push(contribution);
+    }
+
+    if first_error.is_none() {
+        let final_challenge_hash = calculate_hash(&challenge);
+        info!("Every contribution passed, the final challenge has a hash:");
+        print_hash(&final_challenge_hash);
+        report.final_challenge_hash = Some(hex::encode(final_challenge_hash));
+    }
+
+    let contents = serde_json::to_vec_pretty(&report).map_err(std::io::Error::from)?;
+    write_to_file(report_filename, &contents)?;
+    info!("Wrote the audit report to {}", report_filename);
+
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(report),
+    }
+}
+
+/// Checks that a challenge is the one `new` creates: a blank hash, or the hash of a manifest,
+/// followed by the generators
+fn check_initial_challenge<T: Engine + Sync>(challenge: &[u8], parameters: &Phase1Parameters<T>) -> Result<()> {
+    let (hash, elements) = challenge.split_at(parameters.hash_size);
+    if hash != blank_hash().as_slice() && !CeremonyManifest::is_challenge_header(hash) {
+        return Err(VerificationError::InvalidInitialChallenge.into());
+    }
+    let mut expected = MmapMut::map_anon(parameters.accumulator_size)?;
+    Phase1::initialization(&mut expected, CHALLENGE_IS_COMPRESSED, parameters)?;
+    if elements != &expected[parameters.hash_size..] {
+        return Err(VerificationError::InvalidInitialChallenge.into());
+    }
+    Ok(())
+}
+
+/// Verifies a response to `challenge` and writes the challenge following it to `new_challenge`,
+/// recording the hashes of the response and of the public key on the way
+#[allow(clippy::too_many_arguments)]
+fn audit_contribution<T: Engine + Sync>(
+    challenge: &[u8],
+    new_challenge: &mut [u8],
+    response_filename: &str,
+    contribution: &mut ContributionAudit,
+    check_input_correctness: CheckForCorrectness,
+    check_output_correctness: CheckForCorrectness,
+    subgroup_check_mode: SubgroupCheckMode,
+    parameters: &Phase1Parameters<T>,
+) -> Result<()> {
+    let response = InputFile::open(
+        response_filename,
+        parameters.contribution_size,
+        &parameters.file_header(CONTRIBUTION_IS_COMPRESSED),
+    )?;
+    let response = response.contents();
+    let response_hash = calculate_hash(response);
+    contribution.response_hash = Some(hex::encode(response_hash));
+
+    let public_key_position = parameters.contribution_size - parameters.public_key_size;
+    contribution.public_key_hash = Some(hex::encode(calculate_hash(&response[public_key_position..])));
+    let public_key = PublicKey::read(response, CONTRIBUTION_IS_COMPRESSED, parameters)?;
+
+    // Check the hash chain - the response must be based on the previous challenge
+    let challenge_hash = calculate_hash(challenge);
+    if response[..64] != challenge_hash[..] {
+        return Err(Error::HashChainMismatch(response_filename.to_string()));
+    }
+
+    new_challenge[..64].copy_from_slice(&response_hash);
+    Phase1::verification(
+        challenge,
+        response,
+        new_challenge,
+        &public_key,
+        &challenge_hash,
+        CHALLENGE_IS_COMPRESSED,
+        CONTRIBUTION_IS_COMPRESSED,
+        CHALLENGE_IS_COMPRESSED,
+        check_input_correctness,
+        check_output_correctness,
+        subgroup_check_mode,
+        false,
+        parameters,
+    )?;
+    Phase1::aggregate_verification(
+        (new_challenge, CHALLENGE_IS_COMPRESSED, check_output_correctness),
+        parameters,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, new_challenge, test_dir::TestDir, transform_pok_and_correctness};
+    use setup_utils::{converters::ProvingSystem, derive_rng_from_seed, BatchExpMode};
+
+    use ark_bls12_377::Bls12_377;
+
+    use std::fs;
+
+    #[test]
+    fn test_audit() {
+        let dir = TestDir::new("phase1-audit");
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 3, 4);
+
+        new_challenge(
+            &dir.path("challenge_0"),
+            &dir.path("challenge_0.hash"),
+            None,
+            true,
+            &parameters,
+        )
+        .unwrap();
+        for (i, seed) in [b"alice", b"bobby"].iter().enumerate() {
+            contribute(
+                &dir.path(&format!("challenge_{}", i)),
+                &dir.path(&format!("challenge_{}.hash", i)),
+                &dir.path(&format!("response_{}", i)),
+                &dir.path(&format!("response_{}.hash", i)),
+                false,
+                CheckForCorrectness::No,
+                BatchExpMode::Auto,
+                &parameters,
+                derive_rng_from_seed(*seed),
+            )
+            .unwrap();
+            transform_pok_and_correctness(
+                &dir.path(&format!("challenge_{}", i)),
+                &dir.path(&format!("challenge_{}.verified.hash", i)),
+                CheckForCorrectness::No,
+                &dir.path(&format!("response_{}", i)),
+                &dir.path(&format!("response_{}.verified.hash", i)),
+                CheckForCorrectness::Full,
+                &dir.path(&format!("challenge_{}", i + 1)),
+                &dir.path(&format!("challenge_{}.hash", i + 1)),
+                None,
+                false,
+                SubgroupCheckMode::Auto,
+                true,
+                &parameters,
+            )
+            .unwrap();
+        }
+        fs::write(
+            dir.path("responses"),
+            format!("{}\n{}\n", dir.path("response_0"), dir.path("response_1")),
+        )
+        .unwrap();
+
+        let report = audit(
+            &dir.path("challenge_0"),
+            &dir.path("responses"),
+            &dir.path("report.json"),
+            CheckForCorrectness::Full,
+            CheckForCorrectness::Full,
+            SubgroupCheckMode::Auto,
+            &parameters,
+        )
+        .unwrap();
+        assert!(report
+            .contributions
+            .iter()
+            .all(|contribution| contribution.status == AuditStatus::Passed));
+        // the audit ends with the same challenge as the verification of every contribution
+        let final_challenge = fs::read(dir.path("challenge_2.hash")).unwrap();
+        assert_eq!(report.final_challenge_hash, Some(hex::encode(final_challenge)));
+        let written: AuditReport = serde_json::from_slice(&fs::read(dir.path("report.json")).unwrap()).unwrap();
+        assert_eq!(written, report);
+
+        // responses out of order break the hash chain
+        fs::write(
+            dir.path("swapped_responses"),
+            format!("{}\n{}\n", dir.path("response_1"), dir.path("response_0")),
+        )
+        .unwrap();
+        let err = audit(
+            &dir.path("challenge_0"),
+            &dir.path("swapped_responses"),
+            &dir.path("swapped_report.json"),
+            CheckForCorrectness::Full,
+            CheckForCorrectness::Full,
+            SubgroupCheckMode::Auto,
+            &parameters,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::HashChainMismatch(_)), "{}", err);
+        let written: AuditReport = serde_json::from_slice(&fs::read(dir.path("swapped_report.json")).unwrap()).unwrap();
+        let statuses = written
+            .contributions
+            .iter()
+            .map(|contribution| contribution.status)
+            .collect::<Vec<_>>();
+        assert_eq!(statuses, vec![AuditStatus::Failed, AuditStatus::Skipped]);
+        assert!(written.final_challenge_hash.is_none());
+
+        // the audit can't start from a challenge other than the initial one, even if its hash is
+        // replaced by a blank one
+        let mut tampered = fs::read(dir.path("challenge_1")).unwrap();
+        let audit_from = |challenge: &[u8]| {
+            fs::write(dir.path("tampered_challenge"), challenge).unwrap();
+            audit(
+                &dir.path("tampered_challenge"),
+                &dir.path("responses"),
+                &dir.path("tampered_report.json"),
+                CheckForCorrectness::Full,
+                CheckForCorrectness::Full,
+                SubgroupCheckMode::Auto,
+                &parameters,
+            )
+            .unwrap_err()
+        };
+        let err = audit_from(&tampered);
+        assert!(
+            matches!(
+                err,
+                Error::VerificationError(VerificationError::InvalidInitialChallenge)
+            ),
+            "{}",
+            err
+        );
+        let offset = tampered.len() - parameters.accumulator_size;
+        tampered[offset..offset + parameters.hash_size].copy_from_slice(&blank_hash());
+        let err = audit_from(&tampered);
+        assert!(
+            matches!(
+                err,
+                Error::VerificationError(VerificationError::InvalidInitialChallenge)
+            ),
+            "{}",
+            err
+        );
+        assert_eq!(err.exit_code(), Error::EXIT_CODE_VERIFICATION);
+    }
+}
@@ -1,25 +1,44 @@
 use phase1::{CurveParameters, Phase1Parameters};
 use phase1_cli::{
+    audit,
+    check_challenge_manifest,
     combine,
     contribute,
+    export_kzg_srs,
+    export_ptau,
+    header_curve_kind,
+    import_ptau,
+    inspect,
     new_challenge,
+    reduce,
     split,
     transform_pok_and_correctness,
     transform_ratios,
+    verify_beacon,
+    verify_transcript,
     Command,
     Phase1Opts,
 };
 use setup_utils::{
+    beacon_hash_from_str,
     converters::CurveKind,
     derive_rng_from_seed,
-    from_slice,
+    snarkjs::SnarkjsPoint,
     upgrade_correctness_check_config,
+    write_failure_report,
+    BeaconCheckpoints,
+    CeremonyManifest,
+    Error,
+    Result,
+    UseCompression,
     DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
     DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
     DEFAULT_VERIFY_CHECK_OUTPUT_CORRECTNESS,
 };
 
 use ark_bls12_377::Bls12_377;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
 use ark_bw6_761::BW6_761;
 use ark_ec::pairing::Pairing as Engine;
 use ark_mnt4_753::MNT4_753;
@@ -33,40 +52,59 @@ use tracing_subscriber::{
     fmt::{time::ChronoUtc, Subscriber},
 };
 
-fn execute_cmd<E: Engine>(opts: Phase1Opts) {
-    let curve = CurveParameters::<E>::new();
-    let parameters = Phase1Parameters::<E>::new(
-        opts.contribution_mode,
-        opts.chunk_index,
-        opts.chunk_size,
-        curve,
-        opts.proving_system,
-        opts.power,
-        opts.batch_size,
-    );
+fn execute_cmd<E: Engine>(opts: Phase1Opts, manifest: Option<CeremonyManifest>) -> Result<()>
+where
+    E::G1Affine: SnarkjsPoint,
+    E::G2Affine: SnarkjsPoint,
+{
+    let parameters = match &manifest {
+        Some(manifest) => Phase1Parameters::<E>::from_manifest(manifest, opts.chunk_index)?,
+        None => Phase1Parameters::<E>::new(
+            opts.contribution_mode,
+            opts.chunk_index,
+            opts.chunk_size,
+            CurveParameters::<E>::new(),
+            opts.proving_system,
+            opts.power,
+            opts.batch_size,
+        ),
+    };
+    // challenges must belong to the ceremony described by the manifest, if one was given
+    let check_manifest = |challenge_filename: &str| match &manifest {
+        Some(manifest) => check_challenge_manifest(challenge_filename, manifest),
+        None => Ok(()),
+    };
 
     let command = opts.clone().command.unwrap_or_else(|| {
         error!("No command was provided.");
         error!("{}", Phase1Opts::usage());
-        process::exit(2)
+        process::exit(Error::EXIT_CODE_ARGS)
     });
 
     let now = Instant::now();
 
     match command {
         Command::New(opt) => {
-            new_challenge(&opt.challenge_fname, &opt.challenge_hash_fname, &parameters);
+            new_challenge(
+                &opt.challenge_fname,
+                &opt.challenge_hash_fname,
+                manifest.as_ref(),
+                opt.file_header,
+                &parameters,
+            )?;
         }
         Command::Contribute(opt) => {
+            check_manifest(&opt.challenge_fname)?;
             // contribute to the randomness
-            let seed = hex::decode(&read_to_string(&opts.seed).expect("should have read seed").trim())
-                .expect("seed should be a hex string");
+            let seed = hex::decode(read_to_string(&opts.seed)?.trim())
+                .map_err(|_| Error::InvalidSeed(format!("{} should contain a hex string", opts.seed)))?;
             let rng = derive_rng_from_seed(&seed);
             contribute(
                 &opt.challenge_fname,
                 &opt.challenge_hash_fname,
                 &opt.response_fname,
                 &opt.response_hash_fname,
+                opt.resume,
                 upgrade_correctness_check_config(
                     DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
                     opts.force_correctness_checks,
@@ -74,18 +112,22 @@ fn execute_cmd<E: Engine>(opts: Phase1Opts) {
                 opts.batch_exp_mode,
                 &parameters,
                 rng,
-            );
+            )?;
         }
         Command::Beacon(opt) => {
-            // use the beacon's randomness
+            check_manifest(&opt.challenge_fname)?;
+            // use the beacon's randomness, after hashing it 2^iterations_exp times
             // Place block hash here (block number #564321)
-            let beacon_hash = hex::decode(&opt.beacon_hash).expect("could not hex decode beacon hash");
-            let rng = derive_rng_from_seed(&from_slice(&beacon_hash));
+            let beacon_hash = beacon_hash_from_str(&opt.beacon_hash)?;
+            let checkpoints =
+                BeaconCheckpoints::read_or_compute(&opt.checkpoints_fname, beacon_hash, opt.iterations_exp)?;
+            let rng = derive_rng_from_seed(&checkpoints.result);
             contribute(
                 &opt.challenge_fname,
                 &opt.challenge_hash_fname,
                 &opt.response_fname,
                 &opt.response_hash_fname,
+                opt.resume,
                 upgrade_correctness_check_config(
                     DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
                     opts.force_correctness_checks,
@@ -93,9 +135,22 @@ fn execute_cmd<E: Engine>(opts: Phase1Opts) {
                 opts.batch_exp_mode,
                 &parameters,
                 rng,
-            );
+            )?;
+        }
+        Command::VerifyBeacon(opt) => {
+            check_manifest(&opt.challenge_fname)?;
+            verify_beacon(
+                &opt.challenge_fname,
+                &opt.response_fname,
+                &opt.checkpoints_fname,
+                beacon_hash_from_str(&opt.beacon_hash)?,
+                opt.iterations_exp,
+                opt.allow_low_iterations,
+                &parameters,
+            )?;
         }
         Command::VerifyAndTransformPokAndCorrectness(opt) => {
+            check_manifest(&opt.challenge_fname)?;
             // we receive a previous participation, verify it, and generate a new challenge from it
             transform_pok_and_correctness(
                 &opt.challenge_fname,
@@ -109,10 +164,12 @@ fn execute_cmd<E: Engine>(opts: Phase1Opts) {
                 ),
                 &opt.new_challenge_fname,
                 &opt.new_challenge_hash_fname,
+                opt.transcript_fname.as_deref(),
+                opt.resume,
                 opts.subgroup_check_mode,
                 !opts.skip_ratio_check,
                 &parameters,
-            );
+            )?;
         }
         Command::VerifyAndTransformRatios(opt) => {
             // we receive a previous participation, verify it, and generate a new challenge from it
@@ -120,18 +177,87 @@ fn execute_cmd<E: Engine>(opts: Phase1Opts) {
                 &opt.response_fname,
                 upgrade_correctness_check_config(DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS, opts.force_correctness_checks),
                 &parameters,
-            );
+            )?;
         }
         Command::Combine(opt) => {
-            combine(&opt.response_list_fname, &opt.combined_fname, &parameters);
+            combine(&opt.response_list_fname, &opt.combined_fname, &parameters)?;
         }
         Command::Split(opt) => {
-            split(&opt.chunk_fname_prefix, &opt.full_fname, &parameters);
+            split(&opt.chunk_fname_prefix, &opt.full_fname, &parameters)?;
+        }
+        Command::Reduce(opt) => {
+            check_manifest(&opt.challenge_fname)?;
+            reduce(
+                &opt.challenge_fname,
+                &opt.reduced_challenge_fname,
+                &opt.reduced_challenge_hash_fname,
+                upgrade_correctness_check_config(DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS, opts.force_correctness_checks),
+                &Phase1Parameters::<E>::new_full(opts.proving_system, opt.from_power, opts.batch_size),
+                &Phase1Parameters::<E>::new_full(opts.proving_system, opt.to_power, opts.batch_size),
+            )?;
+        }
+        Command::ExportPtau(opt) => {
+            check_manifest(&opt.challenge_fname)?;
+            export_ptau(
+                &opt.challenge_fname,
+                opt.response_list_fname.as_deref(),
+                &opt.ptau_fname,
+                upgrade_correctness_check_config(DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS, opts.force_correctness_checks),
+                &parameters,
+            )?;
+        }
+        Command::ImportPtau(opt) => {
+            import_ptau(
+                &opt.ptau_fname,
+                &opt.challenge_fname,
+                &opt.challenge_hash_fname,
+                upgrade_correctness_check_config(DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS, opts.force_correctness_checks),
+                &parameters,
+            )?;
+        }
+        Command::ExportKzgSrs(opt) => {
+            check_manifest(&opt.challenge_fname)?;
+            export_kzg_srs(
+                &opt.challenge_fname,
+                &opt.srs_fname,
+                opt.format,
+                if opt.uncompressed {
+                    UseCompression::No
+                } else {
+                    UseCompression::Yes
+                },
+                opt.max_degree,
+                upgrade_correctness_check_config(DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS, opts.force_correctness_checks),
+                &parameters,
+            )?;
+        }
+        Command::Audit(opt) => {
+            check_manifest(&opt.challenge_fname)?;
+            audit(
+                &opt.challenge_fname,
+                &opt.response_list_fname,
+                &opt.report_fname,
+                upgrade_correctness_check_config(DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS, opts.force_correctness_checks),
+                upgrade_correctness_check_config(
+                    DEFAULT_VERIFY_CHECK_OUTPUT_CORRECTNESS,
+                    opts.force_correctness_checks,
+                ),
+                opts.subgroup_check_mode,
+                &Phase1Parameters::<E>::new_full(opts.proving_system, opts.power, opts.batch_size),
+            )?;
+        }
+        Command::VerifyTranscript(opt) => {
+            verify_transcript::<E>(&opt.transcript_fname)?;
+        }
+        Command::Inspect(opt) => {
+            inspect(&opt.input_fname, &parameters)?;
         }
     };
 
     let new_now = Instant::now();
     info!("Executing {:?} took: {:?}", opts, new_now.duration_since(now));
+
+    Ok(())
 }
 
 fn main() {
@@ -141,12 +267,39 @@ fn main() {
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
-    let opts: Phase1Opts = Phase1Opts::parse_args_default_or_exit();
+    let mut opts: Phase1Opts = Phase1Opts::parse_args_default_or_exit();
+    let failure_report = opts.failure_report.clone();
 
-    match opts.curve_kind {
-        CurveKind::Bls12_377 => execute_cmd::<Bls12_377>(opts),
-        CurveKind::BW6 => execute_cmd::<BW6_761>(opts),
-        CurveKind::MNT4_753 => execute_cmd::<MNT4_753>(opts),
-        CurveKind::MNT6_753 => execute_cmd::<MNT6_753>(opts),
-    };
+    let result = opts
+        .apply_manifest()
+        .and_then(|manifest| {
+            // files with a header are inspected on the curve it records
+            if let Some(Command::Inspect(opt)) = &opts.command {
+                if let Some(curve_kind) = header_curve_kind(&opt.input_fname)? {
+                    opts.curve_kind = curve_kind;
+                }
+            }
+            Ok(manifest)
+        })
+        .and_then(|manifest| match opts.curve_kind {
+            CurveKind::Bls12_377 => execute_cmd::<Bls12_377>(opts, manifest),
+            CurveKind::Bls12_381 => execute_cmd::<Bls12_381>(opts, manifest),
+            CurveKind::Bn254 => execute_cmd::<Bn254>(opts, manifest),
+            CurveKind::BW6 => execute_cmd::<BW6_761>(opts, manifest),
+            CurveKind::MNT4_753 => execute_cmd::<MNT4_753>(opts, manifest),
+            CurveKind::MNT6_753 => execute_cmd::<MNT6_753>(opts, manifest),
+        });
+
+    if let Err(e) = result {
+        error!("{}", e);
+        if let Some(failure_report) = failure_report {
+            if let Err(report_err) = write_failure_report(&failure_report, &e) {
+                error!(
+                    "Could not write the failure report to {}: {}",
+                    failure_report, report_err
+                );
+            }
+        }
+        process::exit(e.exit_code());
+    }
 }
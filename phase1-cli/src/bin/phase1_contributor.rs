@@ -0,0 +1,95 @@
+use phase1_cli::{Contributor, CoordinatorClient};
+use setup_utils::{
+    converters::{batch_exp_mode_from_str, curve_from_str, CurveKind},
+    BatchExpMode,
+    Error,
+    Result,
+};
+
+use ark_bls12_377::Bls12_377;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_bw6_761::BW6_761;
+use ark_mnt4_753::MNT4_753;
+use ark_mnt6_753::MNT6_753;
+
+use gumdrop::Options;
+use std::{fs::read_to_string, path::PathBuf, process, time::Duration};
+use tracing::{error, info};
+use tracing_subscriber::{
+    filter::EnvFilter,
+    fmt::{time::ChronoUtc, Subscriber},
+};
+
+#[derive(Debug, Options, Clone)]
+struct ContributorOpts {
+    help: bool,
+    #[options(help = "the URL of the coordinator", default = "http://127.0.0.1:8080")]
+    coordinator: String,
+    #[options(help = "the name to contribute under", required)]
+    participant: String,
+    #[options(help = "the seed to derive private elements from")]
+    seed: String,
+    #[options(
+        help = "the directory keeping the challenges and responses",
+        default = "contributions"
+    )]
+    work_dir: String,
+    #[options(
+        help = "the size in bytes of the parts responses are uploaded in",
+        default = "16777216"
+    )]
+    upload_part_size: usize,
+    #[options(help = "how many seconds to wait before asking for a chunk again", default = "10")]
+    poll_interval: u64,
+    #[options(
+        help = "whether to use batch exponentiation",
+        default = "auto",
+        parse(try_from_str = "batch_exp_mode_from_str")
+    )]
+    batch_exp_mode: BatchExpMode,
+}
+
+fn run(opts: ContributorOpts) -> Result<()> {
+    let seed = hex::decode(read_to_string(&opts.seed)?.trim())
+        .map_err(|_| Error::InvalidSeed(format!("{} should contain a hex string", opts.seed)))?;
+    let contributor = Contributor {
+        client: CoordinatorClient::new(&opts.coordinator),
+        participant: opts.participant,
+        seed,
+        work_dir: PathBuf::from(opts.work_dir),
+        upload_part_size: opts.upload_part_size,
+        poll_interval: Duration::from_secs(opts.poll_interval),
+        batch_exp_mode: opts.batch_exp_mode,
+    };
+
+    let settings = contributor.client.ceremony()?.settings;
+    info!(
+        "Contributing to a ceremony of power {} on {}, with chunks of size {}",
+        settings.power, settings.curve_kind, settings.chunk_size
+    );
+    let curve_kind = curve_from_str(&settings.curve_kind).map_err(Error::CoordinatorError)?;
+    match curve_kind {
+        CurveKind::Bls12_377 => contributor.run::<Bls12_377>(&settings),
+        CurveKind::Bls12_381 => contributor.run::<Bls12_381>(&settings),
+        CurveKind::Bn254 => contributor.run::<Bn254>(&settings),
+        CurveKind::BW6 => contributor.run::<BW6_761>(&settings),
+        CurveKind::MNT4_753 => contributor.run::<MNT4_753>(&settings),
+        CurveKind::MNT6_753 => contributor.run::<MNT6_753>(&settings),
+    }
+}
+
+fn main() {
+    Subscriber::builder()
+        .with_target(false)
+        .with_timer(ChronoUtc::rfc3339())
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let opts: ContributorOpts = ContributorOpts::parse_args_default_or_exit();
+
+    if let Err(e) = run(opts) {
+        error!("{}", e);
+        process::exit(e.exit_code());
+    }
+}
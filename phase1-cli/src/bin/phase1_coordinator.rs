@@ -0,0 +1,154 @@
+use phase1_cli::{read_ceremony_state, serve, CeremonySettings, Coordinator};
+use setup_utils::{
+    converters::{curve_from_str, proving_system_from_str, CurveKind},
+    Error,
+    Result,
+};
+
+use ark_bls12_377::Bls12_377;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_bw6_761::BW6_761;
+use ark_ec::pairing::Pairing as Engine;
+use ark_mnt4_753::MNT4_753;
+use ark_mnt6_753::MNT6_753;
+
+use gumdrop::Options;
+use std::{io, path::Path, process};
+use tiny_http::Server;
+use tracing::{error, info};
+use tracing_subscriber::{
+    filter::EnvFilter,
+    fmt::{time::ChronoUtc, Subscriber},
+};
+
+#[derive(Debug, Options, Clone)]
+struct CoordinatorOpts {
+    help: bool,
+    #[options(help = "the directory keeping the state of the ceremony", default = "ceremony")]
+    state_dir: String,
+    #[options(command)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Options, Clone)]
+enum Command {
+    #[options(help = "creates a new ceremony in the state directory")]
+    New(NewOpts),
+    #[options(help = "serves the ceremony kept in the state directory to participants")]
+    Serve(ServeOpts),
+    #[options(help = "releases the lock on a chunk, while the ceremony is not being served")]
+    Unlock(UnlockOpts),
+}
+
+#[derive(Debug, Options, Clone)]
+struct NewOpts {
+    help: bool,
+    #[options(help = "the elliptic curve to use", default = "bls12_377")]
+    curve_kind: String,
+    #[options(help = "the proving system to use", default = "groth16")]
+    proving_system: String,
+    #[options(help = "the circuit power (circuit size will be 2^{power})", default = "21")]
+    power: usize,
+    #[options(help = "the size of batches to process", default = "256")]
+    batch_size: usize,
+    #[options(help = "the chunk size", default = "65536")]
+    chunk_size: usize,
+    #[options(
+        help = "the number of participants, each of which contributes to every chunk",
+        default = "1"
+    )]
+    rounds: usize,
+    #[options(
+        help = "the number of seconds after which a lock expires if its holder made no progress, 0 for never",
+        default = "86400"
+    )]
+    lock_timeout: u64,
+}
+
+#[derive(Debug, Options, Clone)]
+struct ServeOpts {
+    help: bool,
+    #[options(help = "the address to listen on", default = "127.0.0.1:8080")]
+    listen: String,
+}
+
+#[derive(Debug, Options, Clone)]
+struct UnlockOpts {
+    help: bool,
+    #[options(help = "the index of the chunk to unlock", required)]
+    chunk_index: usize,
+}
+
+fn execute_cmd<E: Engine + Sync>(state_dir: &Path, command: Command, settings: CeremonySettings) -> Result<()> {
+    match command {
+        Command::New(_) => {
+            Coordinator::<E>::new(state_dir, settings)?;
+        }
+        Command::Serve(opt) => {
+            let mut coordinator = Coordinator::<E>::open(state_dir)?;
+            let server = Server::http(&opt.listen).map_err(io::Error::other)?;
+            info!("Serving the ceremony in {} on {}", state_dir.display(), opt.listen);
+            serve(&mut coordinator, &server)?;
+        }
+        Command::Unlock(opt) => {
+            Coordinator::<E>::open(state_dir)?.unlock(opt.chunk_index)?;
+        }
+    };
+
+    Ok(())
+}
+
+fn run(opts: CoordinatorOpts) -> Result<()> {
+    let command = opts.command.unwrap_or_else(|| {
+        error!("No command was provided.");
+        error!("{}", CoordinatorOpts::usage());
+        process::exit(Error::EXIT_CODE_ARGS)
+    });
+    let state_dir = Path::new(&opts.state_dir);
+
+    let settings = match &command {
+        Command::New(opt) => CeremonySettings {
+            curve_kind: opt.curve_kind.clone(),
+            proving_system: opt.proving_system.clone(),
+            power: opt.power,
+            batch_size: opt.batch_size,
+            chunk_size: opt.chunk_size,
+            rounds: opt.rounds,
+            lock_timeout: opt.lock_timeout,
+        },
+        Command::Serve(_) | Command::Unlock(_) => read_ceremony_state(state_dir)?.settings,
+    };
+    let curve_kind = curve_from_str(&settings.curve_kind).unwrap_or_else(|e| {
+        error!("{}", e);
+        process::exit(Error::EXIT_CODE_ARGS)
+    });
+    if let Err(e) = proving_system_from_str(&settings.proving_system) {
+        error!("{}", e);
+        process::exit(Error::EXIT_CODE_ARGS)
+    }
+
+    match curve_kind {
+        CurveKind::Bls12_377 => execute_cmd::<Bls12_377>(state_dir, command, settings),
+        CurveKind::Bls12_381 => execute_cmd::<Bls12_381>(state_dir, command, settings),
+        CurveKind::Bn254 => execute_cmd::<Bn254>(state_dir, command, settings),
+        CurveKind::BW6 => execute_cmd::<BW6_761>(state_dir, command, settings),
+        CurveKind::MNT4_753 => execute_cmd::<MNT4_753>(state_dir, command, settings),
+        CurveKind::MNT6_753 => execute_cmd::<MNT6_753>(state_dir, command, settings),
+    }
+}
+
+fn main() {
+    Subscriber::builder()
+        .with_target(false)
+        .with_timer(ChronoUtc::rfc3339())
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let opts: CoordinatorOpts = CoordinatorOpts::parse_args_default_or_exit();
+
+    if let Err(e) = run(opts) {
+        error!("{}", e);
+        process::exit(e.exit_code());
+    }
+}
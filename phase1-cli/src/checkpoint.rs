@@ -0,0 +1,124 @@
+use phase1::checkpoint::ComputationCheckpoint;
+use setup_utils::{check_file_size, Error, Result};
+
+use std::{
+    fs::{self, File, OpenOptions},
+    path::Path,
+    time::{Duration, Instant},
+};
+use tracing::info;
+
+/// How often the progress is saved
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The checkpoint of an output file is stored next to it
+pub(crate) fn checkpoint_filename(output_filename: &str) -> String {
+    format!("{}.checkpoint", output_filename)
+}
+
+/// Returns the checkpoint to resume from, if `resume` is set and the output file exists. If the
+/// output file exists without a checkpoint, the previous run stopped before saving its first
+/// checkpoint, so the output is removed and the computation starts over.
+pub(crate) fn read_checkpoint(output_filename: &str, resume: bool) -> Result<Option<ComputationCheckpoint>> {
+    if !resume || !Path::new(output_filename).exists() {
+        return Ok(None);
+    }
+    let checkpoint_filename = checkpoint_filename(output_filename);
+    if !Path::new(&checkpoint_filename).exists() {
+        info!("{} exists without a checkpoint, starting over", output_filename);
+        fs::remove_file(output_filename)?;
+        return Ok(None);
+    }
+    let checkpoint = fs::read(&checkpoint_filename).map_err(|_| {
+        Error::CannotResume(format!(
+            "{} exists but {} could not be read",
+            output_filename, checkpoint_filename
+        ))
+    })?;
+    let checkpoint = ComputationCheckpoint::read(checkpoint.as_slice())
+        .map_err(|_| Error::CannotResume(format!("{} is not a valid checkpoint", checkpoint_filename)))?;
+    Ok(Some(checkpoint))
+}
+
+/// Reopens the output file when resuming, or creates it otherwise.
+pub(crate) fn open_output(output_filename: &str, resuming: bool, length: usize) -> Result<File> {
+    if resuming {
+        let writer = OpenOptions::new().read(true).write(true).open(output_filename)?;
+        check_file_size(&writer, output_filename, length)?;
+        Ok(writer)
+    } else {
+        let writer = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(output_filename)?;
+        writer.set_len(length as u64)?;
+        Ok(writer)
+    }
+}
+
+/// Saves the checkpoint once the output file is synced to disk, so that the checkpoint never
+/// describes output which was not persisted. The checkpoint is replaced atomically.
+pub(crate) fn write_checkpoint(output: &File, output_filename: &str, checkpoint: &ComputationCheckpoint) -> Result<()> {
+    output.sync_data()?;
+    let checkpoint_filename = checkpoint_filename(output_filename);
+    let tmp_filename = format!("{}.tmp", checkpoint_filename);
+    let mut file = File::create(&tmp_filename)?;
+    checkpoint.write(&mut file)?;
+    file.sync_all()?;
+    fs::rename(tmp_filename, checkpoint_filename)?;
+    Ok(())
+}
+
+/// Returns a callback which saves the checkpoints it receives every `CHECKPOINT_INTERVAL`
+pub(crate) fn save_checkpoints<'a>(
+    output: &'a File,
+    output_filename: &'a str,
+) -> impl FnMut(&ComputationCheckpoint) -> Result<()> + 'a {
+    let mut last_checkpoint_time = Instant::now();
+    move |checkpoint| {
+        if last_checkpoint_time.elapsed() >= CHECKPOINT_INTERVAL {
+            write_checkpoint(output, output_filename, checkpoint)?;
+            info!("Saved the progress after {} batches", checkpoint.completed_batches);
+            last_checkpoint_time = Instant::now();
+        }
+        Ok(())
+    }
+}
+
+/// Removes the checkpoint once the output file is complete
+pub(crate) fn remove_checkpoint(output_filename: &str) -> Result<()> {
+    fs::remove_file(checkpoint_filename(output_filename))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn test_resume_without_checkpoint() {
+        let dir = TestDir::new("phase1-checkpoint");
+        let output_filename = dir.path("response");
+
+        // interrupted before the first checkpoint was saved
+        open_output(&output_filename, false, 16).unwrap();
+        assert!(read_checkpoint(&output_filename, false).unwrap().is_none());
+        assert!(Path::new(&output_filename).exists());
+        assert!(read_checkpoint(&output_filename, true).unwrap().is_none());
+        assert!(!Path::new(&output_filename).exists());
+
+        // interrupted after a checkpoint was saved
+        let output = open_output(&output_filename, false, 16).unwrap();
+        let checkpoint = ComputationCheckpoint::default();
+        write_checkpoint(&output, &output_filename, &checkpoint).unwrap();
+        assert_eq!(read_checkpoint(&output_filename, true).unwrap(), Some(checkpoint));
+        open_output(&output_filename, true, 16).unwrap();
+
+        // a corrupted checkpoint is still an error
+        fs::write(checkpoint_filename(&output_filename), b"").unwrap();
+        let err = read_checkpoint(&output_filename, true).unwrap_err();
+        assert!(matches!(err, Error::CannotResume(_)), "{}", err);
+    }
+}
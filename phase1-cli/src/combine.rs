@@ -1,5 +1,5 @@
-use phase1::{Phase1, Phase1Parameters};
-use setup_utils::UseCompression;
+use phase1::{Phase1, Phase1Parameters, ProvingSystem};
+use setup_utils::{header_length, Error, Result, UseCompression};
 
 use ark_ec::pairing::Pairing as Engine;
 
@@ -10,52 +10,61 @@ use std::{
 };
 use tracing::info;
 
+use crate::file_headers::{write_header, InputFile};
+
 const CONTRIBUTION_IS_COMPRESSED: UseCompression = UseCompression::Yes;
 const COMPRESS_NEW_COMBINED: UseCompression = UseCompression::No;
 
+/// Combines the responses to every chunk into a full accumulator. If any of the responses starts
+/// with a file header, so does the combined accumulator.
 pub fn combine<T: Engine + Sync>(
     response_list_filename: &str,
     combined_filename: &str,
     parameters: &Phase1Parameters<T>,
-) {
+) -> Result<()> {
     info!("Will combine contributions",);
 
     let mut readers = vec![];
 
-    let response_list_reader =
-        BufReader::new(File::open(response_list_filename).expect("should have opened the response list"));
-    for (chunk_index, line) in response_list_reader.lines().enumerate() {
-        let line = line.expect("should have read line");
-        let parameters =
-            parameters.into_chunk_parameters(parameters.contribution_mode, chunk_index, parameters.chunk_size);
-        let response_reader = OpenOptions::new()
-            .read(true)
-            .open(line)
-            .expect("unable open response file in this directory");
-        {
-            let metadata = response_reader
-                .metadata()
-                .expect("unable to get filesystem metadata for response file");
-            let expected_response_length = match CONTRIBUTION_IS_COMPRESSED {
-                UseCompression::Yes => parameters.contribution_size,
-                UseCompression::No => parameters.accumulator_size + parameters.public_key_size,
-            };
-            if metadata.len() != (expected_response_length as u64) {
-                panic!(
-                    "The size of response file should be {}, but it's {}, so something isn't right.",
-                    expected_response_length,
-                    metadata.len()
-                );
-            }
-        }
+    let response_list_reader = BufReader::new(File::open(response_list_filename)?);
+    let response_filenames = response_list_reader.lines().collect::<std::io::Result<Vec<_>>>()?;
+
+    // There must be exactly one response per chunk
+    let powers_length: usize = 1 << parameters.total_size_in_log2;
+    let powers_g1_length = (powers_length << 1) - 1;
+    let powers_length_for_proving_system = match parameters.proving_system {
+        ProvingSystem::Groth16 => powers_g1_length,
+        ProvingSystem::Marlin => powers_length,
+    };
+    let num_chunks = powers_length_for_proving_system.div_ceil(parameters.chunk_size);
+    if response_filenames.len() != num_chunks {
+        return Err(Error::InvalidResponseList(format!(
+            "expected {} responses, one per chunk, but {} lists {}",
+            num_chunks,
+            response_list_filename,
+            response_filenames.len()
+        )));
+    }
 
-        unsafe {
-            readers.push(
-                MmapOptions::new()
-                    .map(&response_reader)
-                    .expect("should have mapped the reader"),
-            );
+    for (chunk_index, response_filename) in response_filenames.iter().enumerate() {
+        if response_filename.trim().is_empty() {
+            return Err(Error::InvalidResponseList(format!(
+                "line {} of {} is empty",
+                chunk_index + 1,
+                response_list_filename
+            )));
         }
+        let parameters =
+            parameters.into_chunk_parameters(parameters.contribution_mode, chunk_index, parameters.chunk_size);
+        let expected_response_length = match CONTRIBUTION_IS_COMPRESSED {
+            UseCompression::Yes => parameters.contribution_size,
+            UseCompression::No => parameters.accumulator_size + parameters.public_key_size,
+        };
+        readers.push(InputFile::open(
+            response_filename,
+            expected_response_length,
+            &parameters.file_header(CONTRIBUTION_IS_COMPRESSED),
+        )?);
     }
 
     let parameters_for_output = Phase1Parameters::<T>::new(
@@ -71,20 +80,24 @@ pub fn combine<T: Engine + Sync>(
         .read(true)
         .write(true)
         .create_new(true)
-        .open(combined_filename)
-        .expect("unable to create new combined file in this directory");
+        .open(combined_filename)?;
 
     info!("parameters for output: {:?}", parameters_for_output);
 
-    writer
-        .set_len(parameters_for_output.accumulator_size as u64)
-        .expect("must make output file large enough");
+    let output_header = readers.iter().any(|r| r.header.is_some()).then(|| {
+        Phase1Parameters::<T>::new_full(
+            parameters.proving_system,
+            parameters.total_size_in_log2,
+            parameters.batch_size,
+        )
+        .file_header(COMPRESS_NEW_COMBINED)
+    });
+    let offset = header_length(output_header.as_ref());
 
-    let mut writable_map = unsafe {
-        MmapOptions::new()
-            .map_mut(&writer)
-            .expect("unable to create a memory map for output")
-    };
+    writer.set_len((offset + parameters_for_output.accumulator_size) as u64)?;
+
+    let mut writable_map = unsafe { MmapOptions::new().map_mut(&writer)? };
+    write_header(&mut writable_map, combined_filename, output_header.as_ref(), false)?;
 
     let parameters = Phase1Parameters::<T>::new(
         parameters.contribution_mode,
@@ -95,20 +108,18 @@ pub fn combine<T: Engine + Sync>(
         parameters.total_size_in_log2,
         parameters.batch_size,
     );
-    let res = Phase1::aggregation(
+    Phase1::aggregation(
         &readers
             .iter()
-            .map(|r| (r.as_ref(), CONTRIBUTION_IS_COMPRESSED))
+            .map(|r| (r.contents(), CONTRIBUTION_IS_COMPRESSED))
             .collect::<Vec<_>>()
             .as_slice(),
-        (&mut writable_map, COMPRESS_NEW_COMBINED),
+        (&mut writable_map[offset..], COMPRESS_NEW_COMBINED),
         &parameters,
-    );
+    )?;
+    writable_map.flush()?;
 
-    if let Err(e) = res {
-        info!("Combining failed: {}", e);
-        panic!("INVALID CONTRIBUTIONS!!!");
-    } else {
-        info!("Combining succeeded!");
-    }
+    info!("Combining succeeded!");
+
+    Ok(())
 }
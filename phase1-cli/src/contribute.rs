@@ -1,80 +1,84 @@
-use phase1::{Phase1, Phase1Parameters};
-use setup_utils::{calculate_hash, print_hash, write_to_file, BatchExpMode, CheckForCorrectness, UseCompression};
+use phase1::{checkpoint::ComputationCheckpoint, Phase1, Phase1Parameters, PublicKey};
+use setup_utils::{
+    calculate_hash,
+    header_length,
+    print_hash,
+    write_to_file,
+    BatchExpMode,
+    CheckForCorrectness,
+    Error,
+    Result,
+    UseCompression,
+};
 
 use ark_ec::pairing::Pairing as Engine;
 
 use memmap::*;
 use rand::Rng;
-use std::{
-    fs::OpenOptions,
-    io::{Read, Write},
-};
+use std::io::{Read, Write};
 use tracing::info;
 
+use crate::{
+    checkpoint::{open_output, read_checkpoint, remove_checkpoint, save_checkpoints, write_checkpoint},
+    file_headers::{write_header, InputFile},
+};
+
 const COMPRESSED_INPUT: UseCompression = UseCompression::No;
 const COMPRESSED_OUTPUT: UseCompression = UseCompression::Yes;
 
+/// Contributes to the challenge and writes the response.
+///
+/// The progress is regularly saved next to the response file. If the contribution is
+/// interrupted, running it again with `resume` set and the same seed carries on from the last
+/// checkpoint, after checking that the partial response was computed from the same challenge
+/// and key and was not corrupted.
+///
+/// If the challenge starts with a file header, the response gets one too.
 pub fn contribute<T: Engine + Sync>(
     challenge_filename: &str,
     challenge_hash_filename: &str,
     response_filename: &str,
     response_hash_filename: &str,
+    resume: bool,
     check_input_correctness: CheckForCorrectness,
     batch_exp_mode: BatchExpMode,
     parameters: &Phase1Parameters<T>,
     mut rng: impl Rng,
-) {
+) -> Result<()> {
     // Try to load challenge file from disk.
-    let reader = OpenOptions::new()
-        .read(true)
-        .open(challenge_filename)
-        .expect("unable open challenge file");
-    {
-        let metadata = reader
-            .metadata()
-            .expect("unable to get filesystem metadata for challenge file");
-        let expected_challenge_length = match COMPRESSED_INPUT {
-            UseCompression::Yes => parameters.contribution_size,
-            UseCompression::No => parameters.accumulator_size,
-        };
-
-        if metadata.len() != (expected_challenge_length as u64) {
-            panic!(
-                "The size of challenge file should be {}, but it's {}, so something isn't right.",
-                expected_challenge_length,
-                metadata.len()
-            );
-        }
-    }
-
-    let readable_map = unsafe {
-        MmapOptions::new()
-            .map(&reader)
-            .expect("unable to create a memory map for input")
+    let expected_challenge_length = match COMPRESSED_INPUT {
+        UseCompression::Yes => parameters.contribution_size,
+        UseCompression::No => parameters.accumulator_size,
     };
-
-    // Create response file in this directory
-    let writer = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create_new(true)
-        .open(response_filename)
-        .expect("unable to create response file");
+    let challenge = InputFile::open(
+        challenge_filename,
+        expected_challenge_length,
+        &parameters.file_header(COMPRESSED_INPUT),
+    )?;
+    let readable_map = challenge.contents();
 
     let required_output_length = match COMPRESSED_OUTPUT {
         UseCompression::Yes => parameters.contribution_size,
         UseCompression::No => parameters.accumulator_size + parameters.public_key_size,
     };
 
-    writer
-        .set_len(required_output_length as u64)
-        .expect("must make output file large enough");
+    let output_header = challenge.header.map(|_| parameters.file_header(COMPRESSED_OUTPUT));
+    let offset = header_length(output_header.as_ref());
 
-    let mut writable_map = unsafe {
-        MmapOptions::new()
-            .map_mut(&writer)
-            .expect("unable to create a memory map for output")
-    };
+    let resume_from = read_checkpoint(response_filename, resume)?;
+    let writer = open_output(
+        response_filename,
+        resume_from.is_some(),
+        offset + required_output_length,
+    )?;
+
+    let mut writable_map = unsafe { MmapOptions::new().map_mut(&writer)? };
+    write_header(
+        &mut writable_map,
+        response_filename,
+        output_header.as_ref(),
+        resume_from.is_some(),
+    )?;
 
     info!("Calculating previous contribution hash...");
 
@@ -82,26 +86,31 @@ pub fn contribute<T: Engine + Sync>(
         UseCompression::No == COMPRESSED_INPUT,
         "Hashing the compressed file in not yet defined"
     );
-    let current_accumulator_hash = calculate_hash(&readable_map);
+    let current_accumulator_hash = calculate_hash(readable_map);
 
     {
         info!("`challenge` file contains decompressed points and has a hash:");
         print_hash(&current_accumulator_hash);
-        write_to_file(challenge_hash_filename, current_accumulator_hash.as_slice());
-
-        (&mut writable_map[0..])
-            .write_all(current_accumulator_hash.as_slice())
-            .expect("unable to write a challenge hash to mmap");
-
-        writable_map.flush().expect("unable to write hash to response file");
+        write_to_file(challenge_hash_filename, current_accumulator_hash.as_slice())?;
+
+        if resume_from.is_some() {
+            if writable_map[offset..offset + 64] != current_accumulator_hash[..] {
+                return Err(Error::CannotResume(format!(
+                    "{} was based on another challenge",
+                    response_filename
+                )));
+            }
+        } else {
+            (&mut writable_map[offset..]).write_all(current_accumulator_hash.as_slice())?;
+
+            writable_map.flush()?;
+        }
     }
 
     {
         let mut challenge_hash = [0; 64];
-        let mut memory_slice = readable_map.get(0..64).expect("must read point data from file");
-        memory_slice
-            .read_exact(&mut challenge_hash)
-            .expect("couldn't read hash of challenge file from response file");
+        let mut memory_slice = &readable_map[0..64];
+        memory_slice.read_exact(&mut challenge_hash)?;
 
         info!(
             "`challenge` file claims (!!! Must not be blindly trusted) that it was based on the original contribution with a hash:"
@@ -110,37 +119,48 @@ pub fn contribute<T: Engine + Sync>(
     }
 
     // Construct our keypair using the RNG we created above
-    let (public_key, private_key) =
-        Phase1::key_generation(&mut rng, current_accumulator_hash.as_ref()).expect("could not generate keypair");
+    let (public_key, private_key) = Phase1::key_generation(&mut rng, current_accumulator_hash.as_ref())?;
+
+    // The public key is written before the computation, so that a resumed contribution can check
+    // that it is using the same key
+    if resume_from.is_some() {
+        let previous_public_key = PublicKey::<T>::read(&writable_map[offset..], COMPRESSED_OUTPUT, parameters)?;
+        if previous_public_key != public_key {
+            return Err(Error::CannotResume(format!(
+                "{} was computed with another key, was the seed changed?",
+                response_filename
+            )));
+        }
+    } else {
+        public_key.write(&mut writable_map[offset..], COMPRESSED_OUTPUT, parameters)?;
+        writable_map.flush()?;
+        write_checkpoint(&writer, response_filename, &ComputationCheckpoint::default())?;
+    }
 
     // Perform the transformation
     info!("Computing and writing your contribution, this could take a while...");
 
     // this computes a transformation and writes it
-    Phase1::computation(
-        &readable_map,
-        &mut writable_map,
+    Phase1::computation_with_checkpoints(
+        readable_map,
+        &mut writable_map[offset..],
         COMPRESSED_INPUT,
         COMPRESSED_OUTPUT,
         check_input_correctness,
         batch_exp_mode,
         &private_key,
         &parameters,
-    )
-    .expect("must contribute with the key");
+        resume_from.unwrap_or_default(),
+        save_checkpoints(&writer, response_filename),
+    )?;
 
     info!("Finishing writing your contribution to response file...");
 
-    // Write the public key
-    public_key
-        .write(&mut writable_map, COMPRESSED_OUTPUT, &parameters)
-        .expect("unable to write public key");
-
-    writable_map.flush().expect("must flush a memory map");
+    writable_map.flush()?;
 
     // Get the hash of the contribution, so the user can compare later
-    let output_readonly = writable_map.make_read_only().expect("must make a map readonly");
-    let contribution_hash = calculate_hash(&output_readonly);
+    let output_readonly = writable_map.make_read_only()?;
+    let contribution_hash = calculate_hash(&output_readonly[offset..]);
 
     info!(
         "Done!\n\n\
@@ -148,6 +168,9 @@ pub fn contribute<T: Engine + Sync>(
               The BLAKE2b hash of response file is:\n"
     );
     print_hash(&contribution_hash);
-    write_to_file(response_hash_filename, contribution_hash.as_slice());
+    write_to_file(response_hash_filename, contribution_hash.as_slice())?;
+    remove_checkpoint(response_filename)?;
     info!("Thank you for your participation, much appreciated! :)");
+
+    Ok(())
 }
@@ -0,0 +1,345 @@
+//! A client which contributes to a ceremony run by `phase1-coordinator`.
+//!
+//! The client fetches the settings of the ceremony from the coordinator, then repeatedly locks
+//! a chunk, downloads its challenge, contributes to it and uploads the response with its hash,
+//! until it has contributed to every chunk. The same seed is used for all chunks, as required
+//! for the chunks to be combined.
+//!
+//! Every file is kept in a working directory, so that an interrupted run can be started again:
+//! a contribution which was cut short is resumed from its checkpoint, and a response which was
+//! partially uploaded is sent from where the coordinator stopped receiving it.
+use crate::{
+    checkpoint::checkpoint_filename,
+    contribute,
+    CeremonySettings,
+    CeremonyState,
+    LockResponse,
+    UploadStatus,
+    RESPONSE_HASH_HEADER,
+};
+use phase1::Phase1Parameters;
+use setup_utils::{
+    converters::{proving_system_from_str, ContributionMode},
+    derive_rng_from_seed,
+    BatchExpMode,
+    Error,
+    Result,
+    DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
+};
+
+use ark_ec::pairing::Pairing as Engine;
+
+use serde::de::DeserializeOwned;
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+use tracing::info;
+
+/// Talks to the HTTP API of a coordinator
+pub struct CoordinatorClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl CoordinatorClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    pub fn ceremony(&self) -> Result<CeremonyState> {
+        self.json(self.agent.get(&self.endpoint("ceremony")).call())
+    }
+
+    pub fn lock(&self, participant: &str) -> Result<LockResponse> {
+        self.json(self.agent.post(&self.endpoint(&format!("lock/{}", participant))).call())
+    }
+
+    /// Downloads the latest challenge of a chunk to `filename`
+    pub fn download_challenge(&self, chunk_index: usize, filename: &str) -> Result<()> {
+        let path = format!("chunks/{}/challenge", chunk_index);
+        let response = check(self.agent.get(&self.endpoint(&path)).call())?;
+        io::copy(&mut response.into_reader(), &mut File::create(filename)?)?;
+        Ok(())
+    }
+
+    pub fn upload_status(&self, participant: &str, chunk_index: usize) -> Result<UploadStatus> {
+        let path = format!("chunks/{}/upload/{}", chunk_index, participant);
+        self.json(self.agent.get(&self.endpoint(&path)).call())
+    }
+
+    /// Uploads the part of a response starting at `offset`
+    pub fn upload(&self, participant: &str, chunk_index: usize, offset: u64, data: &[u8]) -> Result<UploadStatus> {
+        let path = format!("chunks/{}/upload/{}/{}", chunk_index, participant, offset);
+        self.json(self.agent.post(&self.endpoint(&path)).send_bytes(data))
+    }
+
+    /// Asks the coordinator to verify the response which was uploaded, given its hash
+    pub fn complete_upload(&self, participant: &str, chunk_index: usize, response_hash: &[u8]) -> Result<()> {
+        let path = format!("chunks/{}/complete/{}", chunk_index, participant);
+        let request = self
+            .agent
+            .post(&self.endpoint(&path))
+            .set(RESPONSE_HASH_HEADER, &hex::encode(response_hash));
+        check(request.call())?;
+        Ok(())
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.url, path)
+    }
+
+    fn json<T: DeserializeOwned>(&self, result: std::result::Result<ureq::Response, ureq::Error>) -> Result<T> {
+        Ok(check(result)?.into_json()?)
+    }
+}
+
+/// Turns the failure reports sent by the coordinator into errors
+fn check(result: std::result::Result<ureq::Response, ureq::Error>) -> Result<ureq::Response> {
+    match result {
+        Ok(response) => Ok(response),
+        Err(ureq::Error::Status(status_code, response)) => {
+            let url = response.get_url().to_string();
+            let body = response.into_string().unwrap_or_default();
+            let reason = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|report| report["error"].as_str().map(str::to_string))
+                .unwrap_or(body);
+            Err(Error::CoordinatorError(format!(
+                "{} answered {}: {}",
+                url, status_code, reason
+            )))
+        }
+        Err(e) => Err(io::Error::other(e).into()),
+    }
+}
+
+/// A participant contributing to a ceremony
+pub struct Contributor {
+    pub client: CoordinatorClient,
+    pub participant: String,
+    pub seed: Vec<u8>,
+    /// The directory keeping the challenges and responses
+    pub work_dir: PathBuf,
+    /// The size of the parts responses are uploaded in
+    pub upload_part_size: usize,
+    /// How long to wait before asking for a chunk again when none is ready
+    pub poll_interval: Duration,
+    pub batch_exp_mode: BatchExpMode,
+}
+
+impl Contributor {
+    /// Contributes to every chunk of the ceremony, and returns once the coordinator verified all
+    /// of the responses
+    pub fn run<E: Engine + Sync>(&self, settings: &CeremonySettings) -> Result<()> {
+        fs::create_dir_all(&self.work_dir)?;
+        loop {
+            let lock = self.client.lock(&self.participant)?;
+            match lock.chunk_index {
+                Some(chunk_index) => self.contribute_to_chunk::<E>(settings, lock.round, chunk_index)?,
+                None => {
+                    let state = self.client.ceremony()?;
+                    let done = state
+                        .chunks
+                        .iter()
+                        .all(|chunk| chunk.contributors.get(lock.round) == Some(&self.participant));
+                    if done || lock.finished {
+                        info!("{} contributed to every chunk", self.participant);
+                        return Ok(());
+                    }
+                    info!("No chunk is ready for round {} yet, waiting", lock.round);
+                    thread::sleep(self.poll_interval);
+                }
+            }
+        }
+    }
+
+    fn contribute_to_chunk<E: Engine + Sync>(
+        &self,
+        settings: &CeremonySettings,
+        round: usize,
+        chunk_index: usize,
+    ) -> Result<()> {
+        let filename = |extension: &str| {
+            self.work_dir
+                .join(format!("round_{}_chunk_{}.{}", round, chunk_index, extension))
+                .to_string_lossy()
+                .into_owned()
+        };
+        let response_filename = filename("response");
+        let response_hash_filename = filename("response.hash");
+        // the hash is written once the contribution is complete, before its checkpoint is removed
+        let checkpoint_exists = Path::new(&checkpoint_filename(&response_filename)).exists();
+        let contributed = Path::new(&response_hash_filename).exists() && !checkpoint_exists;
+
+        if contributed {
+            info!("Already contributed to chunk {} of round {}", chunk_index, round);
+        } else {
+            info!("Contributing to chunk {} of round {}", chunk_index, round);
+            self.client.download_challenge(chunk_index, &filename("challenge"))?;
+            let proving_system = proving_system_from_str(&settings.proving_system).map_err(Error::CoordinatorError)?;
+            let parameters = Phase1Parameters::<E>::new_chunk(
+                ContributionMode::Chunked,
+                chunk_index,
+                settings.chunk_size,
+                proving_system,
+                settings.power,
+                settings.batch_size,
+            );
+            // always resuming, which also starts over when a previous run was interrupted before
+            // saving its first checkpoint and left a response without one behind
+            contribute(
+                &filename("challenge"),
+                &filename("challenge.hash"),
+                &response_filename,
+                &response_hash_filename,
+                true,
+                DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
+                self.batch_exp_mode,
+                &parameters,
+                derive_rng_from_seed(&self.seed),
+            )?;
+        }
+
+        self.upload_response(chunk_index, &response_filename)?;
+        let response_hash = fs::read(&response_hash_filename)?;
+        self.client
+            .complete_upload(&self.participant, chunk_index, &response_hash)?;
+        info!("The response to chunk {} of round {} was accepted", chunk_index, round);
+        Ok(())
+    }
+
+    /// Uploads the response in parts, starting from what the coordinator already received. Only
+    /// one part of the response is held in memory at a time.
+    fn upload_response(&self, chunk_index: usize, response_filename: &str) -> Result<()> {
+        let mut response = File::open(response_filename)?;
+        let response_length = response.metadata()?.len();
+
+        let UploadStatus { mut received } = self.client.upload_status(&self.participant, chunk_index)?;
+        if received > response_length {
+            // the coordinator received something else, start over
+            received = 0;
+        }
+        if received > 0 {
+            info!("Resuming the upload of chunk {} at byte {}", chunk_index, received);
+        }
+        response.seek(SeekFrom::Start(received))?;
+        let mut part = Vec::with_capacity(self.upload_part_size.max(1));
+        while received < response_length {
+            part.clear();
+            (&mut response)
+                .take(self.upload_part_size.max(1) as u64)
+                .read_to_end(&mut part)?;
+            if part.is_empty() {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+            received = self
+                .client
+                .upload(&self.participant, chunk_index, received, &part)?
+                .received;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_dir::TestDir, Coordinator, DEFAULT_LOCK_TIMEOUT};
+
+    use ark_bls12_377::Bls12_377;
+
+    use std::sync::{Arc, Mutex};
+    use tiny_http::{Response, Server};
+
+    /// A coordinator which forwards requests to a real one, but fails the first upload which
+    /// doesn't start at 0, as if the connection was lost
+    fn mock_coordinator(
+        mut coordinator: Coordinator<Bls12_377>,
+        server: Arc<Server>,
+        requests: Arc<Mutex<Vec<String>>>,
+    ) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            let mut interrupted = false;
+            for mut request in server.incoming_requests() {
+                let url = request.url().to_string();
+                requests.lock().unwrap().push(url.clone());
+                let segments = url.trim_matches('/').split('/').collect::<Vec<_>>();
+                let continues_upload =
+                    matches!(segments.as_slice(), ["chunks", _, "upload", _, offset] if *offset != "0");
+                if continues_upload && !interrupted {
+                    interrupted = true;
+                    request
+                        .respond(Response::from_string("connection lost").with_status_code(503))
+                        .unwrap();
+                    continue;
+                }
+                let response = coordinator.handle(&mut request);
+                request.respond(response).unwrap();
+            }
+        })
+    }
+
+    #[test]
+    fn test_contributor_resumes_upload() {
+        let dir = TestDir::new("phase1-contributor");
+        let settings = CeremonySettings {
+            curve_kind: "bls12_377".to_string(),
+            proving_system: "groth16".to_string(),
+            power: 2,
+            batch_size: 4,
+            chunk_size: 4,
+            rounds: 1,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+        };
+        let coordinator = Coordinator::<Bls12_377>::new(&dir.join("ceremony"), settings.clone()).unwrap();
+
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let handle = mock_coordinator(coordinator, server.clone(), requests.clone());
+
+        let contributor = Contributor {
+            client: CoordinatorClient::new(&url),
+            participant: "alice".to_string(),
+            seed: b"alice".to_vec(),
+            work_dir: dir.join("alice"),
+            upload_part_size: 128,
+            poll_interval: Duration::from_millis(10),
+            batch_exp_mode: BatchExpMode::Auto,
+        };
+        assert_eq!(contributor.client.ceremony().unwrap().settings, settings);
+
+        // a previous run was interrupted before saving the first checkpoint of its response
+        fs::create_dir_all(&contributor.work_dir).unwrap();
+        let stale_response = contributor.work_dir.join("round_0_chunk_0.response");
+        fs::write(&stale_response, b"stale").unwrap();
+
+        // the second part of the first upload is lost
+        let err = contributor.run::<Bls12_377>(&settings).unwrap_err();
+        assert!(matches!(err, Error::CoordinatorError(_)), "{}", err);
+        assert_ne!(fs::read(&stale_response).unwrap(), b"stale");
+
+        // running again carries on with the upload instead of starting over
+        contributor.run::<Bls12_377>(&settings).unwrap();
+        let state = contributor.client.ceremony().unwrap();
+        assert!(state.finished);
+        assert!(state.chunks.iter().all(|chunk| chunk.contributors == ["alice"]));
+        let requests = requests.lock().unwrap();
+        let first_parts = requests
+            .iter()
+            .filter(|url| url.starts_with("/chunks/0/upload/alice/0"))
+            .count();
+        assert_eq!(first_parts, 1);
+        assert!(requests.contains(&"/chunks/0/upload/alice".to_string()));
+
+        server.unblock();
+        handle.join().unwrap();
+    }
+}
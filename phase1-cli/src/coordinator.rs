@@ -0,0 +1,902 @@
+//! A coordinator which drives a chunked Phase 1 ceremony and exposes it over a small HTTP API.
+//!
+//! Every participant contributes once to every chunk, in the order they joined the ceremony:
+//! the contribution of the `r`-th participant to a chunk is round `r` of that chunk. Since the
+//! chunks are combined at the end, a participant must use the same secret for all of them,
+//! which `contribute` does when it is given the same seed. Participants lock a chunk, download
+//! its challenge and upload their response, which is verified with
+//! `transform_pok_and_correctness` and becomes the challenge of the next round. Different
+//! participants can work on different chunks at the same time. After the last round, the
+//! responses are combined and checked with `transform_ratios`.
+//!
+//! The state of the ceremony is kept in a directory, so that the coordinator can be restarted:
+//!
+//! - `ceremony.json`: the settings of the ceremony and its progress
+//! - `round_<r>/chunk_<i>.challenge`: the challenge of chunk `i` in round `r`, followed by the
+//!   verified response `round_<r>/chunk_<i>.response`
+//! - `transcript_<i>`: the transcript of the contributions to chunk `i`
+//! - `response_list` and `combined`: the responses of the last round and their combination
+//!
+//! The API is:
+//!
+//! | Request                                           | Effect                                                    |
+//! |---------------------------------------------------|-----------------------------------------------------------|
+//! | `GET /ceremony`                                   | returns the `CeremonyState` as JSON                       |
+//! | `POST /lock/<participant>`                        | locks a chunk for the participant, see `LockResponse`     |
+//! | `GET /chunks/<i>/challenge`                       | downloads the latest challenge of chunk `i`               |
+//! | `POST /chunks/<i>/response/<participant>`         | uploads and verifies the response of the lock holder      |
+//! | `GET /chunks/<i>/upload/<participant>`            | returns the `UploadStatus` of a response uploaded in parts |
+//! | `POST /chunks/<i>/upload/<participant>/<offset>`  | uploads the part of a response starting at `offset`       |
+//! | `POST /chunks/<i>/complete/<participant>`         | verifies a response uploaded in parts                     |
+//!
+//! Uploading a response in parts allows an interrupted upload to be resumed. Responses can be
+//! sent along with their hex encoded hash in the `X-Response-Hash` header, which the coordinator
+//! checks before verifying them. A lock whose holder hasn't uploaded anything for
+//! `lock_timeout` seconds expires, and the chunk can be locked by the next participant of the
+//! round, who may be the same one.
+//!
+//! Failed requests are answered with the JSON `FailureReport` of the error, with the status 400
+//! if the response was rejected, 404 for unknown requests, 409 for requests which conflict with
+//! the state of the ceremony and 500 for other errors.
+use crate::{checkpoint::checkpoint_filename, combine, new_challenge, transform_pok_and_correctness, transform_ratios};
+use phase1::Phase1Parameters;
+use setup_utils::{
+    calculate_hash,
+    converters::{proving_system_from_str, ContributionMode, ProvingSystem},
+    Error,
+    Result,
+    SubgroupCheckMode,
+    DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+    DEFAULT_VERIFY_CHECK_OUTPUT_CORRECTNESS,
+};
+
+use ark_ec::pairing::Pairing as Engine;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Cursor, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::{error, info};
+
+const STATE_FILENAME: &str = "ceremony.json";
+
+/// The header carrying the hex encoded BLAKE2b hash of an uploaded response
+pub const RESPONSE_HASH_HEADER: &str = "X-Response-Hash";
+
+/// Locks expire after a day without progress by default
+pub const DEFAULT_LOCK_TIMEOUT: u64 = 24 * 60 * 60;
+
+/// The settings a ceremony is created with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeremonySettings {
+    pub curve_kind: String,
+    pub proving_system: String,
+    pub power: usize,
+    pub batch_size: usize,
+    pub chunk_size: usize,
+    /// The number of participants, each of which contributes once to every chunk
+    pub rounds: usize,
+    /// The number of seconds after which a lock expires if its holder made no progress, or 0 for
+    /// locks which never expire
+    #[serde(default = "default_lock_timeout")]
+    pub lock_timeout: u64,
+}
+
+fn default_lock_timeout() -> u64 {
+    DEFAULT_LOCK_TIMEOUT
+}
+
+/// The progress of a single chunk
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChunkState {
+    /// The participant currently contributing to the chunk
+    pub locked_by: Option<String>,
+    /// When the lock was taken or the last part of the response was uploaded, in seconds since
+    /// the Unix epoch
+    #[serde(default)]
+    pub locked_at: Option<u64>,
+    /// The participants whose contributions to the chunk were verified, one per round
+    pub contributors: Vec<String>,
+}
+
+/// The settings and the progress of a ceremony, as kept in its state directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeremonyState {
+    pub settings: CeremonySettings,
+    /// The participants in the order they joined, the participant at index `r` contributes to
+    /// round `r` of every chunk
+    pub participants: Vec<String>,
+    pub chunks: Vec<ChunkState>,
+    pub finished: bool,
+}
+
+/// The answer to a lock request. `round` is the round the participant contributes to, and
+/// `chunk_index` is `None` when no chunk is ready for that round, in which case the participant
+/// should ask again later, unless the ceremony is `finished`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockResponse {
+    pub round: usize,
+    pub chunk_index: Option<usize>,
+    pub finished: bool,
+}
+
+/// How much of a response was uploaded
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UploadStatus {
+    pub received: u64,
+}
+
+/// Reads the state of the ceremony kept in `state_dir`
+pub fn read_ceremony_state(state_dir: &Path) -> Result<CeremonyState> {
+    let contents = fs::read(state_dir.join(STATE_FILENAME))?;
+    serde_json::from_slice(&contents).map_err(|e| {
+        Error::CannotResume(format!(
+            "{} is not a valid ceremony state: {}",
+            state_dir.join(STATE_FILENAME).display(),
+            e
+        ))
+    })
+}
+
+pub struct Coordinator<E: Engine> {
+    state_dir: PathBuf,
+    state: CeremonyState,
+    parameters: Phase1Parameters<E>,
+}
+
+impl<E: Engine + Sync> Coordinator<E> {
+    /// Creates a ceremony in `state_dir`, with an initial challenge for every chunk
+    pub fn new(state_dir: &Path, settings: CeremonySettings) -> Result<Self> {
+        let parameters = chunk_parameters(&settings)?;
+        let num_chunks = num_chunks(&parameters);
+        if settings.rounds == 0 {
+            return Err(Error::CoordinatorError(
+                "a ceremony needs at least one round".to_string(),
+            ));
+        }
+
+        let coordinator = Self {
+            state_dir: state_dir.to_path_buf(),
+            state: CeremonyState {
+                settings,
+                participants: vec![],
+                chunks: vec![ChunkState::default(); num_chunks],
+                finished: false,
+            },
+            parameters,
+        };
+
+        fs::create_dir_all(state_dir)?;
+        fs::create_dir(coordinator.round_dir(0))?;
+        for chunk_index in 0..num_chunks {
+            new_challenge(
+                &coordinator.chunk_filename(0, chunk_index, "challenge"),
+                &coordinator.chunk_filename(0, chunk_index, "challenge.hash"),
+                None,
+                false,
+                &coordinator.chunk(chunk_index),
+            )?;
+        }
+        coordinator.save()?;
+        info!(
+            "Created a ceremony with {} chunks in {}",
+            num_chunks,
+            state_dir.display()
+        );
+
+        Ok(coordinator)
+    }
+
+    /// Opens the ceremony kept in `state_dir`
+    pub fn open(state_dir: &Path) -> Result<Self> {
+        let state = read_ceremony_state(state_dir)?;
+        let parameters = chunk_parameters(&state.settings)?;
+        if state.chunks.len() != num_chunks(&parameters) {
+            return Err(Error::CannotResume(format!(
+                "the ceremony should have {} chunks, but its state has {}",
+                num_chunks(&parameters),
+                state.chunks.len()
+            )));
+        }
+        let mut coordinator = Self {
+            state_dir: state_dir.to_path_buf(),
+            state,
+            parameters,
+        };
+        coordinator.try_finish()?;
+        Ok(coordinator)
+    }
+
+    pub fn state(&self) -> &CeremonyState {
+        &self.state
+    }
+
+    /// Locks a chunk for `participant`, who joins the ceremony if they haven't yet and a round
+    /// is left. A participant holds at most one lock, so asking again returns the chunk they
+    /// already locked, unless the lock expired.
+    pub fn lock(&mut self, participant: &str) -> Result<LockResponse> {
+        self.release_expired_locks()?;
+        let round = match self.state.participants.iter().position(|p| p == participant) {
+            Some(round) => round,
+            None if self.state.participants.len() < self.state.settings.rounds => {
+                self.state.participants.push(participant.to_string());
+                self.save()?;
+                info!(
+                    "{} joined the ceremony and will contribute to round {}",
+                    participant,
+                    self.state.participants.len() - 1
+                );
+                self.state.participants.len() - 1
+            }
+            None => {
+                return Err(Error::CoordinatorError(format!(
+                    "{} cannot join the ceremony, all of its {} rounds are taken",
+                    participant, self.state.settings.rounds
+                )))
+            }
+        };
+
+        let chunk_index = if let Some(chunk_index) = self.locked_chunk(participant) {
+            Some(chunk_index)
+        } else {
+            let available = self
+                .state
+                .chunks
+                .iter()
+                .position(|chunk| chunk.locked_by.is_none() && chunk.contributors.len() == round);
+            if let Some(chunk_index) = available {
+                let chunk = &mut self.state.chunks[chunk_index];
+                chunk.locked_by = Some(participant.to_string());
+                chunk.locked_at = Some(now());
+                self.save()?;
+                info!("Chunk {} of round {} was locked by {}", chunk_index, round, participant);
+            }
+            available
+        };
+
+        Ok(LockResponse {
+            round,
+            chunk_index,
+            finished: self.state.finished,
+        })
+    }
+
+    /// Returns the latest challenge file of a chunk
+    pub fn challenge_filename(&self, chunk_index: usize) -> Result<String> {
+        self.check_chunk_index(chunk_index)?;
+        let round = self.state.chunks[chunk_index].contributors.len();
+        Ok(self.chunk_filename(round, chunk_index, "challenge"))
+    }
+
+    /// Returns how many bytes of their response to a chunk `participant` has uploaded so far
+    pub fn upload_status(&self, participant: &str, chunk_index: usize) -> Result<UploadStatus> {
+        self.check_lock(participant, chunk_index)?;
+        let received = match fs::metadata(self.upload_filename(chunk_index)) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(UploadStatus { received })
+    }
+
+    /// Writes a part of the response of `participant` to a chunk they locked. The part must start
+    /// where the previous one ended, or at 0 to start the upload over.
+    pub fn upload(&mut self, participant: &str, chunk_index: usize, offset: u64, data: &[u8]) -> Result<UploadStatus> {
+        self.release_expired_locks()?;
+        let UploadStatus { received } = self.upload_status(participant, chunk_index)?;
+        if offset != 0 && offset != received {
+            return Err(Error::CoordinatorError(format!(
+                "the upload of {} to chunk {} should continue at {}, not {}",
+                participant, chunk_index, received, offset
+            )));
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(offset != 0)
+            .truncate(offset == 0)
+            .open(self.upload_filename(chunk_index))?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        // the upload makes progress, so the lock is kept
+        self.state.chunks[chunk_index].locked_at = Some(now());
+        self.save()?;
+        Ok(UploadStatus {
+            received: offset + data.len() as u64,
+        })
+    }
+
+    /// Uploads the response of `participant` to a chunk they locked at once, and verifies it
+    pub fn submit(
+        &mut self,
+        participant: &str,
+        chunk_index: usize,
+        response: &[u8],
+        response_hash: Option<&[u8]>,
+    ) -> Result<()> {
+        self.upload(participant, chunk_index, 0, response)?;
+        self.complete_upload(participant, chunk_index, response_hash)
+    }
+
+    /// Verifies the uploaded response of `participant` to the challenge of a chunk they locked,
+    /// and turns it into the challenge of the next round. If the participant sent the hash of
+    /// their response, it is checked first. The chunk is unlocked whether the response is valid or
+    /// not. Once the last response is accepted, the ceremony is finished; if that fails, the
+    /// error is logged and finishing is tried again when the coordinator is opened.
+    pub fn complete_upload(
+        &mut self,
+        participant: &str,
+        chunk_index: usize,
+        response_hash: Option<&[u8]>,
+    ) -> Result<()> {
+        self.release_expired_locks()?;
+        self.check_lock(participant, chunk_index)?;
+        let upload_filename = self.upload_filename(chunk_index);
+        if !Path::new(&upload_filename).exists() {
+            return Err(Error::CoordinatorError(format!(
+                "{} has not uploaded a response to chunk {}",
+                participant, chunk_index
+            )));
+        }
+
+        let round = self.state.chunks[chunk_index].contributors.len();
+        let response_filename = self.chunk_filename(round, chunk_index, "response");
+        let new_challenge_filename = self.chunk_filename(round + 1, chunk_index, "challenge");
+        fs::create_dir_all(self.round_dir(round + 1))?;
+        // a previous attempt may have been interrupted during the verification
+        remove_if_exists(&new_challenge_filename)?;
+        remove_if_exists(&checkpoint_filename(&new_challenge_filename))?;
+        fs::rename(&upload_filename, &response_filename)?;
+
+        let result = check_response_hash(&response_filename, response_hash).and_then(|_| {
+            transform_pok_and_correctness(
+                &self.chunk_filename(round, chunk_index, "challenge"),
+                &self.chunk_filename(round, chunk_index, "challenge.hash"),
+                DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+                &response_filename,
+                &self.chunk_filename(round, chunk_index, "response.hash"),
+                DEFAULT_VERIFY_CHECK_OUTPUT_CORRECTNESS,
+                &new_challenge_filename,
+                &self.chunk_filename(round + 1, chunk_index, "challenge.hash"),
+                Some(&self.path(format!("transcript_{}", chunk_index))),
+                false,
+                SubgroupCheckMode::Auto,
+                true,
+                &self.chunk(chunk_index),
+            )
+        });
+
+        let chunk = &mut self.state.chunks[chunk_index];
+        chunk.locked_by = None;
+        chunk.locked_at = None;
+        if let Err(e) = result {
+            error!(
+                "The response of {} to chunk {} of round {} was rejected: {}",
+                participant, chunk_index, round, e
+            );
+            remove_if_exists(&response_filename)?;
+            remove_if_exists(&new_challenge_filename)?;
+            remove_if_exists(&checkpoint_filename(&new_challenge_filename))?;
+            self.save()?;
+            return Err(e.with_chunk_index(chunk_index));
+        }
+        chunk.contributors.push(participant.to_string());
+        self.save()?;
+        info!(
+            "The response of {} to chunk {} of round {} was verified",
+            participant, chunk_index, round
+        );
+
+        self.try_finish()
+    }
+
+    /// Releases the lock on a chunk, e.g. when its holder is known to be gone. The coordinator
+    /// must not be serving the ceremony meanwhile, since it keeps the state in memory.
+    pub fn unlock(&mut self, chunk_index: usize) -> Result<()> {
+        self.check_chunk_index(chunk_index)?;
+        if let Some(participant) = self.state.chunks[chunk_index].locked_by.clone() {
+            self.release_lock(chunk_index)?;
+            self.save()?;
+            info!("The lock of {} on chunk {} was released", participant, chunk_index);
+        }
+        Ok(())
+    }
+
+    /// Finishes the ceremony if every chunk received all of its contributions. A failure is
+    /// logged instead of returned, since the accepted responses are already saved.
+    fn try_finish(&mut self) -> Result<()> {
+        let rounds = self.state.settings.rounds;
+        if self.state.finished || !self.state.chunks.iter().all(|chunk| chunk.contributors.len() == rounds) {
+            return Ok(());
+        }
+        match self.finish() {
+            Ok(()) => self.save(),
+            Err(e) => {
+                error!("The ceremony could not be finished: {}", e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Combines the responses of the last round and verifies the combined accumulator
+    fn finish(&mut self) -> Result<()> {
+        let last_round = self.state.settings.rounds - 1;
+        let response_list_filename = self.path("response_list");
+        let response_list = (0..self.state.chunks.len())
+            .map(|chunk_index| format!("{}\n", self.chunk_filename(last_round, chunk_index, "response")))
+            .collect::<String>();
+        fs::write(&response_list_filename, response_list)?;
+
+        let combined_filename = self.path("combined");
+        remove_if_exists(&combined_filename)?;
+        combine(&response_list_filename, &combined_filename, &self.parameters)?;
+
+        let settings = &self.state.settings;
+        let full_parameters =
+            Phase1Parameters::<E>::new_full(self.parameters.proving_system, settings.power, settings.batch_size);
+        transform_ratios(
+            &combined_filename,
+            DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+            &full_parameters,
+        )?;
+
+        self.state.finished = true;
+        info!(
+            "The ceremony is finished, the combined accumulator is {}",
+            combined_filename
+        );
+        Ok(())
+    }
+
+    /// Answers a request to the API
+    pub fn handle(&mut self, request: &mut Request) -> Response<Cursor<Vec<u8>>> {
+        let url = request.url().to_string();
+        let segments = url.trim_matches('/').split('/').collect::<Vec<_>>();
+        let result = match (request.method(), segments.as_slice()) {
+            (Method::Get, ["ceremony"]) => json_response(&self.state),
+            (Method::Post, ["lock", participant]) => self.lock(participant).and_then(|lock| json_response(&lock)),
+            (Method::Get, ["chunks", chunk_index, "challenge"]) => parse_chunk_index(chunk_index)
+                .and_then(|chunk_index| self.challenge_filename(chunk_index))
+                .and_then(|filename| Ok(Response::from_data(fs::read(filename)?))),
+            (Method::Get, ["chunks", chunk_index, "upload", participant]) => parse_chunk_index(chunk_index)
+                .and_then(|chunk_index| self.upload_status(participant, chunk_index))
+                .and_then(|status| json_response(&status)),
+            (Method::Post, ["chunks", chunk_index, "upload", participant, offset]) => {
+                let mut data = vec![];
+                parse_chunk_index(chunk_index)
+                    .and_then(|chunk_index| {
+                        let offset = offset
+                            .parse()
+                            .map_err(|_| Error::CoordinatorError(format!("{} is not an offset", offset)))?;
+                        request.as_reader().read_to_end(&mut data)?;
+                        self.upload(participant, chunk_index, offset, &data)
+                    })
+                    .and_then(|status| json_response(&status))
+            }
+            (Method::Post, ["chunks", chunk_index, "complete", participant]) => parse_chunk_index(chunk_index)
+                .and_then(|chunk_index| {
+                    let response_hash = response_hash_header(request)?;
+                    self.complete_upload(participant, chunk_index, response_hash.as_deref())
+                })
+                .and_then(|_| json_response(&self.state)),
+            (Method::Post, ["chunks", chunk_index, "response", participant]) => {
+                let mut response = vec![];
+                parse_chunk_index(chunk_index)
+                    .and_then(|chunk_index| {
+                        let response_hash = response_hash_header(request)?;
+                        request.as_reader().read_to_end(&mut response)?;
+                        self.submit(participant, chunk_index, &response, response_hash.as_deref())
+                    })
+                    .and_then(|_| json_response(&self.state))
+            }
+            _ => {
+                return Response::from_string(format!("unknown request {} {}", request.method(), url))
+                    .with_status_code(404)
+            }
+        };
+
+        result.unwrap_or_else(|e| {
+            let status_code = match e {
+                Error::CoordinatorError(_) => 409,
+                Error::IoError(_) => 500,
+                _ => 400,
+            };
+            json_response(&e.report())
+                .unwrap_or_else(|_| Response::from_string(e.to_string()))
+                .with_status_code(status_code)
+        })
+    }
+
+    /// Releases the locks whose holders made no progress for `lock_timeout` seconds
+    fn release_expired_locks(&mut self) -> Result<()> {
+        let lock_timeout = self.state.settings.lock_timeout;
+        if lock_timeout == 0 {
+            return Ok(());
+        }
+        let now = now();
+        let expired = (0..self.state.chunks.len())
+            .filter(|&chunk_index| {
+                let chunk = &self.state.chunks[chunk_index];
+                chunk.locked_by.is_some() && chunk.locked_at.unwrap_or(0).saturating_add(lock_timeout) <= now
+            })
+            .collect::<Vec<_>>();
+        if expired.is_empty() {
+            return Ok(());
+        }
+        for chunk_index in expired {
+            info!(
+                "The lock of {} on chunk {} expired",
+                self.state.chunks[chunk_index].locked_by.as_deref().unwrap_or_default(),
+                chunk_index
+            );
+            self.release_lock(chunk_index)?;
+        }
+        self.save()
+    }
+
+    /// Unlocks a chunk and drops the part of the response uploaded so far, which the next
+    /// holder of the lock shouldn't resume
+    fn release_lock(&mut self, chunk_index: usize) -> Result<()> {
+        remove_if_exists(&self.upload_filename(chunk_index))?;
+        let chunk = &mut self.state.chunks[chunk_index];
+        chunk.locked_by = None;
+        chunk.locked_at = None;
+        Ok(())
+    }
+
+    fn check_lock(&self, participant: &str, chunk_index: usize) -> Result<()> {
+        self.check_chunk_index(chunk_index)?;
+        if self.locked_chunk(participant) != Some(chunk_index) {
+            return Err(Error::CoordinatorError(format!(
+                "{} does not hold the lock on chunk {}",
+                participant, chunk_index
+            )));
+        }
+        Ok(())
+    }
+
+    fn locked_chunk(&self, participant: &str) -> Option<usize> {
+        self.state
+            .chunks
+            .iter()
+            .position(|chunk| chunk.locked_by.as_deref() == Some(participant))
+    }
+
+    fn check_chunk_index(&self, chunk_index: usize) -> Result<()> {
+        if chunk_index >= self.state.chunks.len() {
+            return Err(Error::CoordinatorError(format!(
+                "chunk {} does not exist, the ceremony has {} chunks",
+                chunk_index,
+                self.state.chunks.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn chunk(&self, chunk_index: usize) -> Phase1Parameters<E> {
+        self.parameters
+            .into_chunk_parameters(ContributionMode::Chunked, chunk_index, self.parameters.chunk_size)
+    }
+
+    fn save(&self) -> Result<()> {
+        // write to a temporary file first, so that the state is never left half written
+        let filename = self.path(STATE_FILENAME);
+        let tmp_filename = format!("{}.tmp", filename);
+        let contents = serde_json::to_vec_pretty(&self.state).map_err(io::Error::from)?;
+        fs::write(&tmp_filename, contents)?;
+        fs::rename(&tmp_filename, &filename)?;
+        Ok(())
+    }
+
+    /// The response to a chunk is uploaded next to its challenge, and moved once it is complete
+    fn upload_filename(&self, chunk_index: usize) -> String {
+        let round = self.state.chunks[chunk_index].contributors.len();
+        self.chunk_filename(round, chunk_index, "response.upload")
+    }
+
+    fn round_dir(&self, round: usize) -> String {
+        self.path(format!("round_{}", round))
+    }
+
+    fn chunk_filename(&self, round: usize, chunk_index: usize, extension: &str) -> String {
+        format!("{}/chunk_{}.{}", self.round_dir(round), chunk_index, extension)
+    }
+
+    fn path(&self, filename: impl AsRef<Path>) -> String {
+        self.state_dir.join(filename).to_string_lossy().into_owned()
+    }
+}
+
+/// Serves the API of the coordinator, until the server is unblocked
+pub fn serve<E: Engine + Sync>(coordinator: &mut Coordinator<E>, server: &Server) -> Result<()> {
+    for mut request in server.incoming_requests() {
+        info!("{} {}", request.method(), request.url());
+        let response = coordinator.handle(&mut request);
+        request.respond(response)?;
+    }
+    Ok(())
+}
+
+/// The parameters of the first chunk of the ceremony
+fn chunk_parameters<E: Engine>(settings: &CeremonySettings) -> Result<Phase1Parameters<E>> {
+    let proving_system = proving_system_from_str(&settings.proving_system).map_err(Error::CoordinatorError)?;
+    Ok(Phase1Parameters::<E>::new_chunk(
+        ContributionMode::Chunked,
+        0,
+        settings.chunk_size,
+        proving_system,
+        settings.power,
+        settings.batch_size,
+    ))
+}
+
+fn num_chunks<E: Engine>(parameters: &Phase1Parameters<E>) -> usize {
+    let powers_length_for_proving_system = match parameters.proving_system {
+        ProvingSystem::Groth16 => parameters.powers_g1_length,
+        ProvingSystem::Marlin => parameters.powers_length,
+    };
+    powers_length_for_proving_system.div_ceil(parameters.chunk_size)
+}
+
+fn parse_chunk_index(chunk_index: &str) -> Result<usize> {
+    chunk_index
+        .parse()
+        .map_err(|_| Error::CoordinatorError(format!("{} is not a chunk index", chunk_index)))
+}
+
+/// Reads the hex encoded hash of the response the participant may have sent along
+fn response_hash_header(request: &Request) -> Result<Option<Vec<u8>>> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(RESPONSE_HASH_HEADER))
+        .map(|header| {
+            hex::decode(header.value.as_str())
+                .map_err(|_| Error::InvalidHash(format!("the {} header is not a hex string", RESPONSE_HASH_HEADER)))
+        })
+        .transpose()
+}
+
+fn check_response_hash(response_filename: &str, response_hash: Option<&[u8]>) -> Result<()> {
+    if let Some(response_hash) = response_hash {
+        let actual_hash = calculate_hash(&fs::read(response_filename)?);
+        if actual_hash.as_slice() != response_hash {
+            return Err(Error::InvalidHash(format!(
+                "the uploaded response has the hash {}, but the participant computed {}",
+                hex::encode(actual_hash),
+                hex::encode(response_hash)
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn json_response(value: &impl Serialize) -> Result<Response<Cursor<Vec<u8>>>> {
+    let body = serde_json::to_vec(value).map_err(io::Error::from)?;
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("valid header");
+    Ok(Response::from_data(body).with_header(content_type))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn remove_if_exists(filename: &str) -> Result<()> {
+    match fs::remove_file(filename) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, test_dir::TestDir};
+    use setup_utils::{derive_rng_from_seed, BatchExpMode, CheckForCorrectness};
+
+    use ark_bls12_377::Bls12_377;
+
+    use std::{io::Read, sync::Arc, thread};
+
+    fn contribute_to_chunk(url: &str, dir: &Path, participant: &str, chunk_index: usize) -> Vec<u8> {
+        let mut challenge = vec![];
+        ureq::get(&format!("{}/chunks/{}/challenge", url, chunk_index))
+            .call()
+            .unwrap()
+            .into_reader()
+            .read_to_end(&mut challenge)
+            .unwrap();
+        contribute_to_challenge(&challenge, dir, participant, chunk_index)
+    }
+
+    fn contribute_to_challenge(challenge: &[u8], dir: &Path, participant: &str, chunk_index: usize) -> Vec<u8> {
+        let filename = |name: &str| {
+            dir.join(format!("{}_{}.{}", participant, chunk_index, name))
+                .to_string_lossy()
+                .into_owned()
+        };
+        fs::write(filename("challenge"), challenge).unwrap();
+        let parameters = Phase1Parameters::<Bls12_377>::new_chunk(
+            ContributionMode::Chunked,
+            chunk_index,
+            4,
+            ProvingSystem::Groth16,
+            2,
+            4,
+        );
+        contribute(
+            &filename("challenge"),
+            &filename("challenge.hash"),
+            &filename("response"),
+            &filename("response.hash"),
+            false,
+            CheckForCorrectness::No,
+            BatchExpMode::Auto,
+            &parameters,
+            // a participant uses the same secret for every chunk
+            derive_rng_from_seed(participant.as_bytes()),
+        )
+        .unwrap();
+        fs::read(filename("response")).unwrap()
+    }
+
+    fn lock(url: &str, participant: &str) -> std::result::Result<LockResponse, u16> {
+        match ureq::post(&format!("{}/lock/{}", url, participant)).call() {
+            Ok(response) => Ok(serde_json::from_reader(response.into_reader()).unwrap()),
+            Err(ureq::Error::Status(status_code, _)) => Err(status_code),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    fn upload(url: &str, participant: &str, chunk_index: usize, response: &[u8]) -> std::result::Result<(), u16> {
+        upload_with_hash(url, participant, chunk_index, response, &calculate_hash(response))
+    }
+
+    fn upload_with_hash(
+        url: &str,
+        participant: &str,
+        chunk_index: usize,
+        response: &[u8],
+        response_hash: &[u8],
+    ) -> std::result::Result<(), u16> {
+        let request = ureq::post(&format!("{}/chunks/{}/response/{}", url, chunk_index, participant))
+            .set(RESPONSE_HASH_HEADER, &hex::encode(response_hash));
+        match request.send_bytes(response) {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(status_code, _)) => Err(status_code),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn test_coordinator_loopback() {
+        let dir = TestDir::new("phase1-coordinator");
+        let state_dir = dir.join("ceremony");
+        let settings = CeremonySettings {
+            curve_kind: "bls12_377".to_string(),
+            proving_system: "groth16".to_string(),
+            power: 2,
+            batch_size: 4,
+            chunk_size: 4,
+            rounds: 2,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+        };
+        let mut coordinator = Coordinator::<Bls12_377>::new(&state_dir, settings.clone()).unwrap();
+        assert_eq!(coordinator.state().chunks.len(), 2);
+
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let handle = thread::spawn({
+            let server = server.clone();
+            move || serve(&mut coordinator, &server)
+        });
+
+        // alice joins first and contributes to the first round, bob has to wait for her
+        let alice = lock(&url, "alice").unwrap();
+        assert_eq!((alice.round, alice.chunk_index), (0, Some(0)));
+        assert_eq!(lock(&url, "alice"), Ok(alice));
+        let bob = lock(&url, "bob").unwrap();
+        assert_eq!((bob.round, bob.chunk_index), (1, None));
+        assert_eq!(lock(&url, "carol"), Err(409));
+
+        // only the participant holding the lock can upload, and invalid responses are rejected
+        let response = contribute_to_chunk(&url, &dir, "alice", 0);
+        assert_eq!(upload(&url, "bob", 0, &response), Err(409));
+        let mut corrupted = response.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 1;
+        assert_eq!(upload(&url, "alice", 0, &corrupted), Err(400));
+        assert_eq!(lock(&url, "alice").unwrap().chunk_index, Some(0));
+        assert_eq!(upload_with_hash(&url, "alice", 0, &response, &[0; 64]), Err(400));
+        assert_eq!(lock(&url, "alice").unwrap().chunk_index, Some(0));
+        upload(&url, "alice", 0, &response).unwrap();
+
+        // bob contributes to the first chunk while alice contributes to the second one
+        assert_eq!(lock(&url, "bob").unwrap().chunk_index, Some(0));
+        assert_eq!(lock(&url, "alice").unwrap().chunk_index, Some(1));
+        let response = contribute_to_chunk(&url, &dir, "bob", 0);
+        upload(&url, "bob", 0, &response).unwrap();
+        let response = contribute_to_chunk(&url, &dir, "alice", 1);
+        upload(&url, "alice", 1, &response).unwrap();
+        assert_eq!(lock(&url, "bob").unwrap().chunk_index, Some(1));
+        let response = contribute_to_chunk(&url, &dir, "bob", 1);
+        upload(&url, "bob", 1, &response).unwrap();
+
+        let state: CeremonyState =
+            serde_json::from_reader(ureq::get(&format!("{}/ceremony", url)).call().unwrap().into_reader()).unwrap();
+        assert!(state.finished);
+        assert_eq!(state.participants, ["alice", "bob"]);
+        assert!(state.chunks.iter().all(|chunk| chunk.contributors == ["alice", "bob"]));
+        let alice = lock(&url, "alice").unwrap();
+        assert!(alice.finished);
+        assert_eq!(alice.chunk_index, None);
+        assert!(state_dir.join("combined").exists());
+
+        server.unblock();
+        handle.join().unwrap().unwrap();
+
+        // the state survives a restart
+        let coordinator = Coordinator::<Bls12_377>::open(&state_dir).unwrap();
+        assert_eq!(coordinator.state(), &state);
+        assert_eq!(coordinator.state().settings, settings);
+    }
+
+    #[test]
+    fn test_coordinator_lock_expiry_and_finish_retry() {
+        let dir = TestDir::new("phase1-coordinator-expiry");
+        let state_dir = dir.join("ceremony");
+        let settings = CeremonySettings {
+            curve_kind: "bls12_377".to_string(),
+            proving_system: "groth16".to_string(),
+            power: 2,
+            batch_size: 4,
+            chunk_size: 4,
+            rounds: 1,
+            lock_timeout: 60,
+        };
+        let mut coordinator = Coordinator::<Bls12_377>::new(&state_dir, settings).unwrap();
+        let contribute = |coordinator: &Coordinator<Bls12_377>, chunk_index: usize| {
+            let challenge = fs::read(coordinator.challenge_filename(chunk_index).unwrap()).unwrap();
+            contribute_to_challenge(&challenge, &dir, "alice", chunk_index)
+        };
+
+        // a lock without progress expires, along with the part of the response uploaded so far
+        assert_eq!(coordinator.lock("alice").unwrap().chunk_index, Some(0));
+        coordinator.upload("alice", 0, 0, b"part").unwrap();
+        coordinator.state.chunks[0].locked_at = Some(now() - 61);
+        let err = coordinator.upload("alice", 0, 4, b"part").unwrap_err();
+        assert!(matches!(err, Error::CoordinatorError(_)), "{}", err);
+        assert_eq!(coordinator.state().chunks[0].locked_by, None);
+        assert_eq!(coordinator.lock("alice").unwrap().chunk_index, Some(0));
+        assert_eq!(coordinator.upload_status("alice", 0).unwrap().received, 0);
+
+        // the lock can be released by hand
+        coordinator.unlock(0).unwrap();
+        assert_eq!(coordinator.state().chunks[0].locked_by, None);
+        assert_eq!(coordinator.lock("alice").unwrap().chunk_index, Some(0));
+        let response = contribute(&coordinator, 0);
+        coordinator.submit("alice", 0, &response, None).unwrap();
+
+        // the last response is accepted and saved even though the ceremony can't be finished
+        assert_eq!(coordinator.lock("alice").unwrap().chunk_index, Some(1));
+        fs::create_dir(state_dir.join("combined")).unwrap();
+        let response = contribute(&coordinator, 1);
+        coordinator.submit("alice", 1, &response, None).unwrap();
+        let state = read_ceremony_state(&state_dir).unwrap();
+        assert!(!state.finished);
+        assert!(state.chunks.iter().all(|chunk| chunk.contributors == ["alice"]));
+
+        // finishing is tried again when the coordinator is opened
+        fs::remove_dir(state_dir.join("combined")).unwrap();
+        let coordinator = Coordinator::<Bls12_377>::open(&state_dir).unwrap();
+        assert!(coordinator.state().finished);
+        assert!(read_ceremony_state(&state_dir).unwrap().finished);
+    }
+}
@@ -0,0 +1,154 @@
+use setup_utils::{header_length, split_file_header, Error, FileHeader, Result, FILE_HEADER_SIZE};
+
+use memmap::*;
+use std::fs::OpenOptions;
+
+/// An input file mapped to memory, which may start with a file header
+pub(crate) struct InputFile {
+    map: Mmap,
+    pub(crate) header: Option<FileHeader>,
+}
+
+impl InputFile {
+    /// Maps an input file to memory. If it starts with a header, the header must match `expected`.
+    /// The rest of the file must be `expected_length` bytes long.
+    pub(crate) fn open(filename: &str, expected_length: usize, expected: &FileHeader) -> Result<Self> {
+        let reader = OpenOptions::new().read(true).open(filename)?;
+        let map = unsafe { MmapOptions::new().map(&reader)? };
+        let (header, contents) = split_file_header(&map)?;
+        if let Some(header) = &header {
+            header.check(expected)?;
+        }
+        if contents.len() != expected_length {
+            return Err(Error::InvalidFileSize {
+                file: filename.to_string(),
+                expected: (header_length(header.as_ref()) + expected_length) as u64,
+                got: map.len() as u64,
+            });
+        }
+        Ok(Self { map, header })
+    }
+
+    /// The contents of the file after the header
+    pub(crate) fn contents(&self) -> &[u8] {
+        &self.map[header_length(self.header.as_ref())..]
+    }
+}
+
+/// Writes the header, if any, at the beginning of a new output file. When resuming, checks that the
+/// output was started with the same header instead.
+pub(crate) fn write_header(
+    output: &mut [u8],
+    output_filename: &str,
+    header: Option<&FileHeader>,
+    resuming: bool,
+) -> Result<()> {
+    let header = match header {
+        Some(header) => header.to_bytes(),
+        None => return Ok(()),
+    };
+    if !resuming {
+        output[..FILE_HEADER_SIZE].copy_from_slice(&header);
+    } else if output[..FILE_HEADER_SIZE] != header {
+        return Err(Error::CannotResume(format!(
+            "{} was started with another file header",
+            output_filename
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, new_challenge, test_dir::TestDir, transform_pok_and_correctness};
+    use phase1::Phase1Parameters;
+    use setup_utils::{
+        converters::{ContributionMode, ProvingSystem},
+        derive_rng_from_seed,
+        BatchExpMode,
+        CheckForCorrectness,
+        SubgroupCheckMode,
+        UseCompression,
+    };
+
+    use ark_bls12_377::Bls12_377;
+
+    use std::fs;
+
+    #[test]
+    fn test_file_headers_are_carried_over() {
+        let dir = TestDir::new("phase1-file-headers");
+        let parameters =
+            Phase1Parameters::<Bls12_377>::new_chunk(ContributionMode::Chunked, 1, 4, ProvingSystem::Groth16, 2, 4);
+
+        new_challenge(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            None,
+            true,
+            &parameters,
+        )
+        .unwrap();
+        contribute(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            &dir.path("response"),
+            &dir.path("response.hash"),
+            false,
+            CheckForCorrectness::No,
+            BatchExpMode::Auto,
+            &parameters,
+            derive_rng_from_seed(b"alice"),
+        )
+        .unwrap();
+        transform_pok_and_correctness(
+            &dir.path("challenge"),
+            &dir.path("challenge.verified.hash"),
+            CheckForCorrectness::No,
+            &dir.path("response"),
+            &dir.path("response.verified.hash"),
+            CheckForCorrectness::No,
+            &dir.path("new_challenge"),
+            &dir.path("new_challenge.hash"),
+            None,
+            false,
+            SubgroupCheckMode::Auto,
+            true,
+            &parameters,
+        )
+        .unwrap();
+
+        let header = |name: &str| FileHeader::from_bytes(&fs::read(dir.path(name)).unwrap()).unwrap();
+        assert_eq!(header("challenge"), Some(parameters.file_header(UseCompression::No)));
+        assert_eq!(header("response"), Some(parameters.file_header(UseCompression::Yes)));
+        assert_eq!(
+            header("new_challenge"),
+            Some(parameters.file_header(UseCompression::No))
+        );
+
+        // the hashes don't cover the headers
+        let response = fs::read(dir.path("response")).unwrap();
+        assert_eq!(
+            fs::read(dir.path("response.hash")).unwrap(),
+            setup_utils::calculate_hash(&response[FILE_HEADER_SIZE..]).to_vec()
+        );
+
+        // the challenge of a chunk can't be used for another chunk
+        let other_chunk =
+            Phase1Parameters::<Bls12_377>::new_chunk(ContributionMode::Chunked, 0, 4, ProvingSystem::Groth16, 2, 4);
+        let err = contribute(
+            &dir.path("new_challenge"),
+            &dir.path("new_challenge.hash"),
+            &dir.path("other_response"),
+            &dir.path("other_response.hash"),
+            false,
+            CheckForCorrectness::No,
+            BatchExpMode::Auto,
+            &other_chunk,
+            derive_rng_from_seed(b"bob"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::FileHeaderMismatch(_)), "{}", err);
+    }
+}
@@ -0,0 +1,620 @@
+use phase1::{ContributionMode, CurveParameters, Phase1Parameters, ProvingSystem, PublicKey};
+use phase2::keypair::PublicKey as Phase2PublicKey;
+use setup_utils::{
+    blank_hash,
+    calculate_hash,
+    converters::CurveKind,
+    curve_id,
+    format_hash,
+    read_file_header,
+    split_file_header,
+    CeremonyManifest,
+    Error,
+    FileHeader,
+    Result,
+    UseCompression,
+};
+
+use ark_bls12_377::Bls12_377;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_bw6_761::BW6_761;
+use ark_ec::pairing::Pairing as Engine;
+use ark_ff::FftField;
+use ark_mnt4_753::MNT4_753;
+use ark_mnt6_753::MNT6_753;
+
+use memmap::*;
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+};
+use tracing::info;
+
+/// The kinds of ceremony files which `inspect` recognizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// An initial challenge, created by `new`
+    Challenge,
+    /// A compressed response to a challenge, followed by the public key of its contributor
+    Response,
+    /// A challenge computed from verified or combined responses
+    NewChallenge,
+    /// The Lagrange coefficients computed from Phase 1 by `prepare_phase2`
+    Groth16Params,
+    /// Phase 2 parameters or a chunk of them, followed by the public keys of their contributors
+    MPCParameters,
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileKind::Challenge => write!(f, "challenge"),
+            FileKind::Response => write!(f, "response"),
+            FileKind::NewChallenge => write!(f, "new challenge"),
+            FileKind::Groth16Params => write!(f, "Groth16Params Phase 2 file"),
+            FileKind::MPCParameters => write!(f, "MPCParameters Phase 2 file"),
+        }
+    }
+}
+
+/// What `inspect` found out about a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDescription {
+    pub kind: FileKind,
+    /// The header at the beginning of the file, if it has one
+    pub header: Option<FileHeader>,
+    /// Whether the elements are compressed
+    pub compressed: bool,
+    /// The number of elements of each kind, in the order they're stored
+    pub element_counts: Vec<(&'static str, usize)>,
+    /// The hash embedded in the file: the hash of the file a Phase 1 file was computed from, or the
+    /// transcript hash of the last contribution to Phase 2 parameters
+    pub previous_hash: Option<Vec<u8>>,
+    /// The BLAKE2b hash of the public key of the last contributor, if the file has one
+    pub public_key_hash: Option<Vec<u8>>,
+}
+
+/// Returns the curve recorded in the header of a file, if it has one
+pub fn header_curve_kind(filename: &str) -> Result<Option<CurveKind>> {
+    let (header, _) = read_file_header(File::open(filename)?)?;
+    let header = match header {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    let curves = [
+        (curve_id::<Bls12_377>(), CurveKind::Bls12_377),
+        (curve_id::<Bls12_381>(), CurveKind::Bls12_381),
+        (curve_id::<Bn254>(), CurveKind::Bn254),
+        (curve_id::<BW6_761>(), CurveKind::BW6),
+        (curve_id::<MNT4_753>(), CurveKind::MNT4_753),
+        (curve_id::<MNT6_753>(), CurveKind::MNT6_753),
+    ];
+    curves
+        .into_iter()
+        .find(|(id, _)| *id == header.curve_id)
+        .map(|(_, curve_kind)| Some(curve_kind))
+        .ok_or_else(|| Error::InvalidFileHeader(format!("{} was written for an unknown curve", filename)))
+}
+
+/// Finds out which kind of ceremony file `filename` is by comparing its size to the sizes given by
+/// the formulas of `Phase1Parameters`, and prints what it contains to stdout. The settings in the header of the
+/// file take precedence over `parameters`. Headerless files of full Phase 1 contributions are also
+/// recognized at other powers than the one in `parameters`.
+pub fn inspect<T: Engine + Sync>(filename: &str, parameters: &Phase1Parameters<T>) -> Result<FileDescription> {
+    info!("Inspecting {}", filename);
+
+    let reader = OpenOptions::new().read(true).open(filename)?;
+    let map = unsafe { MmapOptions::new().map(&reader)? };
+    let (header, contents) = split_file_header(&map)?;
+    if let Some(header) = &header {
+        header.check_curve_and_compression::<T>(compression(header.compressed))?;
+        println!("The file starts with a header: {:?}", header);
+    }
+
+    let description = match describe_phase1(contents, header, parameters)? {
+        Some(description) => description,
+        None => match describe_groth16_params::<T>(contents, header) {
+            Some(description) => description,
+            None => describe_mpc_parameters::<T>(contents, header)?.ok_or_else(|| {
+                Error::UnrecognizedFile(format!(
+                    "the size of {} doesn't match any file of the ceremony",
+                    filename
+                ))
+            })?,
+        },
+    };
+
+    println!(
+        "{} is a {} with {} elements",
+        filename,
+        description.kind,
+        if description.compressed {
+            "compressed"
+        } else {
+            "uncompressed"
+        }
+    );
+    for (name, count) in &description.element_counts {
+        println!("{}: {}", name, count);
+    }
+    if let Some(previous_hash) = &description.previous_hash {
+        println!("The file embeds the hash:");
+        print!("{}", format_hash(previous_hash));
+    }
+    if let Some(public_key_hash) = &description.public_key_hash {
+        println!("The public key of the contributor has the hash:");
+        print!("{}", format_hash(public_key_hash));
+    }
+
+    Ok(description)
+}
+
+fn compression(compressed: bool) -> UseCompression {
+    if compressed {
+        UseCompression::Yes
+    } else {
+        UseCompression::No
+    }
+}
+
+/// The compressions the elements of a file may have: the one in its header, or both
+fn compressions(header: Option<FileHeader>) -> Vec<bool> {
+    match header {
+        Some(header) => vec![header.compressed],
+        None => vec![false, true],
+    }
+}
+
+/// The largest power of the ceremonies on the curve `T`
+fn max_power<T: Engine>() -> usize {
+    <T::ScalarField as FftField>::TWO_ADICITY as usize
+}
+
+/// Checks that a chunk exists, since `Phase1Parameters` can't be constructed for chunks past the end
+fn chunk_exists(proving_system: ProvingSystem, power: usize, chunk_index: usize, chunk_size: usize) -> bool {
+    let powers_length = 1 << power;
+    let upper_bound = match proving_system {
+        ProvingSystem::Groth16 => (powers_length << 1) - 1,
+        ProvingSystem::Marlin => powers_length,
+    };
+    chunk_size > 0
+        && chunk_index
+            .checked_mul(chunk_size)
+            .is_some_and(|start| start < upper_bound)
+}
+
+/// The Phase 1 ceremonies a file may belong to: the one described by its header, or else the one
+/// described by `parameters`, followed by the full ceremonies of every power
+fn phase1_candidates<T: Engine>(
+    header: Option<FileHeader>,
+    parameters: &Phase1Parameters<T>,
+) -> Vec<Phase1Parameters<T>> {
+    let new = |proving_system, contribution_mode, chunk_index, chunk_size, power| {
+        Phase1Parameters::<T>::new(
+            contribution_mode,
+            chunk_index,
+            chunk_size,
+            CurveParameters::<T>::new(),
+            proving_system,
+            power,
+            parameters.batch_size,
+        )
+    };
+
+    if let Some(header) = header {
+        let power = header.power as usize;
+        let (chunk_index, chunk_size) = (header.chunk_index as usize, header.chunk_size as usize);
+        // Phase 2 files have headers too, whose chunks may not exist in Phase 1
+        let exists = power <= max_power::<T>()
+            && match header.contribution_mode {
+                ContributionMode::Chunked => chunk_exists(header.proving_system, power, chunk_index, chunk_size),
+                ContributionMode::Full => true,
+            };
+        return match exists {
+            true => vec![new(
+                header.proving_system,
+                header.contribution_mode,
+                chunk_index,
+                chunk_size,
+                power,
+            )],
+            false => vec![],
+        };
+    }
+
+    let mut candidates = vec![];
+    if parameters.contribution_mode == ContributionMode::Chunked && parameters.g1_chunk_size > 0 {
+        candidates.push(parameters.clone());
+    }
+    let given_power = parameters.total_size_in_log2;
+    for power in std::iter::once(given_power).chain((1..=max_power::<T>()).filter(|power| *power != given_power)) {
+        candidates.push(new(parameters.proving_system, ContributionMode::Full, 0, 0, power));
+    }
+    candidates
+}
+
+/// The number of elements of each kind in an accumulator
+fn phase1_element_counts<T: Engine>(parameters: &Phase1Parameters<T>) -> Vec<(&'static str, usize)> {
+    match parameters.proving_system {
+        ProvingSystem::Groth16 => vec![
+            ("TauG1", parameters.g1_chunk_size),
+            ("TauG2", parameters.other_chunk_size),
+            ("AlphaG1", parameters.other_chunk_size),
+            ("BetaG1", parameters.other_chunk_size),
+            ("BetaG2", 1),
+        ],
+        ProvingSystem::Marlin => {
+            let (tau_g2, alpha_g1) = match parameters.chunk_index {
+                0 => (parameters.total_size_in_log2 + 2, 3 + 3 * parameters.total_size_in_log2),
+                _ => (0, 0),
+            };
+            vec![
+                ("TauG1", parameters.g1_chunk_size),
+                ("TauG2", tau_g2),
+                ("AlphaG1", alpha_g1),
+            ]
+        }
+    }
+}
+
+/// Recognizes challenges, which are uncompressed accumulators, and responses, which are compressed
+/// accumulators followed by a public key. Both start with the hash of the previous file.
+fn describe_phase1<T: Engine>(
+    contents: &[u8],
+    header: Option<FileHeader>,
+    parameters: &Phase1Parameters<T>,
+) -> Result<Option<FileDescription>> {
+    for candidate in phase1_candidates(header, parameters) {
+        let compressed = if contents.len() == candidate.accumulator_size {
+            false
+        } else if contents.len() == candidate.contribution_size {
+            true
+        } else {
+            continue;
+        };
+        if !compressions(header).contains(&compressed) {
+            continue;
+        }
+
+        match candidate.contribution_mode {
+            ContributionMode::Chunked => println!(
+                "The file belongs to chunk {} of size {} of a {} ceremony of power {}",
+                candidate.chunk_index, candidate.chunk_size, candidate.proving_system, candidate.total_size_in_log2
+            ),
+            ContributionMode::Full => println!(
+                "The file belongs to a full {} ceremony of power {}",
+                candidate.proving_system, candidate.total_size_in_log2
+            ),
+        }
+
+        let previous_hash = &contents[..candidate.hash_size];
+        let (kind, public_key_hash) = if compressed {
+            let public_key = PublicKey::<T>::read(contents, UseCompression::Yes, &candidate)?;
+            println!("The contributor's public key is {:?}", public_key);
+            let public_key_position = candidate.contribution_size - candidate.public_key_size;
+            let public_key_hash = calculate_hash(&contents[public_key_position..]).to_vec();
+            (FileKind::Response, Some(public_key_hash))
+        } else if previous_hash == blank_hash().as_slice() || CeremonyManifest::is_challenge_header(previous_hash) {
+            (FileKind::Challenge, None)
+        } else {
+            (FileKind::NewChallenge, None)
+        };
+
+        return Ok(Some(FileDescription {
+            kind,
+            header,
+            compressed,
+            element_counts: phase1_element_counts(&candidate),
+            previous_hash: Some(previous_hash.to_vec()),
+            public_key_hash,
+        }));
+    }
+    Ok(None)
+}
+
+/// The sizes of the elements of G1 and G2 on the curve `T`
+fn element_sizes<T: Engine>(compressed: bool) -> (usize, usize) {
+    let curve = CurveParameters::<T>::new();
+    match compressed {
+        true => (curve.g1_compressed_size, curve.g2_compressed_size),
+        false => (curve.g1_size, curve.g2_size),
+    }
+}
+
+/// Recognizes the output of `prepare_phase2`: alpha and beta in G1, beta in G2, then `n` Lagrange
+/// coefficients in G1, in G2, with alpha and with beta, and the `n - 1` elements of the H query,
+/// for a domain size `n` which is a power of 2
+fn describe_groth16_params<T: Engine>(contents: &[u8], header: Option<FileHeader>) -> Option<FileDescription> {
+    for compressed in compressions(header) {
+        let (g1_size, g2_size) = element_sizes::<T>(compressed);
+        for power in 0..=max_power::<T>() {
+            let domain_size = 1usize << power;
+            let size = 2 * g1_size + g2_size + domain_size * (3 * g1_size + g2_size) + (domain_size - 1) * g1_size;
+            if contents.len() != size {
+                continue;
+            }
+            return Some(FileDescription {
+                kind: FileKind::Groth16Params,
+                header,
+                compressed,
+                element_counts: vec![
+                    ("AlphaG1", 1),
+                    ("BetaG1", 1),
+                    ("BetaG2", 1),
+                    ("CoeffsG1", domain_size),
+                    ("CoeffsG2", domain_size),
+                    ("AlphaCoeffsG1", domain_size),
+                    ("BetaCoeffsG1", domain_size),
+                    ("HG1", domain_size - 1),
+                ],
+                previous_hash: None,
+                public_key_hash: None,
+            });
+        }
+    }
+    None
+}
+
+/// Recognizes `MPCParameters` by walking the lengths of their vectors, without reading the
+/// elements, and checking that the public keys of the contributions end the file
+fn describe_mpc_parameters<T: Engine>(contents: &[u8], header: Option<FileHeader>) -> Result<Option<FileDescription>> {
+    for compressed in compressions(header) {
+        let (g1_size, g2_size) = element_sizes::<T>(compressed);
+        // the elements of the proving key, as (name, size of an element, whether it's a vector)
+        let layout = [
+            ("AlphaG1", g1_size, false),
+            ("BetaG2", g2_size, false),
+            ("GammaG2", g2_size, false),
+            ("DeltaG2", g2_size, false),
+            ("GammaAbcG1", g1_size, true),
+            ("BetaG1", g1_size, false),
+            ("DeltaG1", g1_size, false),
+            ("AQuery", g1_size, true),
+            ("BG1Query", g1_size, true),
+            ("BG2Query", g2_size, true),
+            ("HQuery", g1_size, true),
+            ("LQuery", g1_size, true),
+        ];
+
+        let take = |position: usize, length: usize| position.checked_add(length).filter(|end| *end <= contents.len());
+        let mut position = 0;
+        let mut element_counts = vec![];
+        for (name, element_size, is_vector) in layout {
+            let count = match is_vector {
+                true => match take(position, 8) {
+                    Some(end) => {
+                        let length = u64::from_le_bytes(contents[position..end].try_into().unwrap());
+                        position = end;
+                        length as usize
+                    }
+                    None => break,
+                },
+                false => 1,
+            };
+            match count
+                .checked_mul(element_size)
+                .and_then(|length| take(position, length))
+            {
+                Some(end) => position = end,
+                None => break,
+            }
+            element_counts.push((name, count));
+        }
+        if element_counts.len() != layout.len() {
+            continue;
+        }
+
+        // the hash of the circuit and the number of contributions follow the proving key
+        let contributions_position = match take(position, 64 + 4) {
+            Some(end) => end,
+            None => continue,
+        };
+        let cs_hash = &contents[position..position + 64];
+        let num_contributions = u32::from_be_bytes(contents[position + 64..contributions_position].try_into().unwrap());
+        if Some(contents.len())
+            != (num_contributions as usize)
+                .checked_mul(Phase2PublicKey::<T>::size())
+                .and_then(|length| contributions_position.checked_add(length))
+        {
+            continue;
+        }
+
+        println!("The parameters are for the circuit with the hash:");
+        print!("{}", format_hash(cs_hash));
+        let contributions = Phase2PublicKey::<T>::read_batch(&mut &contents[position + 64..])?;
+        element_counts.push(("Contributions", contributions.len()));
+        let last_contribution = contributions.last();
+        if let Some(public_key) = last_contribution {
+            println!("The last contributor's public key is {:?}", public_key);
+        }
+
+        return Ok(Some(FileDescription {
+            kind: FileKind::MPCParameters,
+            header,
+            compressed,
+            element_counts,
+            previous_hash: last_contribution.map(|public_key| public_key.transcript.to_vec()),
+            public_key_hash: last_contribution.map(|public_key| public_key.hash().to_vec()),
+        }));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, new_challenge, test_dir::TestDir, transform_pok_and_correctness};
+    use phase1::Phase1;
+    use phase2::{
+        helpers::testing::TestCircuit,
+        load_circuit::Matrices,
+        parameters::{circuit_to_qap, MPCParameters},
+    };
+    use setup_utils::{
+        derive_rng_from_seed,
+        domain_size,
+        BatchExpMode,
+        CheckForCorrectness,
+        Groth16Params,
+        SubgroupCheckMode,
+        UseCompression,
+    };
+
+    use std::fs;
+
+    #[test]
+    fn test_inspect_ceremony_files() {
+        let dir = TestDir::new("phase1-inspect");
+        let parameters =
+            Phase1Parameters::<Bls12_377>::new_chunk(ContributionMode::Chunked, 1, 4, ProvingSystem::Groth16, 2, 4);
+
+        new_challenge(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            None,
+            false,
+            &parameters,
+        )
+        .unwrap();
+        contribute(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            &dir.path("response"),
+            &dir.path("response.hash"),
+            false,
+            CheckForCorrectness::No,
+            BatchExpMode::Auto,
+            &parameters,
+            derive_rng_from_seed(b"alice"),
+        )
+        .unwrap();
+        transform_pok_and_correctness(
+            &dir.path("challenge"),
+            &dir.path("challenge.verified.hash"),
+            CheckForCorrectness::No,
+            &dir.path("response"),
+            &dir.path("response.verified.hash"),
+            CheckForCorrectness::No,
+            &dir.path("new_challenge"),
+            &dir.path("new_challenge.hash"),
+            None,
+            false,
+            SubgroupCheckMode::Auto,
+            true,
+            &parameters,
+        )
+        .unwrap();
+
+        let challenge = inspect(&dir.path("challenge"), &parameters).unwrap();
+        assert_eq!(challenge.kind, FileKind::Challenge);
+        assert!(!challenge.compressed);
+        assert_eq!(challenge.element_counts[0], ("TauG1", 3));
+        assert_eq!(challenge.previous_hash, Some(blank_hash().to_vec()));
+
+        let response = inspect(&dir.path("response"), &parameters).unwrap();
+        assert_eq!(response.kind, FileKind::Response);
+        assert!(response.compressed);
+        assert_eq!(
+            response.previous_hash,
+            Some(fs::read(dir.path("challenge.hash")).unwrap())
+        );
+        assert!(response.public_key_hash.is_some());
+
+        let transformed = inspect(&dir.path("new_challenge"), &parameters).unwrap();
+        assert_eq!(transformed.kind, FileKind::NewChallenge);
+        assert_eq!(
+            transformed.previous_hash,
+            Some(fs::read(dir.path("response.verified.hash")).unwrap())
+        );
+
+        // the settings of files with a header are read from it
+        new_challenge(
+            &dir.path("headered_challenge"),
+            &dir.path("headered_challenge.hash"),
+            None,
+            true,
+            &parameters,
+        )
+        .unwrap();
+        let other_parameters =
+            Phase1Parameters::<Bls12_377>::new_chunk(ContributionMode::Chunked, 0, 2, ProvingSystem::Groth16, 3, 4);
+        let headered_challenge = inspect(&dir.path("headered_challenge"), &other_parameters).unwrap();
+        assert_eq!(headered_challenge.kind, FileKind::Challenge);
+        assert_eq!(
+            headered_challenge.header,
+            Some(parameters.file_header(UseCompression::No))
+        );
+        assert_eq!(headered_challenge.element_counts[0], ("TauG1", 3));
+
+        // full challenges are recognized without being given their power
+        let full_parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 3, 4);
+        new_challenge(
+            &dir.path("full_challenge"),
+            &dir.path("full_challenge.hash"),
+            None,
+            false,
+            &full_parameters,
+        )
+        .unwrap();
+        let full_challenge = inspect(&dir.path("full_challenge"), &parameters).unwrap();
+        assert_eq!(full_challenge.kind, FileKind::Challenge);
+        assert_eq!(full_challenge.element_counts[0], ("TauG1", 15));
+
+        let accumulator = Phase1::deserialize(
+            &fs::read(dir.path("full_challenge")).unwrap(),
+            UseCompression::No,
+            CheckForCorrectness::No,
+            &full_parameters,
+        )
+        .unwrap();
+        let groth16_params = |domain_size| {
+            Groth16Params::<Bls12_377>::new(
+                domain_size,
+                accumulator.tau_powers_g1.clone(),
+                accumulator.tau_powers_g2.clone(),
+                accumulator.alpha_tau_powers_g1.clone(),
+                accumulator.beta_tau_powers_g1.clone(),
+                accumulator.beta_g2,
+            )
+            .unwrap()
+        };
+        let mut phase2 = vec![];
+        groth16_params(4).write(&mut phase2, UseCompression::No).unwrap();
+        fs::write(dir.path("phase2"), phase2).unwrap();
+        let phase2 = inspect(&dir.path("phase2"), &parameters).unwrap();
+        assert_eq!(phase2.kind, FileKind::Groth16Params);
+        assert!(phase2.element_counts.contains(&("HG1", 3)));
+
+        // Phase 2 parameters with a contribution
+        let matrices = Matrices::<Bls12_377>::from(
+            circuit_to_qap::<Bls12_377, _>(TestCircuit::<Bls12_377>(None))
+                .unwrap()
+                .to_matrices()
+                .unwrap(),
+        );
+        let phase2_size = std::cmp::max(
+            matrices.num_constraints,
+            matrices.num_witness_variables + matrices.num_instance_variables,
+        );
+        let mut mpc_parameters =
+            MPCParameters::new(matrices, groth16_params(domain_size::<Bls12_377>(phase2_size))).unwrap();
+        mpc_parameters
+            .contribute(BatchExpMode::Auto, &mut derive_rng_from_seed(b"bob"))
+            .unwrap();
+        let mut serialized = vec![];
+        mpc_parameters.write(&mut serialized, UseCompression::No).unwrap();
+        fs::write(dir.path("mpc_parameters"), serialized).unwrap();
+        let mpc = inspect(&dir.path("mpc_parameters"), &parameters).unwrap();
+        assert_eq!(mpc.kind, FileKind::MPCParameters);
+        assert!(!mpc.compressed);
+        assert!(mpc.element_counts.contains(&("Contributions", 1)));
+        let public_key = &mpc_parameters.contributions[0];
+        assert_eq!(mpc.previous_hash, Some(public_key.transcript.to_vec()));
+        assert_eq!(mpc.public_key_hash, Some(public_key.hash().to_vec()));
+
+        fs::write(dir.path("unknown"), [1; 100]).unwrap();
+        let err = inspect(&dir.path("unknown"), &parameters).unwrap_err();
+        assert!(matches!(err, Error::UnrecognizedFile(_)), "{}", err);
+    }
+}
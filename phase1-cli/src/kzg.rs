@@ -0,0 +1,219 @@
+use phase1::{Phase1, Phase1Parameters};
+use setup_utils::{
+    calculate_hash,
+    converters::{ProvingSystem, SrsFormat},
+    print_hash,
+    split_file_header,
+    CheckForCorrectness,
+    Result,
+    UseCompression,
+};
+
+use ark_ec::pairing::Pairing as Engine;
+use ark_serialize::CanonicalSerialize;
+
+use memmap::MmapOptions;
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
+use tracing::info;
+
+use crate::file_headers::InputFile;
+
+const CHALLENGE_IS_COMPRESSED: UseCompression = UseCompression::No;
+const RESPONSE_IS_COMPRESSED: UseCompression = UseCompression::Yes;
+
+/// Exports the universal parameters of KZG10 from a full challenge or response. Without a
+/// `max_degree`, a Marlin accumulator is converted to the complete parameters, serialized like
+/// `ark_poly_commit::kzg10::UniversalParams` so that they can be loaded directly for SonicKZG10
+/// and Marlin. Otherwise, and for Groth16 accumulators, only the powers of tau in G1 up to
+/// `max_degree`, `h` and `beta_h` are exported, which is what PLONK needs.
+pub fn export_kzg_srs<T: Engine + Sync>(
+    input_filename: &str,
+    srs_filename: &str,
+    format: SrsFormat,
+    compressed: UseCompression,
+    max_degree: Option<usize>,
+    check_input_correctness: CheckForCorrectness,
+    parameters: &Phase1Parameters<T>,
+) -> Result<()> {
+    info!("Will export {} to KZG10 universal parameters", input_filename);
+
+    let (input, input_compressed) = open_accumulator(input_filename, parameters)?;
+    let universal_params = if parameters.proving_system == ProvingSystem::Marlin && max_degree.is_none() {
+        Phase1::deserialize(input.contents(), input_compressed, check_input_correctness, parameters)?
+            .kzg_universal_params()?
+    } else {
+        Phase1::read_kzg_powers(
+            input.contents(),
+            input_compressed,
+            check_input_correctness,
+            max_degree,
+            parameters,
+        )?
+    };
+
+    let mut srs = vec![];
+    match format {
+        SrsFormat::Arkworks => universal_params.serialize_with_mode(&mut srs, compressed)?,
+        SrsFormat::Raw => universal_params.write_raw(&mut srs)?,
+    }
+    let writer = OpenOptions::new().write(true).create_new(true).open(srs_filename)?;
+    let mut writer = BufWriter::new(writer);
+    writer.write_all(&srs)?;
+    writer.flush()?;
+
+    info!(
+        "Exported {} universal parameters for polynomials of degree up to {} to {}, with a hash:",
+        format,
+        universal_params.max_degree(),
+        srs_filename
+    );
+    print_hash(&calculate_hash(&srs));
+
+    Ok(())
+}
+
+/// Opens a challenge, or a response if the file has the size of one, and returns whether its
+/// elements are compressed
+fn open_accumulator<T: Engine>(
+    filename: &str,
+    parameters: &Phase1Parameters<T>,
+) -> Result<(InputFile, UseCompression)> {
+    let length = {
+        let reader = OpenOptions::new().read(true).open(filename)?;
+        let map = unsafe { MmapOptions::new().map(&reader)? };
+        split_file_header(&map)?.1.len()
+    };
+    let (expected_length, compressed) = if length == parameters.contribution_size {
+        (parameters.contribution_size, RESPONSE_IS_COMPRESSED)
+    } else {
+        (parameters.accumulator_size, CHALLENGE_IS_COMPRESSED)
+    };
+    let input = InputFile::open(filename, expected_length, &parameters.file_header(compressed))?;
+    Ok((input, compressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, new_challenge, test_dir::TestDir};
+    use phase1::kzg::KzgUniversalParams;
+    use setup_utils::{buffer_size, derive_rng_from_seed, BatchExpMode, Error};
+
+    use ark_bls12_377::Bls12_377;
+    use ark_serialize::CanonicalDeserialize;
+
+    use std::fs;
+
+    #[test]
+    fn test_export_kzg_srs() {
+        let dir = TestDir::new("phase1-kzg");
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Marlin, 3, 4);
+
+        new_challenge(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            None,
+            true,
+            &parameters,
+        )
+        .unwrap();
+        export_kzg_srs(
+            &dir.path("challenge"),
+            &dir.path("srs"),
+            SrsFormat::Arkworks,
+            UseCompression::Yes,
+            None,
+            CheckForCorrectness::Full,
+            &parameters,
+        )
+        .unwrap();
+
+        let srs = fs::read(dir.path("srs")).unwrap();
+        let universal_params = KzgUniversalParams::<Bls12_377>::deserialize_compressed(srs.as_slice()).unwrap();
+        assert_eq!(universal_params.powers_of_g.len(), 8);
+        assert_eq!(universal_params.neg_powers_of_h.len(), 3);
+
+        // a Marlin accumulator can't be exported beyond the degree it supports
+        let err = export_kzg_srs(
+            &dir.path("challenge"),
+            &dir.path("too_large_srs"),
+            SrsFormat::Arkworks,
+            UseCompression::Yes,
+            Some(8),
+            CheckForCorrectness::Full,
+            &parameters,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_export_kzg_srs_from_groth16_response() {
+        let dir = TestDir::new("phase1-kzg-groth16");
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 3, 4);
+
+        new_challenge(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            None,
+            false,
+            &parameters,
+        )
+        .unwrap();
+        contribute(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            &dir.path("response"),
+            &dir.path("response.hash"),
+            false,
+            CheckForCorrectness::No,
+            BatchExpMode::Auto,
+            &parameters,
+            derive_rng_from_seed(b"alice"),
+        )
+        .unwrap();
+
+        export_kzg_srs(
+            &dir.path("response"),
+            &dir.path("srs"),
+            SrsFormat::Arkworks,
+            UseCompression::No,
+            None,
+            CheckForCorrectness::Full,
+            &parameters,
+        )
+        .unwrap();
+        let srs = fs::read(dir.path("srs")).unwrap();
+        let universal_params = KzgUniversalParams::<Bls12_377>::deserialize_uncompressed(srs.as_slice()).unwrap();
+        assert_eq!(universal_params.max_degree(), parameters.powers_g1_length - 1);
+        assert!(universal_params.powers_of_gamma_g.is_empty());
+
+        export_kzg_srs(
+            &dir.path("response"),
+            &dir.path("srs.raw"),
+            SrsFormat::Raw,
+            UseCompression::Yes,
+            Some(4),
+            CheckForCorrectness::Full,
+            &parameters,
+        )
+        .unwrap();
+        let raw = fs::read(dir.path("srs.raw")).unwrap();
+        let mut expected = vec![];
+        KzgUniversalParams {
+            powers_of_g: universal_params.powers_of_g[..5].to_vec(),
+            ..universal_params
+        }
+        .write_raw(&mut expected)
+        .unwrap();
+        assert_eq!(raw, expected);
+        assert_eq!(
+            raw.len(),
+            5 * buffer_size::<<Bls12_377 as Engine>::G1Affine>(UseCompression::No)
+                + 2 * buffer_size::<<Bls12_377 as Engine>::G2Affine>(UseCompression::No)
+        );
+    }
+}
@@ -2,17 +2,50 @@
 #![cfg_attr(nightly, feature(doc_cfg, external_doc))]
 #![cfg_attr(nightly, doc(include = "../README.md"))]
 
+mod audit;
+pub use audit::{audit, AuditReport, AuditStatus, ContributionAudit};
+
+mod checkpoint;
+
 mod combine;
 pub use combine::combine;
 
 mod contribute;
 pub use contribute::contribute;
 
+mod file_headers;
+
+mod inspect;
+pub use inspect::{header_curve_kind, inspect, FileDescription, FileKind};
+
+mod contributor;
+pub use contributor::{Contributor, CoordinatorClient};
+
+mod coordinator;
+pub use coordinator::{
+    read_ceremony_state,
+    serve,
+    CeremonySettings,
+    CeremonyState,
+    ChunkState,
+    Coordinator,
+    LockResponse,
+    UploadStatus,
+    DEFAULT_LOCK_TIMEOUT,
+    RESPONSE_HASH_HEADER,
+};
+
 mod split;
 pub use split::split;
 
+mod reduce;
+pub use reduce::reduce;
+
+mod kzg;
+pub use kzg::export_kzg_srs;
+
 mod new_challenge;
-pub use new_challenge::new_challenge;
+pub use new_challenge::{check_challenge_manifest, new_challenge};
 
 mod transform_pok_and_correctness;
 pub use transform_pok_and_correctness::transform_pok_and_correctness;
@@ -20,7 +53,19 @@ pub use transform_pok_and_correctness::transform_pok_and_correctness;
 mod transform_ratios;
 pub use transform_ratios::transform_ratios;
 
-use setup_utils::converters::{ContributionMode, CurveKind, ProvingSystem};
+mod verify_beacon;
+pub use verify_beacon::verify_beacon;
+
+mod verify_transcript;
+pub use verify_transcript::verify_transcript;
+
+mod ptau;
+pub use ptau::{export_ptau, import_ptau};
+
+#[cfg(test)]
+mod test_dir;
+
+use setup_utils::converters::{ContributionMode, CurveKind, ProvingSystem, SrsFormat};
 
 use gumdrop::Options;
 use setup_utils::{
@@ -29,9 +74,12 @@ use setup_utils::{
         contribution_mode_from_str,
         curve_from_str,
         proving_system_from_str,
+        srs_format_from_str,
         subgroup_check_mode_from_str,
     },
     BatchExpMode,
+    CeremonyManifest,
+    Result,
     SubgroupCheckMode,
 };
 use std::default::Default;
@@ -88,6 +136,30 @@ pub struct Phase1Opts {
     pub subgroup_check_mode: SubgroupCheckMode,
     #[options(help = "whether to skip ratio check")]
     pub skip_ratio_check: bool,
+    #[options(help = "the file to which a JSON report is written if the command fails")]
+    pub failure_report: Option<String>,
+    #[options(
+        help = "the ceremony manifest to read the curve, proving system, contribution mode, power, batch size and chunk size from, instead of their flags"
+    )]
+    pub manifest: Option<String>,
+}
+
+impl Phase1Opts {
+    /// Reads the manifest given with `--manifest`, if any, and replaces the settings of the
+    /// ceremony with the ones it describes
+    pub fn apply_manifest(&mut self) -> Result<Option<CeremonyManifest>> {
+        let manifest = match &self.manifest {
+            Some(manifest_filename) => CeremonyManifest::read(manifest_filename)?,
+            None => return Ok(None),
+        };
+        self.curve_kind = manifest.curve_kind()?;
+        self.proving_system = manifest.proving_system()?;
+        self.contribution_mode = manifest.contribution_mode()?;
+        self.power = manifest.power;
+        self.batch_size = manifest.batch_size;
+        self.chunk_size = manifest.chunk_size;
+        Ok(Some(manifest))
+    }
 }
 
 // The supported commands
@@ -101,7 +173,9 @@ pub enum Command {
     )]
     Contribute(ContributeOpts),
     #[options(help = "contribute randomness via a random beacon (e.g. a bitcoin block header hash)")]
-    Beacon(ContributeOpts),
+    Beacon(BeaconOpts),
+    #[options(help = "verify the iterated hash work of a beacon and that the beacon contribution was derived from it")]
+    VerifyBeacon(VerifyBeaconOpts),
     // this receives a challenge + response file, verifies it and generates a new challenge, for a single chunk.
     #[options(help = "verify the contributions so far and generate a new challenge, for a single chunk")]
     VerifyAndTransformPokAndCorrectness(VerifyPokAndCorrectnessOpts),
@@ -113,6 +187,20 @@ pub enum Command {
     Combine(CombineOpts),
     #[options(help = "receive a full contribution and splits it into chunks")]
     Split(SplitOpts),
+    #[options(help = "reduce a full Groth16 challenge to the challenge of a smaller power")]
+    Reduce(ReduceOpts),
+    #[options(help = "convert a full challenge to a snarkjs ptau file")]
+    ExportPtau(ExportPtauOpts),
+    #[options(help = "convert a snarkjs ptau file to a challenge")]
+    ImportPtau(ImportPtauOpts),
+    #[options(help = "convert a full Marlin challenge to KZG10 universal parameters")]
+    ExportKzgSrs(ExportKzgSrsOpts),
+    #[options(help = "verify every full contribution to the ceremony in order, and report on each contributor")]
+    Audit(AuditOpts),
+    #[options(help = "verify the hash chain and the proofs of knowledge of a transcript")]
+    VerifyTranscript(VerifyTranscriptOpts),
+    #[options(help = "describe a challenge, response or Phase 2 file")]
+    Inspect(InspectOpts),
 }
 
 // Options for the Contribute command
@@ -123,6 +211,8 @@ pub struct NewOpts {
     pub challenge_fname: String,
     #[options(help = "the new challenge file hash", default = "challenge.verified.hash")]
     pub challenge_hash_fname: String,
+    #[options(help = "start the challenge and the files derived from it with a header describing the ceremony")]
+    pub file_header: bool,
 }
 
 // Options for the Contribute command
@@ -137,11 +227,38 @@ pub struct ContributeOpts {
     pub response_fname: String,
     #[options(help = "the response file which will be generated hash", default = "response.hash")]
     pub response_hash_fname: String,
+    #[options(help = "resume an interrupted contribution to the response file")]
+    pub resume: bool,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct BeaconOpts {
+    help: bool,
+    #[options(help = "the provided challenge file", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(help = "the provided challenge file hash", default = "challenge.hash")]
+    pub challenge_hash_fname: String,
+    #[options(help = "the response file which will be generated")]
+    pub response_fname: String,
+    #[options(help = "the response file which will be generated hash", default = "response.hash")]
+    pub response_hash_fname: String,
+    #[options(help = "resume an interrupted contribution to the response file")]
+    pub resume: bool,
     #[options(
-        help = "the beacon hash to be used if running a beacon contribution",
+        help = "the beacon hash to be used for the beacon contribution",
         default = "0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620"
     )]
     pub beacon_hash: String,
+    #[options(
+        help = "the number of times the beacon hash is hashed, as a power of two",
+        default = "42"
+    )]
+    pub iterations_exp: u32,
+    #[options(
+        help = "the file to which the intermediate states of the beacon are written, or read from if it exists",
+        default = "beacon.checkpoints"
+    )]
+    pub checkpoints_fname: String,
 }
 
 #[derive(Debug, Options, Clone)]
@@ -165,6 +282,30 @@ pub struct VerifyPokAndCorrectnessOpts {
         default = "new_challenge.verified.hash"
     )]
     pub new_challenge_hash_fname: String,
+    #[options(help = "the transcript file to which the verified contribution will be appended")]
+    pub transcript_fname: Option<String>,
+    #[options(help = "resume an interrupted verification of the response")]
+    pub resume: bool,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyBeaconOpts {
+    help: bool,
+    #[options(help = "the challenge file of the beacon contribution", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(help = "the response file of the beacon contribution", default = "response")]
+    pub response_fname: String,
+    #[options(help = "the intermediate states of the beacon", default = "beacon.checkpoints")]
+    pub checkpoints_fname: String,
+    #[options(help = "the beacon hash the beacon contribution should have used", required)]
+    pub beacon_hash: String,
+    #[options(
+        help = "the number of times the beacon hash should have been hashed, as a power of two",
+        required
+    )]
+    pub iterations_exp: u32,
+    #[options(help = "accept a beacon hashed fewer than 2^40 times, e.g. in tests")]
+    pub allow_low_iterations: bool,
 }
 
 #[derive(Debug, Options, Clone)]
@@ -191,3 +332,96 @@ pub struct SplitOpts {
     #[options(help = "the full response file", default = "full")]
     pub full_fname: String,
 }
+
+#[derive(Debug, Options, Clone)]
+pub struct ReduceOpts {
+    help: bool,
+    #[options(help = "the full challenge file which will be reduced", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(
+        help = "the reduced challenge file which will be created",
+        default = "reduced_challenge"
+    )]
+    pub reduced_challenge_fname: String,
+    #[options(help = "the reduced challenge file hash", default = "reduced_challenge.hash")]
+    pub reduced_challenge_hash_fname: String,
+    #[options(required, help = "the power of the challenge")]
+    pub from_power: usize,
+    #[options(required, help = "the power of the reduced challenge")]
+    pub to_power: usize,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct ExportPtauOpts {
+    help: bool,
+    #[options(help = "the full challenge file which will be exported", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(help = "the list of responses whose public keys will be written to the ptau file, in order")]
+    pub response_list_fname: Option<String>,
+    #[options(help = "the ptau file which will be created", default = "challenge.ptau")]
+    pub ptau_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct ImportPtauOpts {
+    help: bool,
+    #[options(help = "the ptau file which will be imported", default = "challenge.ptau")]
+    pub ptau_fname: String,
+    #[options(help = "the challenge file which will be created", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(help = "the challenge file hash", default = "challenge.verified.hash")]
+    pub challenge_hash_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct ExportKzgSrsOpts {
+    help: bool,
+    #[options(
+        help = "the full challenge or response file which will be exported",
+        default = "challenge"
+    )]
+    pub challenge_fname: String,
+    #[options(
+        help = "the universal parameters file which will be created",
+        default = "challenge.srs"
+    )]
+    pub srs_fname: String,
+    #[options(help = "write the group elements uncompressed")]
+    pub uncompressed: bool,
+    #[options(
+        help = "the format of the universal parameters: arkworks or raw",
+        default = "arkworks",
+        parse(try_from_str = "srs_format_from_str")
+    )]
+    pub format: SrsFormat,
+    #[options(help = "only export the powers of tau for polynomials up to this degree")]
+    pub max_degree: Option<usize>,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct AuditOpts {
+    help: bool,
+    #[options(help = "the initial challenge of the ceremony", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(
+        help = "the file listing the full responses of the ceremony in order, one per line",
+        default = "response_list"
+    )]
+    pub response_list_fname: String,
+    #[options(help = "the JSON report which will be created", default = "audit.json")]
+    pub report_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyTranscriptOpts {
+    help: bool,
+    #[options(help = "the transcript file which will be verified", default = "transcript")]
+    pub transcript_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct InspectOpts {
+    help: bool,
+    #[options(help = "the file which will be described")]
+    pub input_fname: String,
+}
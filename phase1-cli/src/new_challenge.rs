@@ -1,19 +1,40 @@
 use phase1::{Phase1, Phase1Parameters};
-use setup_utils::{blank_hash, calculate_hash, print_hash, write_to_file, UseCompression};
+use setup_utils::{
+    blank_hash,
+    calculate_hash,
+    header_length,
+    print_hash,
+    split_file_header,
+    write_to_file,
+    CeremonyManifest,
+    Result,
+    UseCompression,
+    FILE_HEADER_SIZE,
+};
 
 use ark_ec::pairing::Pairing as Engine;
 
 use memmap::*;
-use std::{fs::OpenOptions, io::Write};
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+};
+
+use crate::file_headers::write_header;
 use tracing::info;
 
 const COMPRESS_NEW_CHALLENGE: UseCompression = UseCompression::No;
 
+/// Creates an initial challenge. If it's created from a manifest, the hash of the manifest is
+/// written in place of the hash of a previous response. If `file_header` is set, the challenge
+/// starts with a header describing the ceremony, which is carried over to the following files.
 pub fn new_challenge<T: Engine + Sync>(
     challenge_filename: &str,
     challenge_hash_filename: &str,
+    manifest: Option<&CeremonyManifest>,
+    file_header: bool,
     parameters: &Phase1Parameters<T>,
-) {
+) -> Result<()> {
     info!(
         "Will generate an empty accumulator for 2^{} powers of tau",
         parameters.total_size_in_log2
@@ -24,46 +45,62 @@ pub fn new_challenge<T: Engine + Sync>(
         .read(true)
         .write(true)
         .create_new(true)
-        .open(challenge_filename)
-        .expect("unable to create challenge file");
+        .open(challenge_filename)?;
 
     let expected_challenge_length = match COMPRESS_NEW_CHALLENGE {
         UseCompression::Yes => parameters.contribution_size - parameters.public_key_size,
         UseCompression::No => parameters.accumulator_size,
     };
 
-    file.set_len(expected_challenge_length as u64)
-        .expect("unable to allocate large enough file");
+    let header = file_header.then(|| parameters.file_header(COMPRESS_NEW_CHALLENGE));
+    let offset = header_length(header.as_ref());
 
-    let mut writable_map = unsafe {
-        MmapOptions::new()
-            .map_mut(&file)
-            .expect("unable to create a memory map")
-    };
+    file.set_len((offset + expected_challenge_length) as u64)?;
+
+    let mut writable_map = unsafe { MmapOptions::new().map_mut(&file)? };
+    write_header(&mut writable_map, challenge_filename, header.as_ref(), false)?;
+
+    if let Some(manifest) = manifest {
+        // Write the hash of the manifest, so that challenges from another configuration are detected
+        let header = manifest.challenge_header();
+        (&mut writable_map[offset..]).write_all(&header)?;
+        writable_map.flush()?;
 
-    // Write a blank BLAKE2b hash:
-    let hash = blank_hash();
-    (&mut writable_map[0..])
-        .write_all(hash.as_slice())
-        .expect("unable to write a default hash to mmap");
-    writable_map
-        .flush()
-        .expect("unable to write blank hash to challenge file");
+        info!("Manifest hash for an empty challenge:");
+        print_hash(&header);
+    } else {
+        // Write a blank BLAKE2b hash:
+        let hash = blank_hash();
+        (&mut writable_map[offset..]).write_all(hash.as_slice())?;
+        writable_map.flush()?;
 
-    info!("Blank hash for an empty challenge:");
-    print_hash(&hash);
+        info!("Blank hash for an empty challenge:");
+        print_hash(&hash);
+    }
 
-    Phase1::initialization(&mut writable_map, COMPRESS_NEW_CHALLENGE, &parameters)
-        .expect("generation of initial accumulator is successful");
-    writable_map.flush().expect("unable to flush memmap to disk");
+    Phase1::initialization(&mut writable_map[offset..], COMPRESS_NEW_CHALLENGE, &parameters)?;
+    writable_map.flush()?;
 
     // Get the hash of the contribution, so the user can compare later
-    let output_readonly = writable_map.make_read_only().expect("must make a map readonly");
-    let contribution_hash = calculate_hash(&output_readonly);
+    let output_readonly = writable_map.make_read_only()?;
+    let contribution_hash = calculate_hash(&output_readonly[offset..]);
 
-    write_to_file(challenge_hash_filename, contribution_hash.as_slice());
+    write_to_file(challenge_hash_filename, contribution_hash.as_slice())?;
 
     info!("Empty contribution is formed with a hash:");
     print_hash(&contribution_hash);
     info!("Wrote a fresh accumulator to challenge file");
+
+    Ok(())
+}
+
+/// Checks that a challenge belongs to the ceremony described by a manifest. Only initial challenges
+/// record the manifest they were created from: later ones are accepted whatever ceremony they
+/// belong to, and are only tied to an initial challenge by the hash chain.
+pub fn check_challenge_manifest(challenge_filename: &str, manifest: &CeremonyManifest) -> Result<()> {
+    // Challenges are always longer than a file header and a hash
+    let mut prefix = [0; FILE_HEADER_SIZE + 64];
+    File::open(challenge_filename)?.read_exact(&mut prefix)?;
+    let (_, contents) = split_file_header(&prefix)?;
+    manifest.check_challenge_header(&contents[..64])
 }
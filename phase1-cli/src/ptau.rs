@@ -0,0 +1,150 @@
+use phase1::{ptau::PtauContribution, Phase1, Phase1Parameters};
+use setup_utils::{
+    calculate_hash,
+    print_hash,
+    snarkjs::SnarkjsPoint,
+    split_file_header,
+    write_to_file,
+    CheckForCorrectness,
+    GenericArray,
+    Result,
+    UseCompression,
+};
+
+use ark_ec::pairing::Pairing as Engine;
+
+use memmap::*;
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+};
+use tracing::info;
+
+use crate::file_headers::InputFile;
+
+const CHALLENGE_IS_COMPRESSED: UseCompression = UseCompression::No;
+const CONTRIBUTION_IS_COMPRESSED: UseCompression = UseCompression::Yes;
+
+/// Converts a full challenge to a snarkjs `.ptau` file. If a response list is given, the
+/// public keys of these responses (in the order of the ceremony) are written to the
+/// contributions section.
+pub fn export_ptau<T: Engine + Sync>(
+    challenge_filename: &str,
+    response_list_filename: Option<&str>,
+    ptau_filename: &str,
+    check_input_correctness: CheckForCorrectness,
+    parameters: &Phase1Parameters<T>,
+) -> Result<()>
+where
+    T::G1Affine: SnarkjsPoint,
+    T::G2Affine: SnarkjsPoint,
+{
+    info!("Will export the challenge to a ptau file");
+
+    let challenge = InputFile::open(
+        challenge_filename,
+        parameters.accumulator_size,
+        &parameters.file_header(CHALLENGE_IS_COMPRESSED),
+    )?;
+    let challenge_hash = calculate_hash(challenge.contents());
+
+    let response_filenames = match response_list_filename {
+        Some(response_list_filename) => BufReader::new(File::open(response_list_filename)?)
+            .lines()
+            .collect::<std::io::Result<Vec<_>>>()?,
+        None => vec![],
+    };
+
+    // Each response starts with the hash of the challenge it was based on, which is the
+    // challenge following the previous response. The last response is followed by the
+    // challenge being exported.
+    let response_files = response_filenames
+        .iter()
+        .map(std::fs::read)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let mut responses = vec![];
+    for response_file in &response_files {
+        let (header, response) = split_file_header(response_file)?;
+        if let Some(header) = header {
+            header.check(&parameters.file_header(CONTRIBUTION_IS_COMPRESSED))?;
+        }
+        responses.push(response);
+    }
+    let mut contributions = vec![];
+    for (i, response) in responses.iter().enumerate() {
+        let next_challenge = match responses.get(i + 1) {
+            Some(next_response) => GenericArray::clone_from_slice(&next_response[0..parameters.hash_size]),
+            None => challenge_hash,
+        };
+        let contribution = PtauContribution::from_response(
+            response,
+            CONTRIBUTION_IS_COMPRESSED,
+            check_input_correctness,
+            next_challenge,
+            parameters,
+        )?;
+        contributions.push(contribution);
+    }
+
+    let accumulator = Phase1::deserialize(
+        challenge.contents(),
+        CHALLENGE_IS_COMPRESSED,
+        check_input_correctness,
+        parameters,
+    )?;
+
+    let writer = OpenOptions::new().write(true).create_new(true).open(ptau_filename)?;
+    let mut writer = BufWriter::new(writer);
+    accumulator.write_ptau(&mut writer, &contributions)?;
+    writer.flush()?;
+
+    info!(
+        "Exported the challenge with {} contributions to {}",
+        contributions.len(),
+        ptau_filename
+    );
+
+    Ok(())
+}
+
+/// Converts a snarkjs `.ptau` file to a challenge which can be contributed to.
+pub fn import_ptau<T: Engine + Sync>(
+    ptau_filename: &str,
+    challenge_filename: &str,
+    challenge_hash_filename: &str,
+    check_input_correctness: CheckForCorrectness,
+    parameters: &Phase1Parameters<T>,
+) -> Result<()>
+where
+    T::G1Affine: SnarkjsPoint,
+    T::G2Affine: SnarkjsPoint,
+{
+    info!("Will import a ptau file as a challenge");
+
+    let reader = OpenOptions::new().read(true).open(ptau_filename)?;
+    let ptau_map = unsafe { MmapOptions::new().map(&reader)? };
+
+    let (accumulator, contributions) = Phase1::read_ptau(&ptau_map, check_input_correctness, parameters)?;
+    info!("The ptau file contains {} contributions", contributions.len());
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(challenge_filename)?;
+    file.set_len(parameters.accumulator_size as u64)?;
+    let mut writable_map = unsafe { MmapOptions::new().map_mut(&file)? };
+
+    (&mut writable_map[0..]).write_all(accumulator.hash.as_slice())?;
+    accumulator.serialize(&mut writable_map, CHALLENGE_IS_COMPRESSED, parameters)?;
+    writable_map.flush()?;
+
+    let output_readonly = writable_map.make_read_only()?;
+    let challenge_hash = calculate_hash(&output_readonly);
+    write_to_file(challenge_hash_filename, challenge_hash.as_slice())?;
+
+    info!("Imported challenge has a hash:");
+    print_hash(&challenge_hash);
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use phase1::{Phase1, Phase1Parameters};
+use setup_utils::{
+    calculate_hash,
+    header_length,
+    print_hash,
+    reduced_hash,
+    write_to_file,
+    CheckForCorrectness,
+    Result,
+    UseCompression,
+};
+
+use ark_ec::pairing::Pairing as Engine;
+
+use memmap::*;
+use std::{fs::OpenOptions, io::Write};
+use tracing::info;
+
+use crate::file_headers::{write_header, InputFile};
+
+const CHALLENGE_IS_COMPRESSED: UseCompression = UseCompression::No;
+
+/// Reduces a full Groth16 challenge to the challenge of a smaller power, whose hash is
+/// `reduced_hash(old_power, new_power)` in place of the hash of a previous response. The reduced
+/// challenge starts with a header if the challenge does.
+pub fn reduce<T: Engine + Sync>(
+    challenge_filename: &str,
+    reduced_challenge_filename: &str,
+    reduced_challenge_hash_filename: &str,
+    check_input_correctness: CheckForCorrectness,
+    parameters: &Phase1Parameters<T>,
+    reduced_parameters: &Phase1Parameters<T>,
+) -> Result<()> {
+    info!(
+        "Will reduce the challenge from 2^{} to 2^{} powers of tau",
+        parameters.total_size_in_log2, reduced_parameters.total_size_in_log2
+    );
+
+    let challenge = InputFile::open(
+        challenge_filename,
+        parameters.accumulator_size,
+        &parameters.file_header(CHALLENGE_IS_COMPRESSED),
+    )?;
+
+    let header = challenge
+        .header
+        .as_ref()
+        .map(|_| reduced_parameters.file_header(CHALLENGE_IS_COMPRESSED));
+    let offset = header_length(header.as_ref());
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(reduced_challenge_filename)?;
+    file.set_len((offset + reduced_parameters.accumulator_size) as u64)?;
+    let mut writable_map = unsafe { MmapOptions::new().map_mut(&file)? };
+    write_header(&mut writable_map, reduced_challenge_filename, header.as_ref(), false)?;
+
+    let hash = reduced_hash(
+        parameters.total_size_in_log2 as u8,
+        reduced_parameters.total_size_in_log2 as u8,
+    );
+    (&mut writable_map[offset..]).write_all(hash.as_slice())?;
+    info!("Reduced hash for the challenge:");
+    print_hash(&hash);
+
+    Phase1::reduce(
+        challenge.contents(),
+        &mut writable_map[offset..],
+        CHALLENGE_IS_COMPRESSED,
+        check_input_correctness,
+        parameters,
+        reduced_parameters,
+    )?;
+    writable_map.flush()?;
+
+    let output_readonly = writable_map.make_read_only()?;
+    let challenge_hash = calculate_hash(&output_readonly[offset..]);
+    write_to_file(reduced_challenge_hash_filename, challenge_hash.as_slice())?;
+
+    info!("Reduced challenge is formed with a hash:");
+    print_hash(&challenge_hash);
+    info!("Wrote the reduced challenge to {}", reduced_challenge_filename);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, new_challenge, test_dir::TestDir, transform_pok_and_correctness, transform_ratios};
+    use setup_utils::{
+        converters::ProvingSystem,
+        derive_rng_from_seed,
+        BatchExpMode,
+        Error,
+        FileHeader,
+        SubgroupCheckMode,
+        FILE_HEADER_SIZE,
+    };
+
+    use ark_bls12_377::Bls12_377;
+
+    use std::fs;
+
+    #[test]
+    fn test_reduce() {
+        let dir = TestDir::new("phase1-reduce");
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 4, 4);
+        let reduced_parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 2, 4);
+
+        new_challenge(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            None,
+            true,
+            &parameters,
+        )
+        .unwrap();
+        contribute(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            &dir.path("response"),
+            &dir.path("response.hash"),
+            false,
+            CheckForCorrectness::No,
+            BatchExpMode::Auto,
+            &parameters,
+            derive_rng_from_seed(b"alice"),
+        )
+        .unwrap();
+        transform_pok_and_correctness(
+            &dir.path("challenge"),
+            &dir.path("challenge.verified.hash"),
+            CheckForCorrectness::No,
+            &dir.path("response"),
+            &dir.path("response.verified.hash"),
+            CheckForCorrectness::Full,
+            &dir.path("new_challenge"),
+            &dir.path("new_challenge.hash"),
+            None,
+            false,
+            SubgroupCheckMode::Auto,
+            false,
+            &parameters,
+        )
+        .unwrap();
+
+        reduce(
+            &dir.path("new_challenge"),
+            &dir.path("reduced_challenge"),
+            &dir.path("reduced_challenge.hash"),
+            CheckForCorrectness::Full,
+            &parameters,
+            &reduced_parameters,
+        )
+        .unwrap();
+        let reduced_challenge = fs::read(dir.path("reduced_challenge")).unwrap();
+        assert_eq!(
+            FileHeader::from_bytes(&reduced_challenge).unwrap(),
+            Some(reduced_parameters.file_header(UseCompression::No))
+        );
+        assert_eq!(
+            &reduced_challenge[FILE_HEADER_SIZE..][..64],
+            reduced_hash(4, 2).as_slice()
+        );
+
+        // the reduced challenge can be contributed to, and the contribution verifies
+        contribute(
+            &dir.path("reduced_challenge"),
+            &dir.path("reduced_challenge.hash"),
+            &dir.path("reduced_response"),
+            &dir.path("reduced_response.hash"),
+            false,
+            CheckForCorrectness::Full,
+            BatchExpMode::Auto,
+            &reduced_parameters,
+            derive_rng_from_seed(b"bob"),
+        )
+        .unwrap();
+        transform_pok_and_correctness(
+            &dir.path("reduced_challenge"),
+            &dir.path("reduced_challenge.verified.hash"),
+            CheckForCorrectness::Full,
+            &dir.path("reduced_response"),
+            &dir.path("reduced_response.verified.hash"),
+            CheckForCorrectness::Full,
+            &dir.path("reduced_new_challenge"),
+            &dir.path("reduced_new_challenge.hash"),
+            None,
+            false,
+            SubgroupCheckMode::Auto,
+            false,
+            &reduced_parameters,
+        )
+        .unwrap();
+        transform_ratios(
+            &dir.path("reduced_new_challenge"),
+            CheckForCorrectness::Full,
+            &reduced_parameters,
+        )
+        .unwrap();
+
+        // the challenge can't grow
+        let err = reduce(
+            &dir.path("reduced_challenge"),
+            &dir.path("grown_challenge"),
+            &dir.path("grown_challenge.hash"),
+            CheckForCorrectness::Full,
+            &reduced_parameters,
+            &parameters,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)), "{}", err);
+    }
+}
@@ -1,5 +1,5 @@
 use phase1::{Phase1, Phase1Parameters, ProvingSystem};
-use setup_utils::UseCompression;
+use setup_utils::{header_length, Result, UseCompression};
 
 use ark_ec::pairing::Pairing as Engine;
 
@@ -7,14 +7,20 @@ use memmap::*;
 use std::fs::OpenOptions;
 use tracing::info;
 
+use crate::file_headers::{write_header, InputFile};
+
 const CONTRIBUTION_IS_COMPRESSED: UseCompression = UseCompression::Yes;
 const COMPRESS_NEW_SPLIT: UseCompression = UseCompression::No;
 
-pub fn split<T: Engine + Sync>(chunk_filename_prefix: &str, combined_filename: &str, parameters: &Phase1Parameters<T>) {
+/// Splits a full response into a challenge per chunk. If the response starts with a file header,
+/// so does each of the challenges.
+pub fn split<T: Engine + Sync>(
+    chunk_filename_prefix: &str,
+    combined_filename: &str,
+    parameters: &Phase1Parameters<T>,
+) -> Result<()> {
     info!("Will split contributions");
 
-    let mut writers = vec![];
-
     let powers_length = 1 << parameters.total_size_in_log2;
     let powers_g1_length = (powers_length << 1) - 1;
     let powers_length_for_proving_system = match parameters.proving_system {
@@ -23,41 +29,7 @@ pub fn split<T: Engine + Sync>(chunk_filename_prefix: &str, combined_filename: &
     };
     let num_chunks = (powers_length_for_proving_system + parameters.chunk_size - 1) / parameters.chunk_size;
 
-    for chunk_index in 0..num_chunks {
-        let parameters =
-            parameters.into_chunk_parameters(parameters.contribution_mode, chunk_index, parameters.chunk_size);
-        let response_writer = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create_new(true)
-            .open(format!("{}_{}", chunk_filename_prefix, chunk_index))
-            .expect("unable open response file in this directory");
-
-        response_writer
-            .set_len(parameters.accumulator_size as u64)
-            .expect("must make output file large enough");
-
-        unsafe {
-            writers.push(
-                MmapOptions::new()
-                    .map_mut(&response_writer)
-                    .expect("should have mapped the writer"),
-            );
-        }
-    }
-
-    let reader = OpenOptions::new()
-        .read(true)
-        .open(combined_filename)
-        .expect("unable to read full file");
-
-    let mut readable_map = unsafe {
-        MmapOptions::new()
-            .map(&reader)
-            .expect("unable to create a memory map for input")
-    };
-
-    {
+    let input = {
         let parameters_for_input = Phase1Parameters::<T>::new(
             parameters.contribution_mode,
             0,
@@ -67,20 +39,42 @@ pub fn split<T: Engine + Sync>(chunk_filename_prefix: &str, combined_filename: &
             parameters.total_size_in_log2,
             parameters.batch_size,
         );
-        let metadata = reader
-            .metadata()
-            .expect("unable to get filesystem metadata for response file");
         let expected_response_length = match CONTRIBUTION_IS_COMPRESSED {
             UseCompression::Yes => parameters_for_input.contribution_size,
             UseCompression::No => parameters_for_input.accumulator_size,
         };
-        if metadata.len() != (expected_response_length as u64) {
-            panic!(
-                "The size of response file should be {}, but it's {}, so something isn't right.",
-                expected_response_length,
-                metadata.len()
-            );
-        }
+        let full_parameters = Phase1Parameters::<T>::new_full(
+            parameters.proving_system,
+            parameters.total_size_in_log2,
+            parameters.batch_size,
+        );
+        InputFile::open(
+            combined_filename,
+            expected_response_length,
+            &full_parameters.file_header(CONTRIBUTION_IS_COMPRESSED),
+        )?
+    };
+
+    // The challenges have a header if the response has one
+    let offset = header_length(input.header.as_ref());
+    let mut writers = vec![];
+
+    for chunk_index in 0..num_chunks {
+        let parameters =
+            parameters.into_chunk_parameters(parameters.contribution_mode, chunk_index, parameters.chunk_size);
+        let chunk_filename = format!("{}_{}", chunk_filename_prefix, chunk_index);
+        let response_writer = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&chunk_filename)?;
+
+        let output_header = input.header.map(|_| parameters.file_header(COMPRESS_NEW_SPLIT));
+        response_writer.set_len((offset + parameters.accumulator_size) as u64)?;
+
+        let mut writable_map = unsafe { MmapOptions::new().map_mut(&response_writer)? };
+        write_header(&mut writable_map, &chunk_filename, output_header.as_ref(), false)?;
+        writers.push(writable_map);
     }
 
     let parameters = Phase1Parameters::<T>::new(
@@ -92,19 +86,19 @@ pub fn split<T: Engine + Sync>(chunk_filename_prefix: &str, combined_filename: &
         parameters.total_size_in_log2,
         parameters.batch_size,
     );
-    let res = Phase1::split(
-        (&mut readable_map, CONTRIBUTION_IS_COMPRESSED),
+    Phase1::split(
+        (input.contents(), CONTRIBUTION_IS_COMPRESSED),
         writers
             .iter_mut()
-            .map(|r| (r.as_mut(), COMPRESS_NEW_SPLIT))
+            .map(|w| (&mut w[offset..], COMPRESS_NEW_SPLIT))
             .collect::<Vec<_>>(),
         &parameters,
-    );
-
-    if let Err(e) = res {
-        info!("Splitting failed: {}", e);
-        panic!("INVALID CONTRIBUTIONS!!!");
-    } else {
-        info!("Splitting succeeded!");
+    )?;
+    for writer in &writers {
+        writer.flush()?;
     }
+
+    info!("Splitting succeeded!");
+
+    Ok(())
 }
@@ -1,18 +1,43 @@
 use ark_ec::pairing::Pairing as Engine;
-use phase1::{Phase1, Phase1Parameters, PublicKey};
-use setup_utils::{calculate_hash, print_hash, write_to_file, CheckForCorrectness, SubgroupCheckMode, UseCompression};
+use phase1::{
+    checkpoint::ComputationCheckpoint,
+    transcript::{append_to_transcript, TranscriptEntry},
+    Phase1,
+    Phase1Parameters,
+    PublicKey,
+};
+use setup_utils::{
+    calculate_hash,
+    header_length,
+    print_hash,
+    write_to_file,
+    CheckForCorrectness,
+    Error,
+    Result,
+    SubgroupCheckMode,
+    UseCompression,
+};
 
 use memmap::*;
-use std::{
-    fs::OpenOptions,
-    io::{Read, Write},
-};
+use std::io::{Read, Write};
 use tracing::info;
 
+use crate::{
+    checkpoint::{open_output, read_checkpoint, remove_checkpoint, save_checkpoints, write_checkpoint},
+    file_headers::{write_header, InputFile},
+};
+
 const PREVIOUS_CHALLENGE_IS_COMPRESSED: UseCompression = UseCompression::No;
 const CONTRIBUTION_IS_COMPRESSED: UseCompression = UseCompression::Yes;
 const COMPRESS_NEW_CHALLENGE: UseCompression = UseCompression::No;
 
+/// Verifies the response to a challenge and writes the new challenge.
+///
+/// The progress is regularly saved next to the new challenge file. If the verification is
+/// interrupted, running it again with `resume` set carries on from the last checkpoint, without
+/// verifying or writing the batches which were already verified again.
+///
+/// If the challenge starts with a file header, the new challenge gets one too.
 pub fn transform_pok_and_correctness<T: Engine + Sync>(
     challenge_filename: &str,
     challenge_hash_filename: &str,
@@ -22,79 +47,47 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
     check_output_correctness: CheckForCorrectness,
     new_challenge_filename: &str,
     new_challenge_hash_filename: &str,
+    transcript_filename: Option<&str>,
+    resume: bool,
     subgroup_check_mode: SubgroupCheckMode,
     ratio_check: bool,
     parameters: &Phase1Parameters<T>,
-) {
+) -> Result<()> {
     info!(
         "Will verify and decompress a contribution to accumulator for 2^{} powers of tau",
         parameters.total_size_in_log2
     );
 
     // Try to load challenge file from disk.
-    let challenge_reader = OpenOptions::new()
-        .read(true)
-        .open(challenge_filename)
-        .expect("unable open challenge file in this directory");
-
-    {
-        let metadata = challenge_reader
-            .metadata()
-            .expect("unable to get filesystem metadata for challenge file");
-        let expected_challenge_length = match PREVIOUS_CHALLENGE_IS_COMPRESSED {
-            UseCompression::Yes => parameters.contribution_size - parameters.public_key_size,
-            UseCompression::No => parameters.accumulator_size,
-        };
-        if metadata.len() != (expected_challenge_length as u64) {
-            panic!(
-                "The size of challenge file should be {}, but it's {}, so something isn't right.",
-                expected_challenge_length,
-                metadata.len()
-            );
-        }
-    }
-
-    let challenge_readable_map = unsafe {
-        MmapOptions::new()
-            .map(&challenge_reader)
-            .expect("unable to create a memory map for input")
+    let expected_challenge_length = match PREVIOUS_CHALLENGE_IS_COMPRESSED {
+        UseCompression::Yes => parameters.contribution_size - parameters.public_key_size,
+        UseCompression::No => parameters.accumulator_size,
     };
+    let challenge = InputFile::open(
+        challenge_filename,
+        expected_challenge_length,
+        &parameters.file_header(PREVIOUS_CHALLENGE_IS_COMPRESSED),
+    )?;
+    let challenge_readable_map = challenge.contents();
 
     // Try to load response file from disk.
-    let response_reader = OpenOptions::new()
-        .read(true)
-        .open(response_filename)
-        .expect("unable open response file in this directory");
-
-    {
-        let metadata = response_reader
-            .metadata()
-            .expect("unable to get filesystem metadata for response file");
-        let expected_response_length = match CONTRIBUTION_IS_COMPRESSED {
-            UseCompression::Yes => parameters.contribution_size,
-            UseCompression::No => parameters.accumulator_size + parameters.public_key_size,
-        };
-        if metadata.len() != (expected_response_length as u64) {
-            panic!(
-                "The size of response file should be {}, but it's {}, so something isn't right.",
-                expected_response_length,
-                metadata.len()
-            );
-        }
-    }
-
-    let response_readable_map = unsafe {
-        MmapOptions::new()
-            .map(&response_reader)
-            .expect("unable to create a memory map for input")
+    let expected_response_length = match CONTRIBUTION_IS_COMPRESSED {
+        UseCompression::Yes => parameters.contribution_size,
+        UseCompression::No => parameters.accumulator_size + parameters.public_key_size,
     };
+    let response = InputFile::open(
+        response_filename,
+        expected_response_length,
+        &parameters.file_header(CONTRIBUTION_IS_COMPRESSED),
+    )?;
+    let response_readable_map = response.contents();
 
     info!("Calculating previous challenge hash...");
 
     // Check that contribution is correct
 
-    let current_accumulator_hash = calculate_hash(&challenge_readable_map);
-    write_to_file(challenge_hash_filename, current_accumulator_hash.as_slice());
+    let current_accumulator_hash = calculate_hash(challenge_readable_map);
+    write_to_file(challenge_hash_filename, current_accumulator_hash.as_slice())?;
 
     info!("Hash of the `challenge` file for verification:");
     print_hash(&current_accumulator_hash);
@@ -102,68 +95,68 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
     // Check the hash chain - a new response must be based on the previous challenge!
     {
         let mut response_challenge_hash = [0; 64];
-        let mut memory_slice = response_readable_map
-            .get(0..64)
-            .expect("must read point data from file");
-        memory_slice
-            .read_exact(&mut response_challenge_hash)
-            .expect("couldn't read hash of challenge file from response file");
+        let mut memory_slice = &response_readable_map[0..64];
+        memory_slice.read_exact(&mut response_challenge_hash)?;
 
         info!("`response` was based on the hash:");
         print_hash(&response_challenge_hash);
 
         if &response_challenge_hash[..] != current_accumulator_hash.as_slice() {
-            panic!("Hash chain failure. This is not the right response.");
+            return Err(Error::HashChainMismatch(response_filename.to_string()));
         }
     }
 
-    let response_hash = calculate_hash(&response_readable_map);
-    write_to_file(response_hash_filename, response_hash.as_slice());
+    let response_hash = calculate_hash(response_readable_map);
+    write_to_file(response_hash_filename, response_hash.as_slice())?;
 
     info!("Hash of the response file for verification:");
     print_hash(&response_hash);
 
     // get the contributor's public key
-    let public_key = PublicKey::read(&response_readable_map, CONTRIBUTION_IS_COMPRESSED, &parameters)
-        .expect("wasn't able to deserialize the response file's public key");
+    let public_key = PublicKey::read(response_readable_map, CONTRIBUTION_IS_COMPRESSED, &parameters)?;
 
     // check that it follows the protocol
 
     info!("Verifying a contribution to contain proper powers and correspond to the public key...");
 
-    // Create new challenge file in this directory
-    let writer = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create_new(true)
-        .open(new_challenge_filename)
-        .expect("unable to create new challenge file in this directory");
-
+    // Create new challenge file in this directory, or reopen it to resume the verification.
     // Recomputation strips the public key and uses hashing to link with the previous contribution after decompression
-    writer
-        .set_len(parameters.accumulator_size as u64)
-        .expect("must make output file large enough");
-
-    let mut writable_map = unsafe {
-        MmapOptions::new()
-            .map_mut(&writer)
-            .expect("unable to create a memory map for output")
-    };
-
-    {
-        (&mut writable_map[0..])
-            .write_all(response_hash.as_slice())
-            .expect("unable to write a default hash to mmap");
+    let output_header = challenge.header.map(|_| parameters.file_header(COMPRESS_NEW_CHALLENGE));
+    let offset = header_length(output_header.as_ref());
+
+    let resume_from = read_checkpoint(new_challenge_filename, resume)?;
+    let writer = open_output(
+        new_challenge_filename,
+        resume_from.is_some(),
+        offset + parameters.accumulator_size,
+    )?;
+
+    let mut writable_map = unsafe { MmapOptions::new().map_mut(&writer)? };
+    write_header(
+        &mut writable_map,
+        new_challenge_filename,
+        output_header.as_ref(),
+        resume_from.is_some(),
+    )?;
+
+    if resume_from.is_some() {
+        if writable_map[offset..offset + 64] != response_hash[..] {
+            return Err(Error::CannotResume(format!(
+                "{} was produced from another response",
+                new_challenge_filename
+            )));
+        }
+    } else {
+        (&mut writable_map[offset..]).write_all(response_hash.as_slice())?;
 
-        writable_map
-            .flush()
-            .expect("unable to write hash to new challenge file");
+        writable_map.flush()?;
+        write_checkpoint(&writer, new_challenge_filename, &ComputationCheckpoint::default())?;
     }
 
-    let res = Phase1::verification(
-        &challenge_readable_map,
-        &response_readable_map,
-        &mut writable_map,
+    let res = Phase1::verification_with_checkpoints(
+        challenge_readable_map,
+        response_readable_map,
+        &mut writable_map[offset..],
         &public_key,
         current_accumulator_hash.as_slice(),
         PREVIOUS_CHALLENGE_IS_COMPRESSED,
@@ -174,17 +167,17 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
         subgroup_check_mode,
         ratio_check,
         &parameters,
+        resume_from.unwrap_or_default(),
+        save_checkpoints(&writer, new_challenge_filename),
     );
 
-    info!("Verification succeeded!");
-
-    writable_map.flush().expect("must flush the memory map");
+    writable_map.flush()?;
 
-    let new_challenge_readable_map = writable_map.make_read_only().expect("must make a map readonly");
+    let new_challenge_readable_map = writable_map.make_read_only()?;
 
-    let recompressed_hash = calculate_hash(&new_challenge_readable_map);
+    let recompressed_hash = calculate_hash(&new_challenge_readable_map[offset..]);
 
-    write_to_file(new_challenge_hash_filename, recompressed_hash.as_slice());
+    write_to_file(new_challenge_hash_filename, recompressed_hash.as_slice())?;
 
     info!("Here's the BLAKE2b hash of the decompressed participant's response as new_challenge file:");
     print_hash(&recompressed_hash);
@@ -193,8 +186,110 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
 
     if let Err(e) = res {
         info!("Verification failed: {}", e);
-        panic!("INVALID CONTRIBUTION!!!");
-    } else {
-        info!("Verification succeeded!");
+        return Err(e);
+    }
+    info!("Verification succeeded!");
+
+    if let Some(transcript_filename) = transcript_filename {
+        let entry = TranscriptEntry {
+            chunk_index: parameters.chunk_index,
+            challenge_hash: current_accumulator_hash,
+            response_hash,
+            new_challenge_hash: recompressed_hash,
+            public_key,
+        };
+        append_to_transcript(transcript_filename, &entry)?;
+        info!("Appended the contribution to the transcript {}", transcript_filename);
+    }
+
+    remove_checkpoint(new_challenge_filename)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, new_challenge, test_dir::TestDir};
+    use phase1::{
+        transcript::{read_transcript, verify_transcript},
+        CurveParameters,
+    };
+    use setup_utils::{
+        converters::{ContributionMode, ProvingSystem},
+        derive_rng_from_seed,
+        BatchExpMode,
+    };
+
+    use ark_bls12_377::Bls12_377;
+
+    use std::fs;
+
+    #[test]
+    fn test_resume_after_transcript_append() {
+        let dir = TestDir::new("phase1-resume-transcript");
+
+        let parameters = Phase1Parameters::<Bls12_377>::new(
+            ContributionMode::Full,
+            0,
+            0,
+            CurveParameters::<Bls12_377>::new(),
+            ProvingSystem::Groth16,
+            3,
+            4,
+        );
+        new_challenge(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            None,
+            false,
+            &parameters,
+        )
+        .unwrap();
+        contribute(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            &dir.path("response"),
+            &dir.path("response.hash"),
+            false,
+            CheckForCorrectness::Full,
+            BatchExpMode::Auto,
+            &parameters,
+            derive_rng_from_seed(b"alice"),
+        )
+        .unwrap();
+        let transform = |resume: bool| {
+            transform_pok_and_correctness(
+                &dir.path("challenge"),
+                &dir.path("challenge.verified.hash"),
+                CheckForCorrectness::Full,
+                &dir.path("response"),
+                &dir.path("response.verified.hash"),
+                CheckForCorrectness::Full,
+                &dir.path("new_challenge"),
+                &dir.path("new_challenge.verified.hash"),
+                Some(&dir.path("transcript")),
+                resume,
+                SubgroupCheckMode::Auto,
+                false,
+                &parameters,
+            )
+        };
+        transform(false).unwrap();
+
+        // the verification was interrupted after appending to the transcript but before removing
+        // its checkpoint, so resuming it verifies the response again
+        let new_challenge = fs::File::open(dir.path("new_challenge")).unwrap();
+        write_checkpoint(
+            &new_challenge,
+            &dir.path("new_challenge"),
+            &ComputationCheckpoint::default(),
+        )
+        .unwrap();
+        transform(true).unwrap();
+
+        let entries = read_transcript::<Bls12_377>(&fs::read(dir.path("transcript")).unwrap()).unwrap();
+        assert_eq!(entries.len(), 1);
+        verify_transcript(&entries).unwrap();
     }
 }
@@ -1,29 +1,24 @@
 use phase1::{Phase1, Phase1Parameters};
-use setup_utils::{calculate_hash, print_hash, CheckForCorrectness, UseCompression};
+use setup_utils::{calculate_hash, print_hash, CheckForCorrectness, Result, UseCompression};
 
 use ark_ec::pairing::Pairing as Engine;
 
-use memmap::*;
-use std::fs::OpenOptions;
 use tracing::info;
 
+use crate::file_headers::InputFile;
+
 pub fn transform_ratios<T: Engine + Sync>(
     response_filename: &str,
     check_input_correctness: CheckForCorrectness,
     parameters: &Phase1Parameters<T>,
-) {
+) -> Result<()> {
     info!(
         "Will verify and decompress a contribution to accumulator for 2^{} powers of tau",
         parameters.total_size_in_log2
     );
 
     // Try to load response file from disk.
-    let response_reader = OpenOptions::new()
-        .read(true)
-        .open(response_filename)
-        .expect("unable open response file in this directory");
-
-    {
+    let response = {
         let parameters = Phase1Parameters::<T>::new_chunk(
             parameters.contribution_mode,
             0,
@@ -32,26 +27,21 @@ pub fn transform_ratios<T: Engine + Sync>(
             parameters.total_size_in_log2,
             parameters.batch_size,
         );
-        let metadata = response_reader
-            .metadata()
-            .expect("unable to get filesystem metadata for response file");
-        let expected_response_length = parameters.accumulator_size;
-        if metadata.len() != (expected_response_length as u64) {
-            panic!(
-                "The size of response file should be {}, but it's {}, so something isn't right.",
-                expected_response_length,
-                metadata.len()
-            );
-        }
-    }
-
-    let response_readable_map = unsafe {
-        MmapOptions::new()
-            .map(&response_reader)
-            .expect("unable to create a memory map for input")
+        // The combined response covers the full accumulator
+        let full_parameters = Phase1Parameters::<T>::new_full(
+            parameters.proving_system,
+            parameters.total_size_in_log2,
+            parameters.batch_size,
+        );
+        InputFile::open(
+            response_filename,
+            parameters.accumulator_size,
+            &full_parameters.file_header(UseCompression::No),
+        )?
     };
+    let response_readable_map = response.contents();
 
-    let response_hash = calculate_hash(&response_readable_map);
+    let response_hash = calculate_hash(response_readable_map);
 
     info!("Hash of the response file for verification:");
     print_hash(&response_hash);
@@ -59,15 +49,12 @@ pub fn transform_ratios<T: Engine + Sync>(
     // check that it follows the protocol
     info!("Verifying a contribution to contain proper powers and correspond to the public key...");
 
-    let res = Phase1::aggregate_verification(
-        (&response_readable_map, UseCompression::No, check_input_correctness),
+    Phase1::aggregate_verification(
+        (response_readable_map, UseCompression::No, check_input_correctness),
         &parameters,
-    );
+    )?;
+
+    info!("Verification succeeded!");
 
-    if let Err(e) = res {
-        info!("Verification failed: {}", e);
-        panic!("INVALID CONTRIBUTION!!!");
-    } else {
-        info!("Verification succeeded!");
-    }
+    Ok(())
 }
@@ -0,0 +1,174 @@
+use phase1::{Phase1, Phase1Parameters, PublicKey};
+use setup_utils::{calculate_hash, derive_rng_from_seed, print_hash, BeaconCheckpoints, Error, Result, UseCompression};
+
+use ark_ec::pairing::Pairing as Engine;
+
+use tracing::info;
+
+use crate::file_headers::InputFile;
+
+const CHALLENGE_IS_COMPRESSED: UseCompression = UseCompression::No;
+const CONTRIBUTION_IS_COMPRESSED: UseCompression = UseCompression::Yes;
+
+/// Verifies a beacon contribution: the checkpoints must be the ones of the expected beacon and
+/// number of iterations, their intermediate states are re-checked in parallel, and the public key
+/// of the response must be the one derived from the final state and the hash of the challenge.
+/// The contribution itself is verified by the usual commands.
+pub fn verify_beacon<T: Engine + Sync>(
+    challenge_filename: &str,
+    response_filename: &str,
+    checkpoints_filename: &str,
+    beacon_hash: [u8; 32],
+    iterations_exp: u32,
+    allow_low_iterations: bool,
+    parameters: &Phase1Parameters<T>,
+) -> Result<()> {
+    info!("Will verify the beacon contribution {}", response_filename);
+
+    let checkpoints = BeaconCheckpoints::read(checkpoints_filename)?;
+    info!(
+        "Verifying the {} checkpoints of 2^{} iterations over the beacon hash {}",
+        checkpoints.checkpoints.len(),
+        checkpoints.iterations_exp,
+        hex::encode(checkpoints.beacon_hash)
+    );
+    checkpoints.check_beacon(beacon_hash, iterations_exp, allow_low_iterations)?;
+    checkpoints.verify()?;
+
+    let challenge = InputFile::open(
+        challenge_filename,
+        parameters.accumulator_size,
+        &parameters.file_header(CHALLENGE_IS_COMPRESSED),
+    )?;
+    let challenge_hash = calculate_hash(challenge.contents());
+    info!("`challenge` file has a hash:");
+    print_hash(&challenge_hash);
+
+    let response = InputFile::open(
+        response_filename,
+        parameters.contribution_size,
+        &parameters.file_header(CONTRIBUTION_IS_COMPRESSED),
+    )?;
+    let response = response.contents();
+    if response[..64] != challenge_hash[..] {
+        return Err(Error::HashChainMismatch(response_filename.to_string()));
+    }
+    let public_key = PublicKey::<T>::read(response, CONTRIBUTION_IS_COMPRESSED, parameters)?;
+
+    let mut rng = derive_rng_from_seed(&checkpoints.result);
+    let (expected_public_key, _) = Phase1::key_generation(&mut rng, challenge_hash.as_ref())?;
+    if public_key != expected_public_key {
+        return Err(Error::InvalidBeacon(format!(
+            "the public key of {} was not derived from the beacon",
+            response_filename
+        )));
+    }
+
+    info!(
+        "The beacon contribution was derived from the beacon result {}",
+        hex::encode(checkpoints.result)
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, new_challenge, test_dir::TestDir};
+    use setup_utils::{
+        beacon_hash_from_str,
+        converters::{ContributionMode, ProvingSystem},
+        BatchExpMode,
+        CheckForCorrectness,
+    };
+
+    use ark_bls12_377::Bls12_377;
+    use phase1::CurveParameters;
+
+    #[test]
+    fn test_verify_beacon() {
+        let dir = TestDir::new("phase1-verify-beacon");
+
+        let parameters = Phase1Parameters::<Bls12_377>::new(
+            ContributionMode::Full,
+            0,
+            0,
+            CurveParameters::<Bls12_377>::new(),
+            ProvingSystem::Groth16,
+            3,
+            4,
+        );
+        new_challenge(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            None,
+            false,
+            &parameters,
+        )
+        .unwrap();
+
+        let beacon_hash =
+            beacon_hash_from_str("0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620").unwrap();
+        let checkpoints = BeaconCheckpoints::read_or_compute(dir.path("beacon.checkpoints"), beacon_hash, 4).unwrap();
+        let beacon_contribution = |response: &str, seed: &[u8]| {
+            contribute(
+                &dir.path("challenge"),
+                &dir.path("challenge.hash"),
+                &dir.path(response),
+                &dir.path(&format!("{}.hash", response)),
+                false,
+                CheckForCorrectness::Full,
+                BatchExpMode::Auto,
+                &parameters,
+                derive_rng_from_seed(seed),
+            )
+            .unwrap()
+        };
+        let verify_with = |response: &str, iterations_exp: u32, allow_low_iterations: bool| {
+            verify_beacon(
+                &dir.path("challenge"),
+                &dir.path(response),
+                &dir.path("beacon.checkpoints"),
+                beacon_hash,
+                iterations_exp,
+                allow_low_iterations,
+                &parameters,
+            )
+        };
+        let verify = |response: &str| verify_with(response, 4, true);
+
+        beacon_contribution("response", &checkpoints.result);
+        verify("response").unwrap();
+
+        // so few iterations are only accepted when asked for
+        let err = verify_with("response", 4, false).unwrap_err();
+        assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+
+        // checkpoints which follow from each other, but for another beacon or fewer iterations
+        let mut other_beacon = beacon_hash;
+        other_beacon[31] ^= 1;
+        for (response, other_hash, other_exp) in
+            [("other_beacon", other_beacon, 4), ("fewer_iterations", beacon_hash, 3)]
+        {
+            let other = BeaconCheckpoints::compute(other_hash, other_exp).unwrap();
+            other.write(dir.path("beacon.checkpoints")).unwrap();
+            beacon_contribution(response, &other.result);
+            let err = verify(response).unwrap_err();
+            assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+        }
+        checkpoints.write(dir.path("beacon.checkpoints")).unwrap();
+
+        // a contribution which wasn't seeded by the beacon
+        beacon_contribution("other_response", b"not the beacon");
+        let err = verify("other_response").unwrap_err();
+        assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+
+        // checkpoints which don't follow from each other
+        let mut tampered = checkpoints;
+        tampered.checkpoints[3][0] ^= 1;
+        tampered.write(dir.path("beacon.checkpoints")).unwrap();
+        let err = verify("response").unwrap_err();
+        assert_eq!(err.exit_code(), Error::EXIT_CODE_VERIFICATION);
+    }
+}
@@ -0,0 +1,29 @@
+use phase1::transcript::{read_transcript, verify_transcript as verify_transcript_entries};
+use setup_utils::{print_hash, Result};
+
+use ark_ec::pairing::Pairing as Engine;
+
+use tracing::info;
+
+/// Re-checks the hash chain and the proofs of knowledge of every contribution recorded in a
+/// transcript, without needing any of the challenges or responses.
+pub fn verify_transcript<T: Engine>(transcript_filename: &str) -> Result<()> {
+    info!("Will verify the transcript {}", transcript_filename);
+
+    let transcript = std::fs::read(transcript_filename)?;
+    let entries = read_transcript::<T>(&transcript)?;
+
+    for (i, entry) in entries.iter().enumerate() {
+        info!("Contribution {} to chunk {} has a response hash:", i, entry.chunk_index);
+        print_hash(&entry.response_hash);
+    }
+
+    if let Err(e) = verify_transcript_entries(&entries) {
+        info!("Transcript verification failed: {}", e);
+        return Err(e);
+    }
+
+    info!("Verified {} contributions, the transcript is valid!", entries.len());
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use super::*;
+use setup_utils::rayon_cfg::FirstError;
 
 impl<'a, E: Pairing + Sync> Phase1<'a, E> {
     ///
@@ -38,22 +39,24 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
             let span = info_span!("batch", start, end);
             let _enter = span.enter();
 
+            let errors = FirstError::default();
             match parameters.proving_system {
                 ProvingSystem::Groth16 => {
                     rayon::scope(|t| {
                         let _enter = span.enter();
 
                         t.spawn(|_| {
-                            let _enter = span.enter();
+                            errors.run(|| {
+                                let _enter = span.enter();
 
-                            let elements: Vec<E::G1Affine> = in_tau_g1
-                                .read_batch(compressed_input, CheckForCorrectness::No)
-                                .expect("should have read batch");
-                            tau_g1
-                                .write_batch(&elements, compressed_output)
-                                .expect("should have written batch");
+                                let elements: Vec<E::G1Affine> = in_tau_g1
+                                    .read_batch(compressed_input, CheckForCorrectness::No)
+                                    .map_err(|e| e.with_element_type(ElementType::TauG1))?;
+                                tau_g1.write_batch(&elements, compressed_output)?;
 
-                            trace!("tau_g1 aggregation for chunk {} successful", chunk_index);
+                                trace!("tau_g1 aggregation for chunk {} successful", chunk_index);
+                                Ok(())
+                            })
                         });
 
                         if start < chunk_parameters.powers_length {
@@ -61,54 +64,58 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                                 let _enter = span.enter();
 
                                 t.spawn(|_| {
-                                    let _enter = span.enter();
+                                    errors.run(|| {
+                                        let _enter = span.enter();
 
-                                    let elements: Vec<E::G2Affine> = in_tau_g2
-                                        .read_batch(compressed_input, CheckForCorrectness::No)
-                                        .expect("should have read batch");
-                                    tau_g2
-                                        .write_batch(&elements, compressed_output)
-                                        .expect("should have written batch");
+                                        let elements: Vec<E::G2Affine> = in_tau_g2
+                                            .read_batch(compressed_input, CheckForCorrectness::No)
+                                            .map_err(|e| e.with_element_type(ElementType::TauG2))?;
+                                        tau_g2.write_batch(&elements, compressed_output)?;
 
-                                    trace!("tau_g2 aggregation for chunk {} successful", chunk_index);
+                                        trace!("tau_g2 aggregation for chunk {} successful", chunk_index);
+                                        Ok(())
+                                    })
                                 });
 
                                 t.spawn(|_| {
-                                    let _enter = span.enter();
+                                    errors.run(|| {
+                                        let _enter = span.enter();
 
-                                    let elements: Vec<E::G1Affine> = in_alpha_g1
-                                        .read_batch(compressed_input, CheckForCorrectness::No)
-                                        .expect("should have read batch");
-                                    alpha_g1
-                                        .write_batch(&elements, compressed_output)
-                                        .expect("should have written batch");
+                                        let elements: Vec<E::G1Affine> = in_alpha_g1
+                                            .read_batch(compressed_input, CheckForCorrectness::No)
+                                            .map_err(|e| e.with_element_type(ElementType::AlphaG1))?;
+                                        alpha_g1.write_batch(&elements, compressed_output)?;
 
-                                    trace!("alpha_g1 aggregation for chunk {} successful", chunk_index);
+                                        trace!("alpha_g1 aggregation for chunk {} successful", chunk_index);
+                                        Ok(())
+                                    })
                                 });
 
                                 t.spawn(|_| {
-                                    let _enter = span.enter();
+                                    errors.run(|| {
+                                        let _enter = span.enter();
 
-                                    let elements: Vec<E::G1Affine> = in_beta_g1
-                                        .read_batch(compressed_input, CheckForCorrectness::No)
-                                        .expect("should have read batch");
-                                    beta_g1
-                                        .write_batch(&elements, compressed_output)
-                                        .expect("should have written batch");
+                                        let elements: Vec<E::G1Affine> = in_beta_g1
+                                            .read_batch(compressed_input, CheckForCorrectness::No)
+                                            .map_err(|e| e.with_element_type(ElementType::BetaG1))?;
+                                        beta_g1.write_batch(&elements, compressed_output)?;
 
-                                    trace!("beta_g1 aggregation for chunk {} successful", chunk_index);
+                                        trace!("beta_g1 aggregation for chunk {} successful", chunk_index);
+                                        Ok(())
+                                    })
                                 });
                             });
                         }
 
                         if chunk_index == 0 {
-                            let element: E::G2Affine = (&*in_beta_g2)
-                                .read_element(compressed_input, CheckForCorrectness::No)
-                                .expect("should have read element");
-                            beta_g2
-                                .write_element(&element, compressed_output)
-                                .expect("should have written element");
-                            trace!("beta_g2 aggregation for chunk {} successful", chunk_index);
+                            errors.run(|| {
+                                let element: E::G2Affine = (&*in_beta_g2)
+                                    .read_element(compressed_input, CheckForCorrectness::No)
+                                    .map_err(|e| e.with_element_type(ElementType::BetaG2))?;
+                                beta_g2.write_element(&element, compressed_output)?;
+                                trace!("beta_g2 aggregation for chunk {} successful", chunk_index);
+                                Ok(())
+                            });
                         }
                     });
                 }
@@ -118,16 +125,17 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                         let _enter = span.enter();
 
                         t.spawn(|_| {
-                            let _enter = span.enter();
+                            errors.run(|| {
+                                let _enter = span.enter();
 
-                            let elements: Vec<E::G1Affine> = in_tau_g1
-                                .read_batch(compressed_input, CheckForCorrectness::No)
-                                .expect("should have read batch");
-                            tau_g1
-                                .write_batch(&elements, compressed_output)
-                                .expect("should have written batch");
+                                let elements: Vec<E::G1Affine> = in_tau_g1
+                                    .read_batch(compressed_input, CheckForCorrectness::No)
+                                    .map_err(|e| e.with_element_type(ElementType::TauG1))?;
+                                tau_g1.write_batch(&elements, compressed_output)?;
 
-                            trace!("tau_g1 aggregation for chunk {} successful", chunk_index);
+                                trace!("tau_g1 aggregation for chunk {} successful", chunk_index);
+                                Ok(())
+                            })
                         });
 
                         // handle tau G2
@@ -135,16 +143,17 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                             rayon::scope(|t| {
                                 let _enter = span.enter();
                                 t.spawn(|_| {
-                                    let _enter = span.enter();
+                                    errors.run(|| {
+                                        let _enter = span.enter();
 
-                                    let elements: Vec<E::G2Affine> = in_tau_g2
-                                        .read_batch(compressed_input, CheckForCorrectness::No)
-                                        .expect("should have read batch");
-                                    tau_g2
-                                        .write_batch(&elements, compressed_output)
-                                        .expect("should have written batch");
+                                        let elements: Vec<E::G2Affine> = in_tau_g2
+                                            .read_batch(compressed_input, CheckForCorrectness::No)
+                                            .map_err(|e| e.with_element_type(ElementType::TauG2))?;
+                                        tau_g2.write_batch(&elements, compressed_output)?;
 
-                                    trace!("tau_g2 aggregation for chunk {} successful", chunk_index);
+                                        trace!("tau_g2 aggregation for chunk {} successful", chunk_index);
+                                        Ok(())
+                                    })
                                 });
                             });
                         }
@@ -154,16 +163,17 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                                 let _enter = span.enter();
 
                                 t.spawn(|_| {
-                                    let _enter = span.enter();
+                                    errors.run(|| {
+                                        let _enter = span.enter();
 
-                                    let elements: Vec<E::G1Affine> = in_alpha_g1
-                                        .read_batch(compressed_input, CheckForCorrectness::No)
-                                        .expect("should have read batch");
-                                    alpha_g1
-                                        .write_batch(&elements, compressed_output)
-                                        .expect("should have written batch");
+                                        let elements: Vec<E::G1Affine> = in_alpha_g1
+                                            .read_batch(compressed_input, CheckForCorrectness::No)
+                                            .map_err(|e| e.with_element_type(ElementType::AlphaG1))?;
+                                        alpha_g1.write_batch(&elements, compressed_output)?;
 
-                                    trace!("alpha_g1 aggregation for chunk {} successful", chunk_index);
+                                        trace!("alpha_g1 aggregation for chunk {} successful", chunk_index);
+                                        Ok(())
+                                    })
                                 });
                             });
                         }
@@ -171,6 +181,10 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                 }
             }
 
+            errors
+                .into_result()
+                .map_err(|e| e.with_batch(start, end).with_chunk_index(chunk_index))?;
+
             debug!("chunk {} processing successful", chunk_index);
         }
 
@@ -0,0 +1,84 @@
+//! Checkpoints of a contribution or of its verification, allowing an interrupted run to be
+//! resumed.
+//!
+//! Both process the accumulator in batches. After every batch, the output it wrote (the response
+//! or the new challenge) is hashed on top of the hash of the previous batches. A checkpoint
+//! records how many batches were completed along with that hash, so that the partial output can
+//! be checked before carrying on from the next batch.
+use setup_utils::{blank_hash, buffer_size, GenericArray, HashWriter, Result, UseCompression, U64};
+
+use ark_ec::pairing::Pairing;
+
+use std::io::{self, Read, Write};
+
+/// The progress of a contribution or of a verification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComputationCheckpoint {
+    /// The number of batches which were completed
+    pub completed_batches: usize,
+    /// The hash of the output of these batches
+    pub batch_hash: GenericArray<u8, U64>,
+}
+
+impl Default for ComputationCheckpoint {
+    /// The checkpoint of a run which has not started yet
+    fn default() -> Self {
+        Self {
+            completed_batches: 0,
+            batch_hash: blank_hash(),
+        }
+    }
+}
+
+impl ComputationCheckpoint {
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(&(self.completed_batches as u64).to_le_bytes())?;
+        writer.write_all(self.batch_hash.as_slice())?;
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self> {
+        let mut completed_batches = [0u8; 8];
+        reader.read_exact(&mut completed_batches)?;
+        let mut batch_hash = GenericArray::default();
+        reader.read_exact(batch_hash.as_mut_slice())?;
+        Ok(Self {
+            completed_batches: u64::from_le_bytes(completed_batches) as usize,
+            batch_hash,
+        })
+    }
+
+    /// Returns the checkpoint reached after the batch `[start, end)`, given the output
+    /// buffers it was written to, in the order [TauG1, TauG2, AlphaG1, BetaG1].
+    /// Only the elements which exist in each buffer are hashed.
+    pub(crate) fn next<E: Pairing>(
+        &self,
+        outputs: [&[u8]; 4],
+        (start, end): (usize, usize),
+        compressed: UseCompression,
+    ) -> Self {
+        let element_sizes = [
+            buffer_size::<E::G1Affine>(compressed),
+            buffer_size::<E::G2Affine>(compressed),
+            buffer_size::<E::G1Affine>(compressed),
+            buffer_size::<E::G1Affine>(compressed),
+        ];
+
+        let mut hasher = HashWriter::new(io::sink());
+        hasher
+            .write_all(&self.batch_hash)
+            .expect("writing to a sink cannot fail");
+        for (output, element_size) in outputs.iter().zip(element_sizes) {
+            let len = output.len() / element_size;
+            let (start, end) = (start.min(len), end.min(len));
+            hasher
+                .write_all(&output[start * element_size..end * element_size])
+                .expect("writing to a sink cannot fail");
+        }
+
+        Self {
+            completed_batches: self.completed_batches + 1,
+            batch_hash: hasher.into_hash(),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::ops::Mul;
 
 use ark_ff::{batch_inversion, Field};
 
-use super::*;
+use super::{checkpoint::ComputationCheckpoint, *};
 
 impl<'a, E: Pairing + Sync> Phase1<'a, E> {
     ///
@@ -22,6 +22,41 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
         batch_exp_mode: BatchExpMode,
         key: &PrivateKey<E>,
         parameters: &'a Phase1Parameters<E>,
+    ) -> Result<()> {
+        Self::computation_with_checkpoints(
+            input,
+            output,
+            compressed_input,
+            compressed_output,
+            check_input_for_correctness,
+            batch_exp_mode,
+            key,
+            parameters,
+            ComputationCheckpoint::default(),
+            |_| Ok(()),
+        )
+    }
+
+    ///
+    /// Same as `computation`, but resumes from the checkpoint `resume_from` and calls
+    /// `on_checkpoint` after every batch.
+    ///
+    /// The output of the batches completed before the checkpoint must already be in the output
+    /// buffer: it is hashed again and compared to the checkpoint before the computation carries
+    /// on, so that a corrupted partial output is not silently extended. The key must be the one
+    /// which was used to compute the partial output.
+    ///
+    pub fn computation_with_checkpoints(
+        input: &[u8],
+        output: &mut [u8],
+        compressed_input: UseCompression,
+        compressed_output: UseCompression,
+        check_input_for_correctness: CheckForCorrectness,
+        batch_exp_mode: BatchExpMode,
+        key: &PrivateKey<E>,
+        parameters: &'a Phase1Parameters<E>,
+        resume_from: ComputationCheckpoint,
+        mut on_checkpoint: impl FnMut(&ComputationCheckpoint) -> Result<()>,
     ) -> Result<()> {
         let span = info_span!("phase1-computation");
         let _ = span.enter();
@@ -36,6 +71,35 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
         let (tau_g1_outputs, tau_g2_outputs, alpha_g1_outputs, beta_g1_outputs, beta_g2_outputs) =
             split_mut(output, parameters, compressed_output);
 
+        // Determine the chunk start and end indices based on the contribution mode.
+        let chunk_indices = |start: usize, end: usize| match parameters.contribution_mode {
+            ContributionMode::Chunked => (
+                start - parameters.chunk_index * parameters.chunk_size,
+                end - parameters.chunk_index * parameters.chunk_size,
+            ),
+            ContributionMode::Full => (start, end),
+        };
+
+        // Check the output of the batches which were already computed.
+        let mut checkpoint = ComputationCheckpoint::default();
+        if resume_from.completed_batches > 0 {
+            info!("resuming after {} batches", resume_from.completed_batches);
+
+            let batch_outputs = match parameters.proving_system {
+                ProvingSystem::Groth16 => [&*tau_g1_outputs, tau_g2_outputs, alpha_g1_outputs, beta_g1_outputs],
+                ProvingSystem::Marlin => [&*tau_g1_outputs, &[], &[], &[]],
+            };
+            iter_chunk(parameters, |start, end| {
+                if checkpoint.completed_batches < resume_from.completed_batches {
+                    checkpoint = checkpoint.next::<E>(batch_outputs, chunk_indices(start, end), compressed_output);
+                }
+                Ok(())
+            })?;
+            if checkpoint != resume_from {
+                return Err(Error::CheckpointMismatch(resume_from.completed_batches));
+            }
+        }
+
         match parameters.proving_system {
             ProvingSystem::Groth16 => {
                 // Write beta_g2 (0th index element) to beta_g2_outputs.
@@ -50,20 +114,14 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                 }
 
                 // load `batch_size` chunks on each iteration and perform the transformation
-                iter_chunk(&parameters, |start, end| {
+                iter_chunk_from(&parameters, resume_from.completed_batches, |start, end| {
                     debug!("contributing to chunk from {} to {}", start, end);
 
                     let span = info_span!("batch", start, end);
                     let _ = span.enter();
 
                     // Determine the chunk start and end indices based on the contribution mode.
-                    let (start_chunk, end_chunk) = match parameters.contribution_mode {
-                        ContributionMode::Chunked => (
-                            start - parameters.chunk_index * parameters.chunk_size,
-                            end - parameters.chunk_index * parameters.chunk_size,
-                        ),
-                        ContributionMode::Full => (start, end),
-                    };
+                    let (start_chunk, end_chunk) = chunk_indices(start, end);
 
                     rayon_cfg::scope(|t| {
                         let _ = span.enter();
@@ -114,13 +172,7 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                                     let end = if start + parameters.batch_size > max { max } else { end };
 
                                     // Determine the chunk start and end indices based on the contribution mode.
-                                    let (start_chunk, end_chunk) = match parameters.contribution_mode {
-                                        ContributionMode::Chunked => (
-                                            start - parameters.chunk_index * parameters.chunk_size,
-                                            end - parameters.chunk_index * parameters.chunk_size,
-                                        ),
-                                        ContributionMode::Full => (start, end),
-                                    };
+                                    let (start_chunk, end_chunk) = chunk_indices(start, end);
 
                                     rayon_cfg::scope(|t| {
                                         let _ = span.enter();
@@ -189,7 +241,12 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
 
                     debug!("chunk contribution successful");
 
-                    Ok(())
+                    checkpoint = checkpoint.next::<E>(
+                        [tau_g1_outputs, tau_g2_outputs, alpha_g1_outputs, beta_g1_outputs],
+                        (start_chunk, end_chunk),
+                        compressed_output,
+                    );
+                    on_checkpoint(&checkpoint)
                 })?;
             }
             ProvingSystem::Marlin => {
@@ -257,20 +314,14 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                 }
 
                 // load `batch_size` chunks on each iteration and perform the transformation
-                iter_chunk(&parameters, |start, end| {
+                iter_chunk_from(&parameters, resume_from.completed_batches, |start, end| {
                     debug!("contributing to chunk from {} to {}", start, end);
 
                     let span = info_span!("batch", start, end);
                     let _ = span.enter();
 
                     // Determine the chunk start and end indices based on the contribution mode.
-                    let (start_chunk, end_chunk) = match parameters.contribution_mode {
-                        ContributionMode::Chunked => (
-                            start - parameters.chunk_index * parameters.chunk_size,
-                            end - parameters.chunk_index * parameters.chunk_size,
-                        ),
-                        ContributionMode::Full => (start, end),
-                    };
+                    let (start_chunk, end_chunk) = chunk_indices(start, end);
 
                     rayon_cfg::scope(|t| {
                         let _ = span.enter();
@@ -297,7 +348,12 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
 
                     debug!("chunk contribution successful");
 
-                    Ok(())
+                    checkpoint = checkpoint.next::<E>(
+                        [tau_g1_outputs, &[], &[], &[]],
+                        (start_chunk, end_chunk),
+                        compressed_output,
+                    );
+                    on_checkpoint(&checkpoint)
                 })?;
             }
         }
@@ -444,6 +500,85 @@ mod tests {
         }
     }
 
+    fn curve_computation_resume_test<E: Pairing>(parameters: &Phase1Parameters<E>, interrupt_after: usize) {
+        let compressed_input = UseCompression::No;
+        let compressed_output = UseCompression::Yes;
+        let (input, _) = generate_input(parameters, compressed_input, CheckForCorrectness::No);
+        let mut rng = derive_rng_from_seed(b"curve_computation_resume_test");
+        let (_, priv_key) = Phase1::key_generation(&mut rng, blank_hash().as_ref()).unwrap();
+
+        let compute = |output: &mut [u8],
+                       resume_from: ComputationCheckpoint,
+                       checkpoints: &mut Vec<ComputationCheckpoint>,
+                       interrupt_after: Option<usize>| {
+            Phase1::computation_with_checkpoints(
+                &input,
+                output,
+                compressed_input,
+                compressed_output,
+                CheckForCorrectness::No,
+                BatchExpMode::Auto,
+                &priv_key,
+                parameters,
+                resume_from,
+                |checkpoint| {
+                    checkpoints.push(checkpoint.clone());
+                    match interrupt_after {
+                        // simulate a crash
+                        Some(batches) if checkpoint.completed_batches == batches => Err(Error::InvalidChunk),
+                        _ => Ok(()),
+                    }
+                },
+            )
+        };
+
+        // compute the contribution in one go
+        let mut expected = vec![0; parameters.get_length(compressed_output)];
+        let mut checkpoints = vec![];
+        compute(&mut expected, ComputationCheckpoint::default(), &mut checkpoints, None).unwrap();
+        assert!(checkpoints.len() > interrupt_after);
+        assert_eq!(checkpoints.last().unwrap().completed_batches, checkpoints.len());
+
+        // interrupt it, and resume from the last checkpoint
+        let mut output = vec![0; parameters.get_length(compressed_output)];
+        let mut partial_checkpoints = vec![];
+        assert!(compute(
+            &mut output,
+            ComputationCheckpoint::default(),
+            &mut partial_checkpoints,
+            Some(interrupt_after)
+        )
+        .is_err());
+        assert_eq!(partial_checkpoints[..], checkpoints[..interrupt_after]);
+        let last_checkpoint = partial_checkpoints.pop().unwrap();
+
+        // a corrupted partial output is detected
+        let mut corrupted = output.clone();
+        corrupted[parameters.hash_size] ^= 1;
+        assert!(matches!(
+            compute(&mut corrupted, last_checkpoint.clone(), &mut vec![], None),
+            Err(Error::CheckpointMismatch(batches)) if batches == interrupt_after
+        ));
+
+        let mut resumed_checkpoints = vec![];
+        compute(&mut output, last_checkpoint, &mut resumed_checkpoints, None).unwrap();
+        assert_eq!(resumed_checkpoints[..], checkpoints[interrupt_after..]);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_computation_resume_bls12_377() {
+        for proving_system in &[ProvingSystem::Groth16, ProvingSystem::Marlin] {
+            let parameters = Phase1Parameters::<Bls12_377>::new_full(*proving_system, 5, 16);
+            curve_computation_resume_test(&parameters, 1);
+        }
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 4, 4);
+        curve_computation_resume_test(&parameters, 5);
+        let parameters =
+            Phase1Parameters::<Bls12_377>::new_chunk(ContributionMode::Chunked, 1, 16, ProvingSystem::Groth16, 4, 4);
+        curve_computation_resume_test(&parameters, 3);
+    }
+
     #[test]
     fn test_computation_bls12_377_compressed() {
         // Receives a compressed/uncompressed input, contributes to it, and produces a compressed/uncompressed output
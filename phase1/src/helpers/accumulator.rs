@@ -58,16 +58,14 @@ cfg_if! {
             (start, end): (usize, usize),
             elements: &mut [E::G1Affine],
             check: &(E::G2Affine, E::G2Affine),
-            err_message: &str,
+            element_type: ElementType,
         ) -> Result<()> {
             let size = buffer_size::<E::G1Affine>(compression);
-                        buffer[start * size..end * size].read_batch_preallocated(
-                &mut elements[0..end - start],
-                compression,
-                check_for_correctness,
-            )?;
-                        check_same_ratio::<E>(&power_pairs(&elements[..end - start]), check, format!("Power pairs {}", err_message))?;
-            Ok(())
+            buffer[start * size..end * size]
+                .read_batch_preallocated(&mut elements[0..end - start], compression, check_for_correctness)
+                .map_err(|e| e.with_element_type(element_type))?;
+            check_same_ratio::<E>(&power_pairs(&elements[..end - start]), check, RatioCheck::PowerPairs)
+                .map_err(|e| e.with_element_type(element_type))
         }
 
         /// Reads a list of G2 elements from the buffer to the provided `elements` slice
@@ -78,16 +76,14 @@ cfg_if! {
             (start, end): (usize, usize),
             elements: &mut [E::G2Affine],
             check: &(E::G1Affine, E::G1Affine),
-                        err_message: &str,
+            element_type: ElementType,
         ) -> Result<()> {
             let size = buffer_size::<E::G2Affine>(compression);
-            buffer[start * size..end * size].read_batch_preallocated(
-                &mut elements[0..end - start],
-                compression,
-                check_for_correctness,
-            )?;
-            check_same_ratio::<E>(check, &power_pairs(&elements[..end - start]), format!("Power pairs {}", err_message))?;
-            Ok(())
+            buffer[start * size..end * size]
+                .read_batch_preallocated(&mut elements[0..end - start], compression, check_for_correctness)
+                .map_err(|e| e.with_element_type(element_type))?;
+            check_same_ratio::<E>(check, &power_pairs(&elements[..end - start]), RatioCheck::PowerPairs)
+                .map_err(|e| e.with_element_type(element_type))
         }
 
         /// Reads a list of group elements from the buffer to the provided `elements` slice
@@ -21,6 +21,15 @@ type SplitBuf<'a> = (&'a [u8], &'a [u8], &'a [u8], &'a [u8], &'a [u8]);
 /// `action` will perform an action on the chunk
 pub(crate) fn iter_chunk<E: Pairing>(
     parameters: &Phase1Parameters<E>,
+    action: impl FnMut(usize, usize) -> Result<()>,
+) -> Result<()> {
+    iter_chunk_from(parameters, 0, action)
+}
+
+/// Same as `iter_chunk`, but skips the first `first_batch` batches
+pub(crate) fn iter_chunk_from<E: Pairing>(
+    parameters: &Phase1Parameters<E>,
+    first_batch: usize,
     mut action: impl FnMut(usize, usize) -> Result<()>,
 ) -> Result<()> {
     // Determine the range to iterate over.
@@ -48,6 +57,7 @@ pub(crate) fn iter_chunk<E: Pairing>(
     (min..max)
         .chunks(parameters.batch_size - 1)
         .into_iter()
+        .skip(first_batch)
         .map(|chunk| {
             match chunk.minmax() {
                 MinMaxResult::MinMax(start, end) => {
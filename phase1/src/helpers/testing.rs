@@ -1,4 +1,4 @@
-use crate::{Phase1, Phase1Parameters, PublicKey};
+use crate::{ContributionMode, Phase1, Phase1Parameters, ProvingSystem, PublicKey};
 use setup_utils::*;
 
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
@@ -125,3 +125,61 @@ pub fn generate_random_accumulator<E: Pairing>(
         }
     }
 }
+
+/// Contributes to a fresh accumulator and verifies the contribution for every combination of
+/// proving system, contribution mode and input/output compression, so that a curve can be
+/// covered end to end by a single test.
+pub fn contribute_and_verify_matrix<E: Pairing>(total_size_in_log2: usize, batch_size: usize) {
+    let compressions = [UseCompression::Yes, UseCompression::No];
+    for proving_system in [ProvingSystem::Groth16, ProvingSystem::Marlin] {
+        let full = Phase1Parameters::<E>::new_full(proving_system, total_size_in_log2, batch_size);
+        let chunk = Phase1Parameters::<E>::new_chunk(
+            ContributionMode::Chunked,
+            0,
+            batch_size,
+            proving_system,
+            total_size_in_log2,
+            batch_size,
+        );
+        for parameters in [full, chunk] {
+            for (compressed_input, compressed_output) in compressions
+                .iter()
+                .flat_map(|input| compressions.iter().map(move |output| (*input, *output)))
+            {
+                let (input, output, pub_key, digest) = setup_verify(
+                    compressed_input,
+                    CheckForCorrectness::Full,
+                    compressed_output,
+                    BatchExpMode::Auto,
+                    &parameters,
+                );
+                let mut new_challenge = generate_new_challenge(&parameters, UseCompression::No);
+                Phase1::verification(
+                    &input,
+                    &output,
+                    &mut new_challenge,
+                    &pub_key,
+                    &digest,
+                    compressed_input,
+                    compressed_output,
+                    UseCompression::No,
+                    CheckForCorrectness::Full,
+                    CheckForCorrectness::Full,
+                    SubgroupCheckMode::Auto,
+                    true,
+                    &parameters,
+                )
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "{:?} {:?} contribution (compressed input: {}, compressed output: {}) did not verify: {}",
+                        proving_system,
+                        parameters.contribution_mode,
+                        compressed_input == UseCompression::Yes,
+                        compressed_output == UseCompression::Yes,
+                        e
+                    )
+                });
+            }
+        }
+    }
+}
@@ -61,6 +61,7 @@ mod tests {
     use super::*;
 
     use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
     use ark_bw6_761::BW6_761;
     use ark_ec::AffineRepr;
     use ark_mnt4_753::MNT4_753;
@@ -120,6 +121,56 @@ mod tests {
         curve_initialization_test::<Bls12_377>(4, 4, UseCompression::No);
     }
 
+    #[test]
+    fn test_initialization_bls12_381_compressed() {
+        curve_initialization_test::<Bls12_381>(4, 4, UseCompression::Yes);
+    }
+
+    #[test]
+    fn test_initialization_bls12_381_uncompressed() {
+        curve_initialization_test::<Bls12_381>(4, 4, UseCompression::No);
+    }
+
+    // The generators as encoded by the Zcash Powers of Tau (uncompressed) and by the
+    // Ethereum KZG ceremony (compressed).
+    const BLS12_381_G1_UNCOMPRESSED: &str = "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1";
+    const BLS12_381_G2_UNCOMPRESSED: &str = "13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb80606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801";
+    const BLS12_381_G1_COMPRESSED: &str =
+        "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+    const BLS12_381_G2_COMPRESSED: &str = "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8";
+
+    fn published_encoding_test(compression: UseCompression, g1: &str, g2: &str) {
+        let parameters = Phase1Parameters::<Bls12_381>::new_full(ProvingSystem::Groth16, 4, 4);
+        let length = match compression {
+            UseCompression::Yes => parameters.contribution_size - parameters.public_key_size,
+            UseCompression::No => parameters.accumulator_size,
+        };
+        let mut output = vec![0; length];
+        Phase1::initialization(&mut output, compression, &parameters).unwrap();
+
+        let (g1, g2) = (hex::decode(g1).unwrap(), hex::decode(g2).unwrap());
+        let (tau_g1, tau_g2, alpha_g1, beta_g1, beta_g2) = split(&output, &parameters, compression);
+        // every element of the initial accumulator is the generator, not only the first one
+        // of each vector
+        let check = |elements: &[u8], expected: &[u8], length: usize| {
+            assert_eq!(elements.len(), expected.len() * length);
+            for element in elements.chunks(expected.len()) {
+                assert_eq!(element, expected);
+            }
+        };
+        check(tau_g1, &g1, parameters.powers_g1_length);
+        check(alpha_g1, &g1, parameters.powers_length);
+        check(beta_g1, &g1, parameters.powers_length);
+        check(tau_g2, &g2, parameters.powers_length);
+        check(beta_g2, &g2, 1);
+    }
+
+    #[test]
+    fn test_initialization_bls12_381_matches_published_encodings() {
+        published_encoding_test(UseCompression::No, BLS12_381_G1_UNCOMPRESSED, BLS12_381_G2_UNCOMPRESSED);
+        published_encoding_test(UseCompression::Yes, BLS12_381_G1_COMPRESSED, BLS12_381_G2_COMPRESSED);
+    }
+
     #[test]
     fn test_initialization_bw6_761_compressed() {
         curve_initialization_test::<BW6_761>(4, 4, UseCompression::Yes);
@@ -0,0 +1,324 @@
+//! Conversion of full Marlin accumulators to the universal parameters (SRS) of the KZG10
+//! polynomial commitment scheme, which Marlin uses through SonicKZG10.
+//!
+//! [`KzgUniversalParams`] is serialized exactly like `ark_poly_commit::kzg10::UniversalParams`:
+//!
+//! 1. the powers of tau in G1, as a vector
+//! 2. the powers of tau times alpha in G1 (`powers_of_gamma_g`), as a map from the exponent
+//! 3. the generator `h` of G2 and `beta_h`, which is tau * h
+//! 4. the inverse powers of tau in G2 (`neg_powers_of_h`), as a map from the exponent
+//!
+//! so the serialized parameters can be deserialized into `UniversalParams` directly, which
+//! computes the prepared G2 elements it doesn't store.
+//!
+//! PLONK only needs the powers of tau in G1, `h` and `beta_h`, which Groth16 accumulators have
+//! too. [`Phase1::read_kzg_powers`] reads just these elements from a full accumulator of either
+//! proving system, up to a chosen degree, and leaves the maps used for degree bounds empty.
+use crate::{helpers::buffers::split, Phase1, Phase1Parameters, ProvingSystem};
+use setup_utils::{BatchDeserializer, CheckForCorrectness, Error, UseCompression};
+
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use std::{collections::BTreeMap, io::Write};
+
+/// The universal parameters of KZG10 built from a Marlin accumulator, with the same fields and
+/// serialization as `ark_poly_commit::kzg10::UniversalParams` minus the prepared elements
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KzgUniversalParams<E: Pairing> {
+    /// tau^0, tau^1, ..., tau^{TAU_POWERS_LENGTH - 1} in G1
+    pub powers_of_g: Vec<E::G1Affine>,
+    /// alpha * tau^i in G1 for the exponents needed to enforce degree bounds
+    pub powers_of_gamma_g: BTreeMap<usize, E::G1Affine>,
+    /// The generator of G2
+    pub h: E::G2Affine,
+    /// tau in G2
+    pub beta_h: E::G2Affine,
+    /// 1/tau^i in G2 for the exponents needed to enforce degree bounds
+    pub neg_powers_of_h: BTreeMap<usize, E::G2Affine>,
+}
+
+impl<E: Pairing> KzgUniversalParams<E> {
+    /// The maximum degree of the polynomials these parameters can commit to
+    pub fn max_degree(&self) -> usize {
+        self.powers_of_g.len() - 1
+    }
+
+    /// Writes the raw affine points: the powers of tau in G1 followed by `h` and `beta_h`, each
+    /// as its uncompressed arkworks serialization, without any lengths. The maps used for degree
+    /// bounds are not written.
+    pub fn write_raw<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        for power in &self.powers_of_g {
+            power.serialize_uncompressed(&mut writer)?;
+        }
+        self.h.serialize_uncompressed(&mut writer)?;
+        self.beta_h.serialize_uncompressed(&mut writer)?;
+        Ok(())
+    }
+}
+
+impl<'a, E: Pairing + Sync> Phase1<'a, E> {
+    /// Builds the KZG10 universal parameters from a full Marlin accumulator, using the layout of
+    /// the Marlin elements documented on `Phase1`.
+    pub fn kzg_universal_params(&self) -> Result<KzgUniversalParams<E>, Error> {
+        let parameters = self.parameters;
+        ensure_full_marlin(parameters)?;
+        let powers_length = parameters.powers_length;
+
+        // alpha * tau^0, alpha * tau^1 and alpha * tau^2, followed by the triples starting at
+        // alpha * tau^{TAU_POWERS_LENGTH - 2^i + 1}
+        let (first_alpha_powers, alpha_triples) = self.alpha_tau_powers_g1.split_at(3);
+        let mut powers_of_gamma_g = first_alpha_powers
+            .iter()
+            .copied()
+            .enumerate()
+            .collect::<BTreeMap<_, _>>();
+        for (i, triple) in alpha_triples.chunks(3).enumerate() {
+            let first_exponent = powers_length - (1 << i) + 1;
+            for (j, power) in triple.iter().enumerate() {
+                powers_of_gamma_g.insert(first_exponent + j, *power);
+            }
+        }
+
+        // tau^0 and tau^1, followed by 1/tau^{TAU_POWERS_LENGTH - 2^i + 1}
+        let neg_powers_of_h = self.tau_powers_g2[2..]
+            .iter()
+            .enumerate()
+            .map(|(i, power)| (powers_length - (1 << i) + 1, *power))
+            .collect();
+
+        Ok(KzgUniversalParams {
+            powers_of_g: self.tau_powers_g1.clone(),
+            powers_of_gamma_g,
+            h: self.tau_powers_g2[0],
+            beta_h: self.tau_powers_g2[1],
+            neg_powers_of_h,
+        })
+    }
+}
+
+impl<'a, E: Pairing> Phase1<'a, E> {
+    /// Reads the powers of tau in G1 up to `max_degree`, or all of them, and the first two powers
+    /// of tau in G2 from a full accumulator, without deserializing the rest of it.
+    pub fn read_kzg_powers(
+        input: &[u8],
+        compressed: UseCompression,
+        check_input_for_correctness: CheckForCorrectness,
+        max_degree: Option<usize>,
+        parameters: &Phase1Parameters<E>,
+    ) -> Result<KzgUniversalParams<E>, Error> {
+        let powers_g1_length = match parameters.proving_system {
+            ProvingSystem::Groth16 => parameters.powers_g1_length,
+            ProvingSystem::Marlin => parameters.powers_length,
+        };
+        if parameters.chunk_index != 0 || parameters.g1_chunk_size != powers_g1_length {
+            return Err(Error::UnsupportedProvingSystem(
+                "only full accumulators can be converted to KZG10 universal parameters".to_string(),
+            ));
+        }
+        let max_degree = max_degree.unwrap_or(powers_g1_length - 1);
+        if max_degree >= powers_g1_length {
+            return Err(Error::InvalidArgument(format!(
+                "the accumulator supports polynomials of degree up to {}, not {}",
+                powers_g1_length - 1,
+                max_degree
+            )));
+        }
+
+        let (tau_g1, tau_g2, _, _, _) = split(input, parameters, compressed);
+        let g1_size = setup_utils::buffer_size::<E::G1Affine>(compressed);
+        let g2_size = setup_utils::buffer_size::<E::G2Affine>(compressed);
+        let powers_of_g = tau_g1[..(max_degree + 1) * g1_size].read_batch(compressed, check_input_for_correctness)?;
+        let h_and_beta_h = tau_g2[..2 * g2_size].read_batch::<E::G2Affine>(compressed, check_input_for_correctness)?;
+
+        Ok(KzgUniversalParams {
+            powers_of_g,
+            powers_of_gamma_g: BTreeMap::new(),
+            h: h_and_beta_h[0],
+            beta_h: h_and_beta_h[1],
+            neg_powers_of_h: BTreeMap::new(),
+        })
+    }
+}
+
+fn ensure_full_marlin<E: Pairing>(parameters: &Phase1Parameters<E>) -> Result<(), Error> {
+    if parameters.proving_system != ProvingSystem::Marlin {
+        return Err(Error::UnsupportedProvingSystem(
+            "only Marlin accumulators can be converted to KZG10 universal parameters".to_string(),
+        ));
+    }
+    if parameters.g1_chunk_size != parameters.powers_length {
+        return Err(Error::UnsupportedProvingSystem(
+            "only full accumulators can be converted to KZG10 universal parameters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{helpers::testing::setup_verify, ContributionMode};
+    use setup_utils::{BatchExpMode, CheckForCorrectness, UseCompression};
+
+    use ark_bls12_377::Bls12_377;
+    use ark_bw6_761::BW6_761;
+
+    fn kzg_universal_params_curve_test<E: Pairing + Sync>() {
+        let power = 3;
+        let parameters = Phase1Parameters::<E>::new_full(ProvingSystem::Marlin, power, 4);
+        let (_, output, _, _) = setup_verify(
+            UseCompression::No,
+            CheckForCorrectness::Full,
+            UseCompression::No,
+            BatchExpMode::Auto,
+            &parameters,
+        );
+        let accumulator =
+            Phase1::deserialize(&output, UseCompression::No, CheckForCorrectness::Full, &parameters).unwrap();
+        let params = accumulator.kzg_universal_params().unwrap();
+
+        assert_eq!(params.powers_of_g.len(), 1 << power);
+        assert_eq!(params.neg_powers_of_h.keys().copied().collect::<Vec<_>>(), vec![
+            5, 7, 8
+        ]);
+        assert_eq!(params.powers_of_gamma_g.keys().copied().collect::<Vec<_>>(), vec![
+            0, 1, 2, 5, 6, 7, 8, 9, 10
+        ]);
+
+        // the elements are powers of the same tau
+        let g = params.powers_of_g[0];
+        assert_eq!(
+            E::pairing(params.powers_of_g[1], params.h),
+            E::pairing(g, params.beta_h)
+        );
+        assert_eq!(
+            E::pairing(params.powers_of_gamma_g[&1], params.h),
+            E::pairing(params.powers_of_gamma_g[&0], params.beta_h)
+        );
+        for (exponent, neg_power) in params.neg_powers_of_h.range(..params.powers_of_g.len()) {
+            assert_eq!(
+                E::pairing(params.powers_of_g[*exponent], *neg_power),
+                E::pairing(g, params.h)
+            );
+        }
+
+        let mut serialized = vec![];
+        params.serialize_compressed(&mut serialized).unwrap();
+        assert_eq!(
+            KzgUniversalParams::<E>::deserialize_compressed(serialized.as_slice()).unwrap(),
+            params
+        );
+    }
+
+    #[test]
+    fn test_kzg_universal_params_bls12_377() {
+        kzg_universal_params_curve_test::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_kzg_universal_params_bw6_761() {
+        kzg_universal_params_curve_test::<BW6_761>();
+    }
+
+    #[test]
+    fn test_read_kzg_powers() {
+        for proving_system in [ProvingSystem::Groth16, ProvingSystem::Marlin] {
+            let parameters = Phase1Parameters::<Bls12_377>::new_full(proving_system, 3, 4);
+            let (_, output, _, _) = setup_verify(
+                UseCompression::No,
+                CheckForCorrectness::Full,
+                UseCompression::No,
+                BatchExpMode::Auto,
+                &parameters,
+            );
+            let accumulator =
+                Phase1::deserialize(&output, UseCompression::No, CheckForCorrectness::Full, &parameters).unwrap();
+
+            let params = Phase1::read_kzg_powers(
+                &output,
+                UseCompression::No,
+                CheckForCorrectness::Full,
+                None,
+                &parameters,
+            )
+            .unwrap();
+            assert_eq!(params.powers_of_g, accumulator.tau_powers_g1);
+            assert_eq!(params.h, accumulator.tau_powers_g2[0]);
+            assert_eq!(params.beta_h, accumulator.tau_powers_g2[1]);
+            assert!(params.powers_of_gamma_g.is_empty() && params.neg_powers_of_h.is_empty());
+
+            let truncated = Phase1::read_kzg_powers(
+                &output,
+                UseCompression::No,
+                CheckForCorrectness::Full,
+                Some(2),
+                &parameters,
+            )
+            .unwrap();
+            assert_eq!(truncated.max_degree(), 2);
+            assert_eq!(truncated.powers_of_g, accumulator.tau_powers_g1[..3]);
+
+            let err = Phase1::read_kzg_powers(
+                &output,
+                UseCompression::No,
+                CheckForCorrectness::Full,
+                Some(accumulator.tau_powers_g1.len()),
+                &parameters,
+            )
+            .unwrap_err();
+            assert!(matches!(err, Error::InvalidArgument(_)), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_write_raw() {
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 2, 4);
+        let (_, output, _, _) = setup_verify(
+            UseCompression::No,
+            CheckForCorrectness::Full,
+            UseCompression::No,
+            BatchExpMode::Auto,
+            &parameters,
+        );
+        let params = Phase1::read_kzg_powers(
+            &output,
+            UseCompression::No,
+            CheckForCorrectness::Full,
+            None,
+            &parameters,
+        )
+        .unwrap();
+
+        let mut raw = vec![];
+        params.write_raw(&mut raw).unwrap();
+        let g1_size = setup_utils::buffer_size::<<Bls12_377 as Pairing>::G1Affine>(UseCompression::No);
+        let g2_size = setup_utils::buffer_size::<<Bls12_377 as Pairing>::G2Affine>(UseCompression::No);
+        assert_eq!(raw.len(), params.powers_of_g.len() * g1_size + 2 * g2_size);
+        // the points are the uncompressed elements of the accumulator
+        let (raw_g1, raw_g2) = raw.split_at(params.powers_of_g.len() * g1_size);
+        let tau_g1 = &output[parameters.hash_size..];
+        assert_eq!(raw_g1, &tau_g1[..raw_g1.len()]);
+        assert_eq!(raw_g2, &tau_g1[raw_g1.len()..][..raw_g2.len()]);
+    }
+
+    #[test]
+    fn test_kzg_universal_params_need_a_full_marlin_accumulator() {
+        for parameters in [
+            Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 2, 4),
+            Phase1Parameters::<Bls12_377>::new_chunk(ContributionMode::Chunked, 0, 2, ProvingSystem::Marlin, 2, 4),
+        ] {
+            let (_, output, _, _) = setup_verify(
+                UseCompression::No,
+                CheckForCorrectness::Full,
+                UseCompression::No,
+                BatchExpMode::Auto,
+                &parameters,
+            );
+            let accumulator =
+                Phase1::deserialize(&output, UseCompression::No, CheckForCorrectness::Full, &parameters).unwrap();
+            let err = accumulator.kzg_universal_params().unwrap_err();
+            assert!(matches!(err, Error::UnsupportedProvingSystem(_)), "{}", err);
+        }
+    }
+}
@@ -7,11 +7,17 @@ pub use setup_utils::converters::{ContributionMode, ProvingSystem};
 
 #[cfg(not(feature = "wasm"))]
 mod aggregation;
+pub mod checkpoint;
 mod computation;
 mod initialization;
 mod key_generation;
+pub mod kzg;
+pub mod ptau;
+mod reduction;
 mod serialization;
 #[cfg(not(feature = "wasm"))]
+pub mod transcript;
+#[cfg(not(feature = "wasm"))]
 mod verification;
 
 use crate::helpers::{
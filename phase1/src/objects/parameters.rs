@@ -2,10 +2,15 @@ use ark_serialize::CanonicalSerialize;
 
 use setup_utils::{
     converters::{ContributionMode, ProvingSystem},
+    CeremonyManifest,
+    Error,
+    FileHeader,
+    Result,
     UseCompression,
 };
 
 use ark_ec::pairing::Pairing;
+use ark_ff::FftField;
 
 use std::marker::PhantomData;
 
@@ -110,6 +115,45 @@ impl<E: Pairing> Phase1Parameters<E> {
         )
     }
 
+    /// Constructs the parameters of a chunk of the ceremony described by a manifest, after checking
+    /// that the manifest is valid and that the curve supports its power and the chunk exists
+    pub fn from_manifest(manifest: &CeremonyManifest, chunk_index: usize) -> Result<Self> {
+        manifest.validate()?;
+        let two_adicity = E::ScalarField::TWO_ADICITY as usize;
+        if manifest.power > two_adicity {
+            return Err(Error::InvalidManifest(format!(
+                "the power {} is too large for {}, which supports at most {}",
+                manifest.power, manifest.curve_kind, two_adicity
+            )));
+        }
+
+        let contribution_mode = manifest.contribution_mode()?;
+        let proving_system = manifest.proving_system()?;
+        if contribution_mode == ContributionMode::Chunked {
+            let powers_length = 1 << manifest.power;
+            let powers_length_for_proving_system = match proving_system {
+                ProvingSystem::Groth16 => (powers_length << 1) - 1,
+                ProvingSystem::Marlin => powers_length,
+            };
+            let num_chunks = usize::div_ceil(powers_length_for_proving_system, manifest.chunk_size);
+            if chunk_index >= num_chunks {
+                return Err(Error::InvalidManifest(format!(
+                    "the ceremony has {} chunks, so there is no chunk {}",
+                    num_chunks, chunk_index
+                )));
+            }
+        }
+
+        Ok(Self::new_chunk(
+            contribution_mode,
+            chunk_index,
+            manifest.chunk_size,
+            proving_system,
+            manifest.power,
+            manifest.batch_size,
+        ))
+    }
+
     /// Constructs a new ceremony parameters object from the directly provided curve with parameters
     /// Consider using the `new` method if you want to use one of the pre-implemented curves
     pub fn new(
@@ -245,6 +289,23 @@ impl<E: Pairing> Phase1Parameters<E> {
         }
     }
 
+    /// Returns the header of the files of this ceremony. The chunk index and size are recorded as
+    /// zero when contributing to the full accumulator.
+    pub fn file_header(&self, compressed: UseCompression) -> FileHeader {
+        let (chunk_index, chunk_size) = match self.contribution_mode {
+            ContributionMode::Chunked => (self.chunk_index, self.chunk_size),
+            ContributionMode::Full => (0, 0),
+        };
+        FileHeader::new::<E>(
+            self.proving_system,
+            self.contribution_mode,
+            compressed,
+            self.total_size_in_log2,
+            chunk_index,
+            chunk_size,
+        )
+    }
+
     fn chunk_sizes(
         contribution_mode: ContributionMode,
         chunk_index: usize,
@@ -299,6 +360,7 @@ mod tests {
     use super::*;
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
     use ark_bw6_761::BW6_761;
 
     fn curve_parameters_test<E: Pairing>(g1: usize, g2: usize, g1_compressed: usize, g2_compressed: usize) {
@@ -313,6 +375,39 @@ mod tests {
     fn test_parameter_sizes() {
         curve_parameters_test::<Bls12_377>(96, 192, 48, 96);
         curve_parameters_test::<Bls12_381>(96, 192, 48, 96);
+        curve_parameters_test::<Bn254>(64, 128, 32, 64);
         curve_parameters_test::<BW6_761>(192, 192, 96, 96);
     }
+
+    #[test]
+    fn test_parameters_from_manifest() {
+        let manifest = CeremonyManifest {
+            version: setup_utils::MANIFEST_VERSION,
+            curve_kind: "bn254".to_string(),
+            proving_system: "groth16".to_string(),
+            contribution_mode: "chunked".to_string(),
+            power: 4,
+            batch_size: 8,
+            chunk_size: 10,
+            phase2: None,
+        };
+        let parameters = Phase1Parameters::<Bn254>::from_manifest(&manifest, 3).unwrap();
+        let expected =
+            Phase1Parameters::<Bn254>::new_chunk(ContributionMode::Chunked, 3, 10, ProvingSystem::Groth16, 4, 8);
+        assert_eq!(parameters.accumulator_size, expected.accumulator_size);
+        assert_eq!(parameters.g1_chunk_size, 1);
+
+        // 2^5 - 1 powers of tau in G1 fit in 4 chunks of 10
+        assert!(Phase1Parameters::<Bn254>::from_manifest(&manifest, 4).is_err());
+        let too_large = CeremonyManifest {
+            power: 29,
+            ..manifest.clone()
+        };
+        assert!(Phase1Parameters::<Bn254>::from_manifest(&too_large, 0).is_err());
+        let invalid = CeremonyManifest {
+            batch_size: 0,
+            ..manifest
+        };
+        assert!(Phase1Parameters::<Bn254>::from_manifest(&invalid, 0).is_err());
+    }
 }
@@ -0,0 +1,451 @@
+//! Conversion between the accumulator layout used by this crate and the section based
+//! `.ptau` format used by snarkjs.
+//!
+//! A `.ptau` file starts with the magic `ptau`, a version and the number of sections.
+//! Every section is prefixed by its id and its length in bytes. The sections used here are:
+//!
+//! 1. header: the byte size `n8` of the base field, its modulus `q`, the power and the ceremony power
+//! 2. tau powers in G1 (2^{power+1} - 1 elements)
+//! 3. tau powers in G2 (2^{power} elements)
+//! 4. alpha * tau powers in G1 (2^{power} elements)
+//! 5. beta * tau powers in G1 (2^{power} elements)
+//! 6. beta in G2
+//! 7. the list of contributions
+//!
+//! Points are stored as their affine coordinates, each base prime field element being written
+//! little-endian in Montgomery form with `n8` bytes. The point at infinity is all zeroes.
+//! Only full Groth16 accumulators can be converted, since this is the only layout snarkjs knows about.
+use super::*;
+
+use setup_utils::snarkjs::{
+    modulus_bytes,
+    point_size,
+    prime_field_size,
+    read_point,
+    read_points,
+    read_u32,
+    take,
+    write_file_header,
+    write_point,
+    write_points,
+    write_section_header,
+    Sections,
+    SnarkjsPoint,
+};
+
+use ark_ff::Field;
+
+use std::io::Write;
+
+/// The magic bytes every `.ptau` file starts with
+pub const PTAU_MAGIC: &[u8; 4] = b"ptau";
+/// The size of the intermediate BLAKE2b state snarkjs keeps for every contribution
+pub const PTAU_PARTIAL_HASH_SIZE: usize = 216;
+
+const SECTION_HEADER: u32 = 1;
+const SECTION_TAU_G1: u32 = 2;
+const SECTION_TAU_G2: u32 = 3;
+const SECTION_ALPHA_TAU_G1: u32 = 4;
+const SECTION_BETA_TAU_G1: u32 = 5;
+const SECTION_BETA_G2: u32 = 6;
+const SECTION_CONTRIBUTIONS: u32 = 7;
+
+/// A single entry of the contributions section of a `.ptau` file.
+#[derive(Debug, PartialEq)]
+pub struct PtauContribution<E: Pairing> {
+    /// tau^1 in G1 after the contribution
+    pub tau_g1: E::G1Affine,
+    /// tau^1 in G2 after the contribution
+    pub tau_g2: E::G2Affine,
+    /// alpha in G1 after the contribution
+    pub alpha_g1: E::G1Affine,
+    /// beta in G1 after the contribution
+    pub beta_g1: E::G1Affine,
+    /// beta in G2 after the contribution
+    pub beta_g2: E::G2Affine,
+    /// The proof of knowledge of the contributor
+    pub key: PublicKey<E>,
+    /// The intermediate BLAKE2b state of the response, zeroed if unknown
+    pub partial_hash: Vec<u8>,
+    /// The hash of the challenge which follows this contribution. This is the BLAKE2b hash of the
+    /// challenge file of this tool, not the hash snarkjs computes over its own challenge
+    /// serialization, so `snarkjs verify` does not match it.
+    pub next_challenge: GenericArray<u8, U64>,
+    /// 0 for a regular contribution, 1 for a beacon
+    pub contribution_type: u32,
+    /// The raw (name, beacon iterations, beacon hash) parameters of the contribution
+    pub params: Vec<u8>,
+}
+
+impl<E: Pairing> PtauContribution<E>
+where
+    E::G1Affine: SnarkjsPoint,
+    E::G2Affine: SnarkjsPoint,
+{
+    /// Builds the contribution entry of a response, reading its public key and the
+    /// elements snarkjs records from the first chunk of the accumulator.
+    pub fn from_response(
+        response: &[u8],
+        compressed: UseCompression,
+        check_for_correctness: CheckForCorrectness,
+        next_challenge: GenericArray<u8, U64>,
+        parameters: &Phase1Parameters<E>,
+    ) -> Result<Self> {
+        ensure_full_groth16(parameters)?;
+        let key = PublicKey::read(response, compressed, parameters)?;
+
+        let g1_size = buffer_size::<E::G1Affine>(compressed);
+        let g2_size = buffer_size::<E::G2Affine>(compressed);
+        let (tau_g1, tau_g2, alpha_g1, beta_g1, beta_g2) = split(response, parameters, compressed);
+
+        Ok(Self {
+            tau_g1: (&tau_g1[g1_size..]).read_element(compressed, check_for_correctness)?,
+            tau_g2: (&tau_g2[g2_size..]).read_element(compressed, check_for_correctness)?,
+            alpha_g1: (&*alpha_g1).read_element(compressed, check_for_correctness)?,
+            beta_g1: (&*beta_g1).read_element(compressed, check_for_correctness)?,
+            beta_g2: (&*beta_g2).read_element(compressed, check_for_correctness)?,
+            key,
+            partial_hash: vec![0; PTAU_PARTIAL_HASH_SIZE],
+            next_challenge,
+            contribution_type: 0,
+            params: vec![],
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_point(writer, &self.tau_g1)?;
+        write_point(writer, &self.tau_g2)?;
+        write_point(writer, &self.alpha_g1)?;
+        write_point(writer, &self.beta_g1)?;
+        write_point(writer, &self.beta_g2)?;
+
+        write_point(writer, &self.key.tau_g1.0)?;
+        write_point(writer, &self.key.tau_g1.1)?;
+        write_point(writer, &self.key.alpha_g1.0)?;
+        write_point(writer, &self.key.alpha_g1.1)?;
+        write_point(writer, &self.key.beta_g1.0)?;
+        write_point(writer, &self.key.beta_g1.1)?;
+        write_point(writer, &self.key.tau_g2)?;
+        write_point(writer, &self.key.alpha_g2)?;
+        write_point(writer, &self.key.beta_g2)?;
+
+        if self.partial_hash.len() != PTAU_PARTIAL_HASH_SIZE {
+            return Err(Error::InvalidLength {
+                expected: PTAU_PARTIAL_HASH_SIZE,
+                got: self.partial_hash.len(),
+            });
+        }
+        writer.write_all(&self.partial_hash)?;
+        writer.write_all(self.next_challenge.as_slice())?;
+        writer.write_all(&self.contribution_type.to_le_bytes())?;
+        writer.write_all(&(self.params.len() as u32).to_le_bytes())?;
+        writer.write_all(&self.params)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        // tau_g1, alpha_g1, beta_g1 and the six G1 elements of the key
+        9 * point_size::<E::G1Affine>()
+            // tau_g2, beta_g2 and the three G2 elements of the key
+            + 5 * point_size::<E::G2Affine>()
+            + PTAU_PARTIAL_HASH_SIZE
+            + 64
+            + 4
+            + 4
+            + self.params.len()
+    }
+
+    fn read(reader: &mut &[u8], check_for_correctness: CheckForCorrectness) -> Result<Self> {
+        let tau_g1 = read_point(reader, check_for_correctness)?;
+        let tau_g2 = read_point(reader, check_for_correctness)?;
+        let alpha_g1 = read_point(reader, check_for_correctness)?;
+        let beta_g1 = read_point(reader, check_for_correctness)?;
+        let beta_g2 = read_point(reader, check_for_correctness)?;
+
+        let key_tau_g1_s = read_point(reader, check_for_correctness)?;
+        let key_tau_g1_s_x = read_point(reader, check_for_correctness)?;
+        let key_alpha_g1_s = read_point(reader, check_for_correctness)?;
+        let key_alpha_g1_s_x = read_point(reader, check_for_correctness)?;
+        let key_beta_g1_s = read_point(reader, check_for_correctness)?;
+        let key_beta_g1_s_x = read_point(reader, check_for_correctness)?;
+        let key = PublicKey {
+            tau_g1: (key_tau_g1_s, key_tau_g1_s_x),
+            alpha_g1: (key_alpha_g1_s, key_alpha_g1_s_x),
+            beta_g1: (key_beta_g1_s, key_beta_g1_s_x),
+            tau_g2: read_point(reader, check_for_correctness)?,
+            alpha_g2: read_point(reader, check_for_correctness)?,
+            beta_g2: read_point(reader, check_for_correctness)?,
+        };
+
+        let partial_hash = take(reader, PTAU_PARTIAL_HASH_SIZE)?.to_vec();
+        let next_challenge = GenericArray::clone_from_slice(take(reader, 64)?);
+        let contribution_type = read_u32(reader)?;
+        let params_length = read_u32(reader)? as usize;
+        let params = take(reader, params_length)?.to_vec();
+
+        Ok(Self {
+            tau_g1,
+            tau_g2,
+            alpha_g1,
+            beta_g1,
+            beta_g2,
+            key,
+            partial_hash,
+            next_challenge,
+            contribution_type,
+            params,
+        })
+    }
+}
+
+impl<'a, E: Pairing + Sync> Phase1<'a, E>
+where
+    E::G1Affine: SnarkjsPoint,
+    E::G2Affine: SnarkjsPoint,
+{
+    /// Writes the accumulator and the provided contributions in the snarkjs `.ptau` format.
+    pub fn write_ptau<W: Write>(&self, mut writer: W, contributions: &[PtauContribution<E>]) -> Result<()> {
+        let parameters = self.parameters;
+        ensure_full_groth16(parameters)?;
+
+        let n8 = prime_field_size::<E::G1Affine>();
+        let g1_size = point_size::<E::G1Affine>();
+        let g2_size = point_size::<E::G2Affine>();
+
+        write_file_header(&mut writer, PTAU_MAGIC, 7)?;
+
+        // Header: n8, q, power, ceremony power
+        write_section_header(&mut writer, SECTION_HEADER, 4 + n8 + 4 + 4)?;
+        writer.write_all(&(n8 as u32).to_le_bytes())?;
+        writer.write_all(&modulus_bytes::<BaseField<E>>())?;
+        writer.write_all(&(parameters.total_size_in_log2 as u32).to_le_bytes())?;
+        writer.write_all(&(parameters.total_size_in_log2 as u32).to_le_bytes())?;
+
+        write_section_header(&mut writer, SECTION_TAU_G1, self.tau_powers_g1.len() * g1_size)?;
+        write_points(&mut writer, &self.tau_powers_g1)?;
+        write_section_header(&mut writer, SECTION_TAU_G2, self.tau_powers_g2.len() * g2_size)?;
+        write_points(&mut writer, &self.tau_powers_g2)?;
+        write_section_header(
+            &mut writer,
+            SECTION_ALPHA_TAU_G1,
+            self.alpha_tau_powers_g1.len() * g1_size,
+        )?;
+        write_points(&mut writer, &self.alpha_tau_powers_g1)?;
+        write_section_header(
+            &mut writer,
+            SECTION_BETA_TAU_G1,
+            self.beta_tau_powers_g1.len() * g1_size,
+        )?;
+        write_points(&mut writer, &self.beta_tau_powers_g1)?;
+        write_section_header(&mut writer, SECTION_BETA_G2, g2_size)?;
+        write_point(&mut writer, &self.beta_g2)?;
+
+        let contributions_size = 4 + contributions.iter().map(|c| c.size()).sum::<usize>();
+        write_section_header(&mut writer, SECTION_CONTRIBUTIONS, contributions_size)?;
+        writer.write_all(&(contributions.len() as u32).to_le_bytes())?;
+        for contribution in contributions {
+            contribution.write(&mut writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads an accumulator and its contributions from a snarkjs `.ptau` file.
+    ///
+    /// The power and the base field of the file must match the provided parameters. The returned
+    /// accumulator carries the `next_challenge` hash of the last contribution (or the blank hash
+    /// if there were none), so that it can be serialized as a challenge directly.
+    pub fn read_ptau(
+        input: &[u8],
+        check_for_correctness: CheckForCorrectness,
+        parameters: &'a Phase1Parameters<E>,
+    ) -> Result<(Phase1<'a, E>, Vec<PtauContribution<E>>)> {
+        ensure_full_groth16(parameters)?;
+
+        let sections = Sections::read(input, PTAU_MAGIC)?;
+
+        // Header
+        let mut header = sections.require(SECTION_HEADER)?;
+        let n8 = read_u32(&mut header)? as usize;
+        if n8 != prime_field_size::<E::G1Affine>() || take(&mut header, n8)? != modulus_bytes::<BaseField<E>>() {
+            return Err(Error::InvalidSnarkjsFile(
+                "the base field of the file does not match the selected curve".to_string(),
+            ));
+        }
+        let power = read_u32(&mut header)? as usize;
+        if power != parameters.total_size_in_log2 {
+            return Err(Error::InvalidSnarkjsFile(format!(
+                "the file contains 2^{} powers, expected 2^{}",
+                power, parameters.total_size_in_log2
+            )));
+        }
+
+        let tau_powers_g1 = read_points(
+            sections.require(SECTION_TAU_G1)?,
+            parameters.powers_g1_length,
+            check_for_correctness,
+        )?;
+        let tau_powers_g2 = read_points(
+            sections.require(SECTION_TAU_G2)?,
+            parameters.powers_length,
+            check_for_correctness,
+        )?;
+        let alpha_tau_powers_g1 = read_points(
+            sections.require(SECTION_ALPHA_TAU_G1)?,
+            parameters.powers_length,
+            check_for_correctness,
+        )?;
+        let beta_tau_powers_g1 = read_points(
+            sections.require(SECTION_BETA_TAU_G1)?,
+            parameters.powers_length,
+            check_for_correctness,
+        )?;
+        let beta_g2 = read_point(&mut sections.require(SECTION_BETA_G2)?, check_for_correctness)?;
+
+        let mut contributions = vec![];
+        if let Some(mut contributions_section) = sections.get(SECTION_CONTRIBUTIONS) {
+            let num_contributions = read_u32(&mut contributions_section)?;
+            for _ in 0..num_contributions {
+                contributions.push(PtauContribution::read(
+                    &mut contributions_section,
+                    check_for_correctness,
+                )?);
+            }
+        }
+
+        let hash = contributions
+            .last()
+            .map(|c| c.next_challenge)
+            .unwrap_or_else(blank_hash);
+
+        Ok((
+            Phase1 {
+                tau_powers_g1,
+                tau_powers_g2,
+                alpha_tau_powers_g1,
+                beta_tau_powers_g1,
+                beta_g2,
+                hash,
+                parameters,
+            },
+            contributions,
+        ))
+    }
+}
+
+/// The prime field the coordinates of the points are defined over
+type BaseField<E> = <<<E as Pairing>::G1Affine as AffineRepr>::BaseField as Field>::BasePrimeField;
+
+/// snarkjs only knows about full Groth16 accumulators
+fn ensure_full_groth16<E: Pairing>(parameters: &Phase1Parameters<E>) -> Result<()> {
+    if parameters.proving_system != ProvingSystem::Groth16 {
+        return Err(Error::InvalidSnarkjsFile(
+            "only Groth16 accumulators can be converted".to_string(),
+        ));
+    }
+    if parameters.g1_chunk_size != parameters.powers_g1_length
+        || parameters.other_chunk_size != parameters.powers_length
+    {
+        return Err(Error::InvalidSnarkjsFile(
+            "only full accumulators can be converted".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::testing::{generate_random_accumulator, setup_verify};
+
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bw6_761::BW6_761;
+    use ark_mnt4_753::MNT4_753;
+
+    fn ptau_roundtrip_curve_test<E: Pairing + Sync>()
+    where
+        E::G1Affine: SnarkjsPoint,
+        E::G2Affine: SnarkjsPoint,
+    {
+        let parameters = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, 3, 4);
+        let (_, accumulator) = generate_random_accumulator(&parameters, UseCompression::No);
+
+        // a real contribution, so that the contribution section carries a valid key
+        let (_, mut response, public_key, _) = setup_verify(
+            UseCompression::No,
+            CheckForCorrectness::Full,
+            UseCompression::Yes,
+            BatchExpMode::Auto,
+            &parameters,
+        );
+        response.resize(parameters.contribution_size, 0);
+        public_key
+            .write(&mut response, UseCompression::Yes, &parameters)
+            .unwrap();
+        let next_challenge = calculate_hash(&response);
+        let contribution = PtauContribution::from_response(
+            &response,
+            UseCompression::Yes,
+            CheckForCorrectness::Full,
+            next_challenge,
+            &parameters,
+        )
+        .unwrap();
+
+        let mut ptau = vec![];
+        accumulator
+            .write_ptau(&mut ptau, std::slice::from_ref(&contribution))
+            .unwrap();
+        let (deserialized, contributions) = Phase1::read_ptau(&ptau, CheckForCorrectness::Full, &parameters).unwrap();
+
+        assert_eq!(contributions, vec![contribution]);
+        assert_eq!(deserialized.tau_powers_g1, accumulator.tau_powers_g1);
+        assert_eq!(deserialized.tau_powers_g2, accumulator.tau_powers_g2);
+        assert_eq!(deserialized.alpha_tau_powers_g1, accumulator.alpha_tau_powers_g1);
+        assert_eq!(deserialized.beta_tau_powers_g1, accumulator.beta_tau_powers_g1);
+        assert_eq!(deserialized.beta_g2, accumulator.beta_g2);
+        assert_eq!(deserialized.hash, next_challenge);
+
+        // the converted accumulator must have the exact size of a challenge
+        let mut challenge = vec![0; parameters.accumulator_size];
+        deserialized
+            .serialize(&mut challenge, UseCompression::No, &parameters)
+            .unwrap();
+        let from_challenge =
+            Phase1::deserialize(&challenge, UseCompression::No, CheckForCorrectness::Full, &parameters).unwrap();
+        assert_eq!(from_challenge.tau_powers_g1, accumulator.tau_powers_g1);
+    }
+
+    #[test]
+    fn test_ptau_roundtrip_bls12_377() {
+        ptau_roundtrip_curve_test::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_ptau_roundtrip_bls12_381() {
+        ptau_roundtrip_curve_test::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_ptau_roundtrip_bw6() {
+        ptau_roundtrip_curve_test::<BW6_761>();
+    }
+
+    #[test]
+    fn test_ptau_roundtrip_mnt4_753() {
+        ptau_roundtrip_curve_test::<MNT4_753>();
+    }
+
+    #[test]
+    fn test_ptau_rejects_wrong_power() {
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 3, 4);
+        let (_, accumulator) = generate_random_accumulator(&parameters, UseCompression::No);
+        let mut ptau = vec![];
+        accumulator.write_ptau(&mut ptau, &[]).unwrap();
+
+        let other_parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 2, 4);
+        assert!(Phase1::read_ptau(&ptau, CheckForCorrectness::No, &other_parameters).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use super::*;
+
+impl<'a, E: Pairing + Sync> Phase1<'a, E> {
+    /// Reduces a full Groth16 accumulator to the accumulator of a smaller power. The first powers
+    /// of each kind are the whole accumulator of the smaller power for the same tau, alpha and beta,
+    /// so they're copied as they are, after being checked in batches if requested. The hash at the
+    /// start of the output is left to the caller.
+    ///
+    /// Marlin accumulators can't be reduced, since their inverse powers of tau in G2 depend on the
+    /// number of powers.
+    pub fn reduce(
+        input: &[u8],
+        output: &mut [u8],
+        compressed: UseCompression,
+        check_input_for_correctness: CheckForCorrectness,
+        parameters: &Phase1Parameters<E>,
+        reduced_parameters: &Phase1Parameters<E>,
+    ) -> Result<()> {
+        ensure_full_groth16(parameters)?;
+        ensure_full_groth16(reduced_parameters)?;
+        if reduced_parameters.total_size_in_log2 > parameters.total_size_in_log2 {
+            return Err(Error::InvalidArgument(format!(
+                "an accumulator of power {} can't be reduced to power {}",
+                parameters.total_size_in_log2, reduced_parameters.total_size_in_log2
+            )));
+        }
+
+        let (in_tau_g1, in_tau_g2, in_alpha_g1, in_beta_g1, in_beta_g2) = split(input, parameters, compressed);
+        let (tau_g1, tau_g2, alpha_g1, beta_g1, beta_g2) = split_mut(output, reduced_parameters, compressed);
+        let copy_prefix_g1 = |output: &mut [u8], input: &[u8]| {
+            copy_prefix::<E::G1Affine>(
+                output,
+                input,
+                compressed,
+                check_input_for_correctness,
+                parameters.batch_size,
+            )
+        };
+        let copy_prefix_g2 = |output: &mut [u8], input: &[u8]| {
+            copy_prefix::<E::G2Affine>(
+                output,
+                input,
+                compressed,
+                check_input_for_correctness,
+                parameters.batch_size,
+            )
+        };
+        copy_prefix_g1(tau_g1, in_tau_g1)?;
+        copy_prefix_g2(tau_g2, in_tau_g2)?;
+        copy_prefix_g1(alpha_g1, in_alpha_g1)?;
+        copy_prefix_g1(beta_g1, in_beta_g1)?;
+        copy_prefix_g2(beta_g2, in_beta_g2)?;
+
+        Ok(())
+    }
+}
+
+fn ensure_full_groth16<E: Pairing>(parameters: &Phase1Parameters<E>) -> Result<()> {
+    if parameters.proving_system != ProvingSystem::Groth16 {
+        return Err(Error::UnsupportedProvingSystem(
+            "only Groth16 accumulators can be reduced".to_string(),
+        ));
+    }
+    if parameters.g1_chunk_size != parameters.powers_g1_length
+        || parameters.other_chunk_size != parameters.powers_length
+    {
+        return Err(Error::UnsupportedProvingSystem(
+            "only full accumulators can be reduced".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Copies the start of `input` which fits in `output`, reading it in batches of `batch_size`
+/// elements first to check them
+fn copy_prefix<G: AffineRepr>(
+    output: &mut [u8],
+    input: &[u8],
+    compressed: UseCompression,
+    check_input_for_correctness: CheckForCorrectness,
+    batch_size: usize,
+) -> Result<()> {
+    let input = &input[..output.len()];
+    if check_input_for_correctness != CheckForCorrectness::No {
+        for batch in input.chunks(buffer_size::<G>(compressed) * batch_size) {
+            batch.read_batch::<G>(compressed, check_input_for_correctness)?;
+        }
+    }
+    output.copy_from_slice(input);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::testing::{generate_output, generate_random_accumulator};
+
+    use ark_bls12_377::Bls12_377;
+    use ark_bw6_761::BW6_761;
+
+    fn reduce_curve_test<E: Pairing + Sync>(compressed: UseCompression) {
+        let parameters = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, 4, 3);
+        let reduced_parameters = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, 2, 3);
+        let (input, accumulator) = generate_random_accumulator(&parameters, compressed);
+        let mut output = generate_output(&reduced_parameters, compressed);
+
+        Phase1::reduce(
+            &input,
+            &mut output,
+            compressed,
+            CheckForCorrectness::Full,
+            &parameters,
+            &reduced_parameters,
+        )
+        .unwrap();
+
+        let reduced = Phase1::deserialize(&output, compressed, CheckForCorrectness::Full, &reduced_parameters).unwrap();
+        assert_eq!(reduced.tau_powers_g1, accumulator.tau_powers_g1[..7]);
+        assert_eq!(reduced.tau_powers_g2, accumulator.tau_powers_g2[..4]);
+        assert_eq!(reduced.alpha_tau_powers_g1, accumulator.alpha_tau_powers_g1[..4]);
+        assert_eq!(reduced.beta_tau_powers_g1, accumulator.beta_tau_powers_g1[..4]);
+        assert_eq!(reduced.beta_g2, accumulator.beta_g2);
+    }
+
+    #[test]
+    fn test_reduce_bls12_377() {
+        reduce_curve_test::<Bls12_377>(UseCompression::No);
+        reduce_curve_test::<Bls12_377>(UseCompression::Yes);
+    }
+
+    #[test]
+    fn test_reduce_bw6_761() {
+        reduce_curve_test::<BW6_761>(UseCompression::No);
+    }
+
+    #[test]
+    fn test_reduce_rejects_other_accumulators() {
+        let groth16 = |power| Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, power, 4);
+        let marlin = |power| Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Marlin, power, 4);
+        for (parameters, reduced_parameters) in [(marlin(3), marlin(2)), (groth16(2), groth16(3))] {
+            let (input, _) = generate_random_accumulator(&parameters, UseCompression::No);
+            let mut output = generate_output(&parameters, UseCompression::No);
+            let err = Phase1::reduce(
+                &input,
+                &mut output,
+                UseCompression::No,
+                CheckForCorrectness::No,
+                &parameters,
+                &reduced_parameters,
+            )
+            .unwrap_err();
+            assert!(
+                matches!(err, Error::UnsupportedProvingSystem(_) | Error::InvalidArgument(_)),
+                "{}",
+                err
+            );
+        }
+    }
+}
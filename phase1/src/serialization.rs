@@ -20,12 +20,18 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
         Ok(())
     }
 
+    /// Deserializes an accumulator. If the input starts with a file header, it's checked against
+    /// the parameters and the compression, otherwise the input is read as a legacy headerless file.
     pub fn deserialize(
         input: &[u8],
         compression: UseCompression,
         check_input_for_correctness: CheckForCorrectness,
         parameters: &'a Phase1Parameters<E>,
     ) -> Result<Phase1<'a, E>> {
+        let (header, input) = split_file_header(input)?;
+        if let Some(header) = header {
+            header.check(&parameters.file_header(compression))?;
+        }
         let (tau_powers_g1, tau_powers_g2, alpha_tau_powers_g1, beta_tau_powers_g1, beta_g2) =
             accumulator::deserialize(input, compression, check_input_for_correctness, parameters)?;
         Ok(Phase1 {
@@ -103,6 +109,30 @@ mod tests {
         serialize_curve_test::<BW6_761>(UseCompression::No, 2, 2);
     }
 
+    #[test]
+    fn test_deserialize_with_file_header() {
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 2, 2);
+        let (buffer, accumulator) = generate_random_accumulator(&parameters, UseCompression::No);
+        let mut with_header = parameters.file_header(UseCompression::No).to_bytes().to_vec();
+        with_header.extend_from_slice(&buffer);
+        let deserialized =
+            Phase1::deserialize(&with_header, UseCompression::No, CheckForCorrectness::No, &parameters).unwrap();
+        assert_eq!(deserialized, accumulator);
+
+        // a header written for another power is rejected before the elements are read
+        let other_parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 3, 2);
+        let mut with_other_header = other_parameters.file_header(UseCompression::No).to_bytes().to_vec();
+        with_other_header.extend_from_slice(&buffer);
+        let err = Phase1::deserialize(
+            &with_other_header,
+            UseCompression::No,
+            CheckForCorrectness::No,
+            &parameters,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::FileHeaderMismatch(_)), "{}", err);
+    }
+
     #[test]
     fn test_decompress_bls12_377() {
         decompress_curve_test::<Bls12_377>();
@@ -0,0 +1,235 @@
+//! An append-only transcript of the verified contributions of a ceremony.
+//!
+//! Every contribution which passes verification is appended to the transcript as one record:
+//!
+//! 1. the chunk index (`u64`, little-endian)
+//! 2. the hash of the challenge the contribution was based on
+//! 3. the hash of the response
+//! 4. the hash of the new challenge produced by the verifier
+//! 5. the compressed public key of the contributor
+//!
+//! For a given chunk, the challenge of each contribution is the new challenge of the previous
+//! one, so the hash chain and the proofs of knowledge of all the contributors can be re-checked
+//! from the transcript alone.
+use super::*;
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use std::{
+    collections::HashMap,
+    fs::OpenOptions,
+    io::{Read, Write},
+    path::Path,
+};
+
+/// A single verified contribution.
+#[derive(Debug, PartialEq)]
+pub struct TranscriptEntry<E: Pairing> {
+    /// The chunk the contribution was made to
+    pub chunk_index: usize,
+    /// The hash of the challenge the contribution was based on
+    pub challenge_hash: GenericArray<u8, U64>,
+    /// The hash of the response
+    pub response_hash: GenericArray<u8, U64>,
+    /// The hash of the challenge produced from the response by the verifier
+    pub new_challenge_hash: GenericArray<u8, U64>,
+    /// The public key of the contributor
+    pub public_key: PublicKey<E>,
+}
+
+impl<E: Pairing> TranscriptEntry<E> {
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(&(self.chunk_index as u64).to_le_bytes())?;
+        writer.write_all(self.challenge_hash.as_slice())?;
+        writer.write_all(self.response_hash.as_slice())?;
+        writer.write_all(self.new_challenge_hash.as_slice())?;
+        self.public_key.serialize_compressed(&mut writer)?;
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self> {
+        let mut chunk_index = [0u8; 8];
+        reader.read_exact(&mut chunk_index)?;
+        let mut read_hash = || -> Result<GenericArray<u8, U64>> {
+            let mut hash = GenericArray::default();
+            reader.read_exact(hash.as_mut_slice())?;
+            Ok(hash)
+        };
+        let challenge_hash = read_hash()?;
+        let response_hash = read_hash()?;
+        let new_challenge_hash = read_hash()?;
+        let public_key = PublicKey::deserialize_compressed(&mut reader)?;
+
+        Ok(Self {
+            chunk_index: u64::from_le_bytes(chunk_index) as usize,
+            challenge_hash,
+            response_hash,
+            new_challenge_hash,
+            public_key,
+        })
+    }
+}
+
+/// Appends an entry to the transcript file, creating it if it does not exist. If the last entry
+/// for the same chunk is already `entry`, e.g. when a verification which was interrupted after
+/// appending it is resumed, nothing is written, since a duplicate would break the hash chain.
+pub fn append_to_transcript<E: Pairing, P: AsRef<Path>>(path: P, entry: &TranscriptEntry<E>) -> Result<()> {
+    let path = path.as_ref();
+    if path.exists() {
+        let entries = read_transcript::<E>(&std::fs::read(path)?)?;
+        let last_entry = entries
+            .iter()
+            .rev()
+            .find(|previous| previous.chunk_index == entry.chunk_index);
+        if last_entry == Some(entry) {
+            return Ok(());
+        }
+    }
+
+    let mut serialized = vec![];
+    entry.write(&mut serialized)?;
+    // the record is written at once so that an interrupted append is not followed by valid data
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&serialized)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Reads all the entries of a transcript
+pub fn read_transcript<E: Pairing>(input: &[u8]) -> Result<Vec<TranscriptEntry<E>>> {
+    let mut reader = input;
+    let mut entries = vec![];
+    while !reader.is_empty() {
+        entries.push(TranscriptEntry::read(&mut reader)?);
+    }
+    Ok(entries)
+}
+
+/// Checks that every contribution to a chunk is based on the new challenge produced from the
+/// previous contribution to the same chunk, and that every public key proves knowledge of
+/// tau, alpha and beta for the challenge it was based on.
+pub fn verify_transcript<E: Pairing>(entries: &[TranscriptEntry<E>]) -> Result<()> {
+    let mut last_challenge_hashes = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(last_challenge_hash) = last_challenge_hashes.get(&entry.chunk_index) {
+            if *last_challenge_hash != entry.challenge_hash {
+                return Err(VerificationError::HashChainFailure(index).into());
+            }
+        }
+
+        let key = &entry.public_key;
+        let [tau_g2_s, alpha_g2_s, beta_g2_s] = compute_g2_s_key(key, &entry.challenge_hash)?;
+        let check_ratios = &[
+            (&key.tau_g1, &(tau_g2_s, key.tau_g2), ElementType::TauG1),
+            (&key.alpha_g1, &(alpha_g2_s, key.alpha_g2), ElementType::AlphaG1),
+            (&key.beta_g1, &(beta_g2_s, key.beta_g2), ElementType::BetaG1),
+        ];
+        for (a, b, element_type) in check_ratios {
+            check_same_ratio::<E>(a, b, RatioCheck::ProofOfKnowledge).map_err(|e| {
+                e.with_element_type(*element_type)
+                    .with_chunk_index(entry.chunk_index)
+                    .with_contribution_index(index)
+            })?;
+        }
+
+        last_challenge_hashes.insert(entry.chunk_index, entry.new_challenge_hash);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ark_bls12_377::Bls12_377;
+    use rand::{thread_rng, Rng};
+
+    fn random_hash() -> GenericArray<u8, U64> {
+        let mut hash = GenericArray::default();
+        thread_rng().fill(hash.as_mut_slice());
+        hash
+    }
+
+    fn entry<E: Pairing>(chunk_index: usize, challenge_hash: GenericArray<u8, U64>) -> TranscriptEntry<E> {
+        let (public_key, _) = Phase1::key_generation(&mut thread_rng(), challenge_hash.as_ref()).unwrap();
+        TranscriptEntry {
+            chunk_index,
+            challenge_hash,
+            response_hash: random_hash(),
+            new_challenge_hash: random_hash(),
+            public_key,
+        }
+    }
+
+    // two contributors, each contributing to two chunks
+    fn generate_transcript<E: Pairing>() -> Vec<TranscriptEntry<E>> {
+        let first_chunk_0 = entry(0, random_hash());
+        let first_chunk_1 = entry(1, random_hash());
+        let second_chunk_0 = entry(0, first_chunk_0.new_challenge_hash);
+        let second_chunk_1 = entry(1, first_chunk_1.new_challenge_hash);
+        vec![first_chunk_0, first_chunk_1, second_chunk_0, second_chunk_1]
+    }
+
+    #[test]
+    fn transcript_roundtrip() {
+        let entries = generate_transcript::<Bls12_377>();
+        let mut serialized = vec![];
+        for entry in &entries {
+            entry.write(&mut serialized).unwrap();
+        }
+        let deserialized = read_transcript::<Bls12_377>(&serialized).unwrap();
+        assert_eq!(deserialized, entries);
+        verify_transcript(&deserialized).unwrap();
+
+        // a truncated record is an error
+        assert!(read_transcript::<Bls12_377>(&serialized[..serialized.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn transcript_append_is_idempotent() {
+        let entries = generate_transcript::<Bls12_377>();
+        let path = std::env::temp_dir().join(format!("transcript-append-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        for entry in &entries {
+            append_to_transcript(&path, entry).unwrap();
+        }
+        // the last entry of each chunk is not appended again, but older ones are
+        append_to_transcript(&path, &entries[2]).unwrap();
+        append_to_transcript(&path, &entries[3]).unwrap();
+        let appended = read_transcript::<Bls12_377>(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(appended, entries);
+        append_to_transcript(&path, &entries[0]).unwrap();
+        let appended = read_transcript::<Bls12_377>(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(appended.len(), entries.len() + 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn transcript_broken_hash_chain() {
+        let mut entries = generate_transcript::<Bls12_377>();
+        // a valid contribution, but not based on the previous challenge of the chunk
+        entries[2] = entry(0, random_hash());
+        assert!(matches!(
+            verify_transcript(&entries),
+            Err(Error::VerificationError(VerificationError::HashChainFailure(2)))
+        ));
+    }
+
+    #[test]
+    fn transcript_invalid_proof_of_knowledge() {
+        let mut entries = generate_transcript::<Bls12_377>();
+        // a key generated for another challenge does not prove anything
+        entries[3].public_key = entry::<Bls12_377>(1, random_hash()).public_key;
+        assert!(matches!(
+            verify_transcript(&entries),
+            Err(Error::VerificationError(VerificationError::InvalidRatio {
+                check: RatioCheck::ProofOfKnowledge,
+                context: VerificationContext {
+                    element_type: Some(ElementType::TauG1),
+                    contribution_index: Some(3),
+                    ..
+                },
+            }))
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 use tracing::error;
 
-use super::*;
+use super::{checkpoint::ComputationCheckpoint, *};
+use setup_utils::rayon_cfg::FirstError;
 
 impl<'a, E: Pairing + Sync> Phase1<'a, E> {
     /// Verifies that the accumulator was transformed correctly
@@ -37,6 +38,51 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
         subgroup_check_mode: SubgroupCheckMode,
         ratio_check: bool,
         parameters: &'a Phase1Parameters<E>,
+    ) -> Result<()> {
+        Self::verification_with_checkpoints(
+            input,
+            output,
+            new_challenge,
+            key,
+            digest,
+            compressed_input,
+            compressed_output,
+            compressed_new_challenge,
+            check_input_for_correctness,
+            check_output_for_correctness,
+            subgroup_check_mode,
+            ratio_check,
+            parameters,
+            ComputationCheckpoint::default(),
+            |_| Ok(()),
+        )
+    }
+
+    ///
+    /// Same as `verification`, but resumes from the checkpoint `resume_from` and calls
+    /// `on_checkpoint` after every batch which was verified and written to the new challenge.
+    ///
+    /// The key and the initial elements are always checked, but the batches completed before
+    /// the checkpoint are neither verified nor written again. Their part of the new challenge
+    /// is hashed and compared to the checkpoint instead.
+    ///
+    #[allow(clippy::too_many_arguments, clippy::cognitive_complexity)]
+    pub fn verification_with_checkpoints(
+        input: &[u8],
+        output: &[u8],
+        new_challenge: &mut [u8],
+        key: &PublicKey<E>,
+        digest: &[u8],
+        compressed_input: UseCompression,
+        compressed_output: UseCompression,
+        compressed_new_challenge: UseCompression,
+        check_input_for_correctness: CheckForCorrectness,
+        check_output_for_correctness: CheckForCorrectness,
+        subgroup_check_mode: SubgroupCheckMode,
+        ratio_check: bool,
+        parameters: &'a Phase1Parameters<E>,
+        resume_from: ComputationCheckpoint,
+        mut on_checkpoint: impl FnMut(&ComputationCheckpoint) -> Result<()>,
     ) -> Result<()> {
         let span = info_span!("phase1-verification");
         let _ = span.enter();
@@ -100,21 +146,26 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
             {
                 // Check the proofs of knowledge for tau, alpha, and beta.
                 let check_ratios = &[
-                    (&(key.tau_g1.0, key.tau_g1.1), &(tau_g2_s, key.tau_g2), "Tau G1<>G2"),
+                    (
+                        &(key.tau_g1.0, key.tau_g1.1),
+                        &(tau_g2_s, key.tau_g2),
+                        ElementType::TauG1,
+                    ),
                     (
                         &(key.alpha_g1.0, key.alpha_g1.1),
                         &(alpha_g2_s, key.alpha_g2),
-                        "Alpha G1<>G2",
+                        ElementType::AlphaG1,
                     ),
                     (
                         &(key.beta_g1.0, key.beta_g1.1),
                         &(beta_g2_s, key.beta_g2),
-                        "Beta G1<>G2",
+                        ElementType::BetaG1,
                     ),
                 ];
 
-                for (a, b, err) in check_ratios {
-                    check_same_ratio::<E>(a, b, err.to_string())?;
+                for (a, b, element_type) in check_ratios {
+                    check_same_ratio::<E>(a, b, RatioCheck::ProofOfKnowledge)
+                        .map_err(|e| e.with_element_type(*element_type))?;
                 }
                 debug!("key ratios were correctly produced");
             }
@@ -141,8 +192,9 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                 check_same_ratio::<E>(
                     &(before_g1[1], after_g1[1]),
                     tau_single_g2_check,
-                    "Before-After: tau_g1".to_string(),
-                )?;
+                    RatioCheck::BeforeAfter,
+                )
+                .map_err(|e| e.with_element_type(ElementType::TauG1))?;
 
                 (before_g1, after_g1)
             };
@@ -165,8 +217,9 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                 check_same_ratio::<E>(
                     tau_single_g1_check,
                     &(before_g2[1], after_g2[1]),
-                    "Before-After: tau_g2".to_string(),
-                )?;
+                    RatioCheck::BeforeAfter,
+                )
+                .map_err(|e| e.with_element_type(ElementType::TauG2))?;
             }
 
             // Check that alpha_g1[0] and beta_g1[0] were computed correctly.
@@ -174,21 +227,18 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                 // Determine the check based on the proof system's requirements.
                 let checks = match parameters.proving_system {
                     ProvingSystem::Groth16 => vec![
-                        (in_alpha_g1, alpha_g1, alpha_single_g2_check),
-                        (in_beta_g1, beta_g1, beta_single_g2_check),
+                        (in_alpha_g1, alpha_g1, alpha_single_g2_check, ElementType::AlphaG1),
+                        (in_beta_g1, beta_g1, beta_single_g2_check, ElementType::BetaG1),
                     ],
-                    ProvingSystem::Marlin => vec![(in_alpha_g1, alpha_g1, alpha_single_g2_check)],
+                    ProvingSystem::Marlin => vec![(in_alpha_g1, alpha_g1, alpha_single_g2_check, ElementType::AlphaG1)],
                 };
 
                 // Check that alpha_g1[0] and beta_g1[0] was multiplied correctly.
-                for (before, after, check) in &checks {
+                for (before, after, check, element_type) in &checks {
                     before.read_batch_preallocated(&mut before_g1, compressed_input, check_input_for_correctness)?;
                     after.read_batch_preallocated(&mut after_g1, compressed_output, check_output_for_correctness)?;
-                    check_same_ratio::<E>(
-                        &(before_g1[0], after_g1[0]),
-                        check,
-                        "Before-After: alpha_g1[0] / beta_g1[0]".to_string(),
-                    )?;
+                    check_same_ratio::<E>(&(before_g1[0], after_g1[0]), check, RatioCheck::BeforeAfter)
+                        .map_err(|e| e.with_element_type(*element_type))?;
                 }
             }
 
@@ -206,15 +256,51 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                     check_same_ratio::<E>(
                         beta_single_g1_check,
                         &(before_beta_g2, after_beta_g2),
-                        "Before-After: beta_g2[0]".to_string(),
-                    )?;
+                        RatioCheck::BeforeAfter,
+                    )
+                    .map_err(|e| e.with_element_type(ElementType::BetaG2))?;
                 }
             }
         };
 
         debug!("initial elements were computed correctly");
 
-        iter_chunk(&parameters, |start, end| {
+        // Determine the chunk start and end indices based on the contribution mode.
+        let chunk_indices = |start: usize, end: usize| match parameters.contribution_mode {
+            ContributionMode::Chunked => (
+                start - parameters.chunk_index * parameters.chunk_size, // start index
+                end - parameters.chunk_index * parameters.chunk_size,   // end index
+            ),
+            ContributionMode::Full => (start, end),
+        };
+
+        // Check the part of the new challenge which was written by the batches already verified.
+        let mut checkpoint = ComputationCheckpoint::default();
+        if resume_from.completed_batches > 0 {
+            info!("resuming after {} batches", resume_from.completed_batches);
+
+            let batch_outputs = match parameters.proving_system {
+                ProvingSystem::Groth16 => [
+                    &*new_challenge_tau_g1,
+                    new_challenge_tau_g2,
+                    new_challenge_alpha_g1,
+                    new_challenge_beta_g1,
+                ],
+                ProvingSystem::Marlin => [&*new_challenge_tau_g1, &[], &[], &[]],
+            };
+            iter_chunk(parameters, |start, end| {
+                if checkpoint.completed_batches < resume_from.completed_batches {
+                    checkpoint =
+                        checkpoint.next::<E>(batch_outputs, chunk_indices(start, end), compressed_new_challenge);
+                }
+                Ok(())
+            })?;
+            if checkpoint != resume_from {
+                return Err(Error::CheckpointMismatch(resume_from.completed_batches));
+            }
+        }
+
+        iter_chunk_from(&parameters, resume_from.completed_batches, |start, end| {
             // Preallocate 2 vectors per batch.
             // Ensure that the pairs are created correctly (we do this in chunks!).
             // Load `batch_size` chunks on each iteration and perform the transformation.
@@ -225,19 +311,14 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
             let _enter = span.enter();
 
             // Determine the chunk start and end indices based on the contribution mode.
-            let (start_chunk, end_chunk) = match parameters.contribution_mode {
-                ContributionMode::Chunked => (
-                    start - parameters.chunk_index * parameters.chunk_size, // start index
-                    end - parameters.chunk_index * parameters.chunk_size,   // end index
-                ),
-                ContributionMode::Full => (start, end),
-            };
+            let (start_chunk, end_chunk) = chunk_indices(start, end);
 
             // If there's only one element, ratio check will fail, so return an error
             if ratio_check && end <= start + 1 {
                 return Err(Error::BatchTooSmall);
             }
 
+            let errors = FirstError::default();
             match parameters.proving_system {
                 ProvingSystem::Groth16 => {
                     rayon::scope(|t| {
@@ -245,35 +326,36 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
 
                         // Process tau_g1 elements.
                         t.spawn(|_| {
-                            let _enter = span.enter();
-
-                            let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
+                            errors.run(|| {
+                                let _enter = span.enter();
 
-                            check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
-                                (tau_g1, compressed_output),
-                                (start_chunk, end_chunk),
-                                &mut g1,
-                                subgroup_check_mode,
-                            )
-                            .expect("could not check element are non zero and in prime order subgroup (tau g1)");
+                                let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
 
-                            if ratio_check {
-                                check_power_ratios::<E>(
-                                    (tau_g1, compressed_output, CheckForCorrectness::No),
+                                check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
+                                    (tau_g1, compressed_output),
                                     (start_chunk, end_chunk),
                                     &mut g1,
-                                    &g2_check,
-                                    "tau g1",
+                                    subgroup_check_mode,
                                 )
-                                .expect("could not check element ratios (tau g1)");
-                            }
+                                .map_err(|e| e.with_element_type(ElementType::TauG1))?;
+
+                                if ratio_check {
+                                    check_power_ratios::<E>(
+                                        (tau_g1, compressed_output, CheckForCorrectness::No),
+                                        (start_chunk, end_chunk),
+                                        &mut g1,
+                                        &g2_check,
+                                        ElementType::TauG1,
+                                    )?;
+                                }
 
-                            let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
-                            new_challenge_tau_g1[start_chunk * size..end_chunk * size]
-                                .write_batch(&mut g1[0..end_chunk - start_chunk], compressed_new_challenge)
-                                .expect("Should have written tau_g1 to new challenge");
+                                let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
+                                new_challenge_tau_g1[start_chunk * size..end_chunk * size]
+                                    .write_batch(&mut g1[0..end_chunk - start_chunk], compressed_new_challenge)?;
 
-                            trace!("tau_g1 verification was successful");
+                                trace!("tau_g1 verification was successful");
+                                Ok(())
+                            })
                         });
 
                         if start < parameters.powers_length {
@@ -290,13 +372,7 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                             let end = if start + parameters.batch_size > max { max } else { end };
 
                             // Determine the chunk start and end indices based on the contribution mode.
-                            let (start_chunk, end_chunk) = match parameters.contribution_mode {
-                                ContributionMode::Chunked => (
-                                    start - parameters.chunk_index * parameters.chunk_size, // start index
-                                    end - parameters.chunk_index * parameters.chunk_size,   // end index
-                                ),
-                                ContributionMode::Full => (start, end),
-                            };
+                            let (start_chunk, end_chunk) = chunk_indices(start, end);
 
                             if end > start + 1 {
                                 rayon::scope(|t| {
@@ -304,106 +380,109 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
 
                                     // Process tau_g2 elements.
                                     t.spawn(|_| {
-                                        let _enter = span.enter();
-
-                                        let mut g2 = vec![E::G2Affine::zero(); parameters.batch_size];
+                                        errors.run(|| {
+                                            let _enter = span.enter();
 
-                                        check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G2Affine>(
-                                        (tau_g2, compressed_output),
-                                        (start_chunk, end_chunk),
-                                        &mut g2,
-                                        subgroup_check_mode,
-                                    )
-                                    .expect(
-                                        "could not check elements are non zero and in prime order subgroup (tau g2)",
-                                    );
+                                            let mut g2 = vec![E::G2Affine::zero(); parameters.batch_size];
 
-                                        if ratio_check {
-                                            check_power_ratios_g2::<E>(
-                                                (tau_g2, compressed_output, CheckForCorrectness::No),
+                                            check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G2Affine>(
+                                                (tau_g2, compressed_output),
                                                 (start_chunk, end_chunk),
-                                                &mut g2[..],
-                                                &g1_check,
-                                                "tau g2",
+                                                &mut g2,
+                                                subgroup_check_mode,
                                             )
-                                            .expect("could not check ratios (tau g2)");
-                                        }
-
-                                        let size = buffer_size::<E::G2Affine>(compressed_new_challenge);
-                                        new_challenge_tau_g2[start_chunk * size..end_chunk * size]
-                                            .write_batch(&mut g2[0..end_chunk - start_chunk], compressed_new_challenge)
-                                            .expect("Should have written tau_g2 to new challenge");
-
-                                        trace!("tau_g2 verification was successful");
+                                            .map_err(|e| e.with_element_type(ElementType::TauG2))?;
+
+                                            if ratio_check {
+                                                check_power_ratios_g2::<E>(
+                                                    (tau_g2, compressed_output, CheckForCorrectness::No),
+                                                    (start_chunk, end_chunk),
+                                                    &mut g2[..],
+                                                    &g1_check,
+                                                    ElementType::TauG2,
+                                                )?;
+                                            }
+
+                                            let size = buffer_size::<E::G2Affine>(compressed_new_challenge);
+                                            new_challenge_tau_g2[start_chunk * size..end_chunk * size].write_batch(
+                                                &mut g2[0..end_chunk - start_chunk],
+                                                compressed_new_challenge,
+                                            )?;
+
+                                            trace!("tau_g2 verification was successful");
+                                            Ok(())
+                                        })
                                     });
 
                                     // Process alpha_g1 elements.
                                     t.spawn(|_| {
-                                        let _enter = span.enter();
+                                        errors.run(|| {
+                                            let _enter = span.enter();
 
-                                        let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
-
-                                        check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
-                                        (alpha_g1, compressed_output),
-                                        (start_chunk, end_chunk),
-                                        &mut g1,
-                                        subgroup_check_mode,
-                                    )
-                                    .expect(
-                                        "could not check elements are non zero and in prime order subgroup (alpha g1)",
-                                    );
+                                            let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
 
-                                        if ratio_check {
-                                            check_power_ratios::<E>(
-                                                (alpha_g1, compressed_output, CheckForCorrectness::No),
+                                            check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
+                                                (alpha_g1, compressed_output),
                                                 (start_chunk, end_chunk),
                                                 &mut g1,
-                                                &g2_check,
-                                                "alpha g1",
+                                                subgroup_check_mode,
                                             )
-                                            .expect("could not check ratios (alpha g1)");
-                                        }
-
-                                        let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
-                                        new_challenge_alpha_g1[start_chunk * size..end_chunk * size]
-                                            .write_batch(&mut g1[0..end_chunk - start_chunk], compressed_new_challenge)
-                                            .expect("Should have written alpha_g1 to new challenge");
-
-                                        trace!("alpha_g1 verification was successful");
+                                            .map_err(|e| e.with_element_type(ElementType::AlphaG1))?;
+
+                                            if ratio_check {
+                                                check_power_ratios::<E>(
+                                                    (alpha_g1, compressed_output, CheckForCorrectness::No),
+                                                    (start_chunk, end_chunk),
+                                                    &mut g1,
+                                                    &g2_check,
+                                                    ElementType::AlphaG1,
+                                                )?;
+                                            }
+
+                                            let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
+                                            new_challenge_alpha_g1[start_chunk * size..end_chunk * size].write_batch(
+                                                &mut g1[0..end_chunk - start_chunk],
+                                                compressed_new_challenge,
+                                            )?;
+
+                                            trace!("alpha_g1 verification was successful");
+                                            Ok(())
+                                        })
                                     });
 
                                     // Process beta_g1 elements.
                                     t.spawn(|_| {
-                                        let _enter = span.enter();
-
-                                        let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
+                                        errors.run(|| {
+                                            let _enter = span.enter();
 
-                                        check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
-                                        (beta_g1, compressed_output),
-                                        (start_chunk, end_chunk),
-                                        &mut g1,
-                                        subgroup_check_mode,
-                                    )
-                                    .expect(
-                                        "could not check element are non zero and in prime order subgroup (beta g1)",
-                                    );
+                                            let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
 
-                                        if ratio_check {
-                                            check_power_ratios::<E>(
-                                                (beta_g1, compressed_output, CheckForCorrectness::No),
+                                            check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
+                                                (beta_g1, compressed_output),
                                                 (start_chunk, end_chunk),
                                                 &mut g1,
-                                                &g2_check,
-                                                "beta g1",
+                                                subgroup_check_mode,
                                             )
-                                            .expect("could not check element ratios (beta g1)");
-                                        }
-                                        let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
-                                        new_challenge_beta_g1[start_chunk * size..end_chunk * size]
-                                            .write_batch(&mut g1[0..end_chunk - start_chunk], compressed_new_challenge)
-                                            .expect("Should have written beta_g1 to new challenge");
-
-                                        trace!("beta_g1 verification was successful");
+                                            .map_err(|e| e.with_element_type(ElementType::BetaG1))?;
+
+                                            if ratio_check {
+                                                check_power_ratios::<E>(
+                                                    (beta_g1, compressed_output, CheckForCorrectness::No),
+                                                    (start_chunk, end_chunk),
+                                                    &mut g1,
+                                                    &g2_check,
+                                                    ElementType::BetaG1,
+                                                )?;
+                                            }
+                                            let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
+                                            new_challenge_beta_g1[start_chunk * size..end_chunk * size].write_batch(
+                                                &mut g1[0..end_chunk - start_chunk],
+                                                compressed_new_challenge,
+                                            )?;
+
+                                            trace!("beta_g1 verification was successful");
+                                            Ok(())
+                                        })
                                     });
                                 });
                             }
@@ -416,71 +495,74 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
 
                         // Process tau_g1 elements.
                         t.spawn(|_| {
-                            let _ = span.enter();
+                            errors.run(|| {
+                                let _ = span.enter();
 
-                            let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
+                                let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
 
-                            check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
-                                (tau_g1, compressed_output),
-                                (start_chunk, end_chunk),
-                                &mut g1,
-                                subgroup_check_mode,
-                            )
-                            .expect("could not check ratios for tau_g1 elements");
+                                check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
+                                    (tau_g1, compressed_output),
+                                    (start_chunk, end_chunk),
+                                    &mut g1,
+                                    subgroup_check_mode,
+                                )
+                                .map_err(|e| e.with_element_type(ElementType::TauG1))?;
 
-                            let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
-                            new_challenge_tau_g1[start_chunk * size..end_chunk * size]
-                                .write_batch(&mut g1[0..end_chunk - start_chunk], compressed_new_challenge)
-                                .expect("Should have written tau_g1 to new challenge");
+                                let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
+                                new_challenge_tau_g1[start_chunk * size..end_chunk * size]
+                                    .write_batch(&mut g1[0..end_chunk - start_chunk], compressed_new_challenge)?;
 
-                            trace!("tau_g1 verification was successful");
+                                trace!("tau_g1 verification was successful");
+                                Ok(())
+                            })
                         });
 
                         if start == 0 {
                             t.spawn(|_| {
-                                let _ = span.enter();
+                                errors.run(|| {
+                                    let _ = span.enter();
 
-                                let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
+                                    let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
 
-                                let num_alpha_powers = 3;
+                                    let num_alpha_powers = 3;
 
-                                let start_chunk = 0;
-                                let end_chunk = num_alpha_powers + 3 * parameters.total_size_in_log2;
+                                    let start_chunk = 0;
+                                    let end_chunk = num_alpha_powers + 3 * parameters.total_size_in_log2;
 
-                                check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
-                                    (alpha_g1, compressed_output),
-                                    (start_chunk, end_chunk),
-                                    &mut g1,
-                                    subgroup_check_mode,
-                                )
-                                .expect("could not check ratios for tau_g1 elements");
+                                    check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G1Affine>(
+                                        (alpha_g1, compressed_output),
+                                        (start_chunk, end_chunk),
+                                        &mut g1,
+                                        subgroup_check_mode,
+                                    )
+                                    .map_err(|e| e.with_element_type(ElementType::AlphaG1))?;
 
-                                let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
-                                new_challenge_alpha_g1[start_chunk * size..end_chunk * size]
-                                    .write_batch(&mut g1[0..end_chunk - start_chunk], compressed_new_challenge)
-                                    .expect("Should have written alpha_g1 to new challenge");
+                                    let size = buffer_size::<E::G1Affine>(compressed_new_challenge);
+                                    new_challenge_alpha_g1[start_chunk * size..end_chunk * size]
+                                        .write_batch(&mut g1[0..end_chunk - start_chunk], compressed_new_challenge)?;
 
-                                trace!("alpha_g1 verification was successful");
+                                    trace!("alpha_g1 verification was successful");
 
-                                let start_chunk = 0;
-                                let end_chunk = parameters.total_size_in_log2 + 2;
+                                    let start_chunk = 0;
+                                    let end_chunk = parameters.total_size_in_log2 + 2;
 
-                                let mut g2 = vec![E::G2Affine::zero(); parameters.batch_size];
+                                    let mut g2 = vec![E::G2Affine::zero(); parameters.batch_size];
 
-                                check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G2Affine>(
-                                    (tau_g2, compressed_output),
-                                    (start_chunk, end_chunk),
-                                    &mut g2,
-                                    subgroup_check_mode,
-                                )
-                                .expect("could not check element are non zero and in prime order subgroup");
+                                    check_elements_are_nonzero_and_in_prime_order_subgroup::<E::G2Affine>(
+                                        (tau_g2, compressed_output),
+                                        (start_chunk, end_chunk),
+                                        &mut g2,
+                                        subgroup_check_mode,
+                                    )
+                                    .map_err(|e| e.with_element_type(ElementType::TauG2))?;
 
-                                let size = buffer_size::<E::G2Affine>(compressed_new_challenge);
-                                new_challenge_tau_g2[start_chunk * size..end_chunk * size]
-                                    .write_batch(&mut g2[0..end_chunk - start_chunk], compressed_new_challenge)
-                                    .expect("Should have written tau_g2 to new challenge");
+                                    let size = buffer_size::<E::G2Affine>(compressed_new_challenge);
+                                    new_challenge_tau_g2[start_chunk * size..end_chunk * size]
+                                        .write_batch(&mut g2[0..end_chunk - start_chunk], compressed_new_challenge)?;
 
-                                trace!("tau_g2 verification was successful");
+                                    trace!("tau_g2 verification was successful");
+                                    Ok(())
+                                })
                             });
                         } else {
                             debug!("Ignoring the last element, because the last was extended anyway.");
@@ -488,10 +570,27 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                     });
                 }
             }
+            errors.into_result().map_err(|e| {
+                let e = e.with_batch(start, end);
+                match parameters.contribution_mode {
+                    ContributionMode::Chunked => e.with_chunk_index(parameters.chunk_index),
+                    ContributionMode::Full => e,
+                }
+            })?;
 
             debug!("batch verification successful");
 
-            Ok(())
+            let batch_outputs = match parameters.proving_system {
+                ProvingSystem::Groth16 => [
+                    &*new_challenge_tau_g1,
+                    new_challenge_tau_g2,
+                    new_challenge_alpha_g1,
+                    new_challenge_beta_g1,
+                ],
+                ProvingSystem::Marlin => [&*new_challenge_tau_g1, &[], &[], &[]],
+            };
+            checkpoint = checkpoint.next::<E>(batch_outputs, (start_chunk, end_chunk), compressed_new_challenge);
+            on_checkpoint(&checkpoint)
         })?;
 
         info!("phase1-verification complete");
@@ -550,24 +649,27 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                     let span = info_span!("batch", start, end);
                     let _enter = span.enter();
 
+                    let errors = FirstError::default();
                     rayon::scope(|t| {
                         let _enter = span.enter();
 
                         t.spawn(|_| {
-                            let _enter = span.enter();
+                            errors.run(|| {
+                                let _enter = span.enter();
 
-                            let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
+                                let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
 
-                            check_power_ratios::<E>(
-                                (tau_g1, compressed_output, check_output_for_correctness),
-                                (start, end),
-                                &mut g1,
-                                &g2_check,
-                                "tau g1",
-                            )
-                            .expect("could not check ratios for tau_g1 elements");
+                                check_power_ratios::<E>(
+                                    (tau_g1, compressed_output, check_output_for_correctness),
+                                    (start, end),
+                                    &mut g1,
+                                    &g2_check,
+                                    ElementType::TauG1,
+                                )?;
 
-                            trace!("tau_g1 verification successful");
+                                trace!("tau_g1 verification successful");
+                                Ok(())
+                            })
                         });
 
                         if start < parameters.powers_length {
@@ -585,54 +687,60 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                                     let _enter = span.enter();
 
                                     t.spawn(|_| {
-                                        let _enter = span.enter();
+                                        errors.run(|| {
+                                            let _enter = span.enter();
 
-                                        let mut g2 = vec![E::G2Affine::zero(); parameters.batch_size];
+                                            let mut g2 = vec![E::G2Affine::zero(); parameters.batch_size];
 
-                                        check_power_ratios_g2::<E>(
-                                            (tau_g2, compressed_output, check_output_for_correctness),
-                                            (start, end),
-                                            &mut g2,
-                                            &g1_check,
-                                            "tau_g2",
-                                        )
-                                        .expect("could not check ratios for tau_g2 elements");
+                                            check_power_ratios_g2::<E>(
+                                                (tau_g2, compressed_output, check_output_for_correctness),
+                                                (start, end),
+                                                &mut g2,
+                                                &g1_check,
+                                                ElementType::TauG2,
+                                            )?;
 
-                                        trace!("tau_g2 verification successful");
+                                            trace!("tau_g2 verification successful");
+                                            Ok(())
+                                        })
                                     });
 
                                     t.spawn(|_| {
-                                        let _enter = span.enter();
+                                        errors.run(|| {
+                                            let _enter = span.enter();
 
-                                        let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
+                                            let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
 
-                                        check_power_ratios::<E>(
-                                            (alpha_g1, compressed_output, check_output_for_correctness),
-                                            (start, end),
-                                            &mut g1,
-                                            &g2_check,
-                                            "alpha_g1",
-                                        )
-                                        .expect("could not check ratios for alpha_g1 elements");
+                                            check_power_ratios::<E>(
+                                                (alpha_g1, compressed_output, check_output_for_correctness),
+                                                (start, end),
+                                                &mut g1,
+                                                &g2_check,
+                                                ElementType::AlphaG1,
+                                            )?;
 
-                                        trace!("alpha_g1 verification successful");
+                                            trace!("alpha_g1 verification successful");
+                                            Ok(())
+                                        })
                                     });
 
                                     t.spawn(|_| {
-                                        let _enter = span.enter();
+                                        errors.run(|| {
+                                            let _enter = span.enter();
 
-                                        let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
+                                            let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
 
-                                        check_power_ratios::<E>(
-                                            (beta_g1, compressed_output, check_output_for_correctness),
-                                            (start, end),
-                                            &mut g1,
-                                            &g2_check,
-                                            "beta_g1",
-                                        )
-                                        .expect("could not check ratios for beta_g1 elements");
+                                            check_power_ratios::<E>(
+                                                (beta_g1, compressed_output, check_output_for_correctness),
+                                                (start, end),
+                                                &mut g1,
+                                                &g2_check,
+                                                ElementType::BetaG1,
+                                            )?;
 
-                                        trace!("beta_g1 verification successful");
+                                            trace!("beta_g1 verification successful");
+                                            Ok(())
+                                        })
                                     });
                                 });
                             } else {
@@ -640,6 +748,7 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                             }
                         }
                     });
+                    errors.into_result().map_err(|e| e.with_batch(start, end))?;
 
                     debug!("chunk verification successful");
 
@@ -653,27 +762,30 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                     let span = info_span!("batch", start, end);
                     let _enter = span.enter();
 
+                    let errors = FirstError::default();
                     rayon::scope(|t| {
                         let _enter = span.enter();
 
                         t.spawn(|_| {
-                            let _enter = span.enter();
+                            errors.run(|| {
+                                let _enter = span.enter();
 
-                            let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
+                                let mut g1 = vec![E::G1Affine::zero(); parameters.batch_size];
 
-                            check_power_ratios::<E>(
-                                (tau_g1, compressed_output, check_output_for_correctness),
-                                (start, end),
-                                &mut g1,
-                                &g2_check,
-                                "tau g1",
-                            )
-                            .expect("could not check ratios for tau_g1 elements");
+                                check_power_ratios::<E>(
+                                    (tau_g1, compressed_output, check_output_for_correctness),
+                                    (start, end),
+                                    &mut g1,
+                                    &g2_check,
+                                    ElementType::TauG1,
+                                )?;
 
-                            trace!("tau_g1 verification successful");
+                                trace!("tau_g1 verification successful");
+                                Ok(())
+                            })
                         });
 
-                        {
+                        errors.run(|| {
                             let powers_of_two_in_range = (0..parameters.total_size_in_log2)
                                 .map(|i| (i, parameters.powers_length as u64 - 1 - (1 << i) + 2))
                                 .map(|(i, p)| (i, p as usize))
@@ -686,16 +798,16 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
 
                                 let g1 = (&tau_g1[p * g1_size..(p + 1) * g1_size])
                                     .read_element(compressed_output, check_output_for_correctness)
-                                    .expect("should have read g1 element");
+                                    .map_err(|e| e.with_element_type(ElementType::TauG1))?;
                                 let g2 = (&tau_g2[(2 + i) * g2_size..(2 + i + 1) * g2_size])
                                     .read_element(compressed_output, check_output_for_correctness)
-                                    .expect("should have read g2 element");
+                                    .map_err(|e| e.with_element_type(ElementType::TauG2))?;
                                 check_same_ratio::<E>(
                                     &(g1, E::G1Affine::generator()),
                                     &(E::G2Affine::generator(), g2),
-                                    "G1<>G2".to_string(),
+                                    RatioCheck::PowersOfTwoG1G2,
                                 )
-                                .expect("should have checked same ratio");
+                                .map_err(|e| e.with_element_type(ElementType::TauG1))?;
 
                                 let mut alpha_g1_elements = vec![E::G1Affine::zero(); 3];
                                 (&alpha_g1[(3 + 3 * i) * g1_size..(3 + 3 * i + 3) * g1_size])
@@ -704,28 +816,30 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                                         compressed_output,
                                         check_output_for_correctness,
                                     )
-                                    .expect("should have read alpha g1 elements");
+                                    .map_err(|e| e.with_element_type(ElementType::AlphaG1))?;
                                 check_same_ratio::<E>(
                                     &(alpha_g1_elements[0], alpha_g1_elements[1]),
                                     &g2_check,
-                                    "alpha_g1 ratio 1".to_string(),
+                                    RatioCheck::AlphaPowers,
                                 )
-                                .expect("should have checked same ratio");
+                                .map_err(|e| e.with_element_type(ElementType::AlphaG1))?;
                                 check_same_ratio::<E>(
                                     &(alpha_g1_elements[1], alpha_g1_elements[2]),
                                     &g2_check,
-                                    "alpha_g1 ratio 2".to_string(),
+                                    RatioCheck::AlphaPowers,
                                 )
-                                .expect("should have checked same ratio");
+                                .map_err(|e| e.with_element_type(ElementType::AlphaG1))?;
                                 check_same_ratio::<E>(
                                     &(alpha_g1_elements[0], g1_alpha_check.0),
                                     &(E::G2Affine::generator(), g2),
-                                    "alpha consistent".to_string(),
+                                    RatioCheck::AlphaConsistency,
                                 )
-                                .expect("should have checked same ratio");
+                                .map_err(|e| e.with_element_type(ElementType::AlphaG1))?;
                             }
-                        }
+                            Ok(())
+                        });
                     });
+                    errors.into_result().map_err(|e| e.with_batch(start, end))?;
 
                     // This is the first batch, check alpha_g1. batch size is guaranteed to be of size >= 3
                     if start == 0 {
@@ -737,9 +851,9 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                             (0, num_alpha_powers),
                             &mut g1,
                             &g2_check,
-                            "alpha g1",
+                            ElementType::AlphaG1,
                         )
-                        .expect("could not check ratios for alpha_g1");
+                        .map_err(|e| e.with_batch(start, end))?;
 
                         trace!("alpha_g1 verification was successful");
 
@@ -750,9 +864,9 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
                             (0, 2),
                             &mut g2,
                             &g1_check,
-                            "tau g2",
+                            ElementType::TauG2,
                         )
-                        .expect("could not check ratios for tau_g2");
+                        .map_err(|e| e.with_batch(start, end))?;
 
                         trace!("tau_g2 verification was successful");
                     }
@@ -772,10 +886,16 @@ impl<'a, E: Pairing + Sync> Phase1<'a, E> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::helpers::testing::{generate_input, generate_new_challenge, generate_output};
+    use crate::helpers::testing::{
+        contribute_and_verify_matrix,
+        generate_input,
+        generate_new_challenge,
+        generate_output,
+    };
     use setup_utils::calculate_hash;
 
     use ark_bls12_377::Bls12_377;
+    use ark_bn254::Bn254;
     use ark_bw6_761::BW6_761;
     use ark_mnt4_753::MNT4_753;
     use ark_mnt6_753::MNT6_753;
@@ -1105,6 +1225,160 @@ mod tests {
         }
     }
 
+    fn verification_resume_test<E: Pairing>(parameters: &Phase1Parameters<E>, interrupt_after: usize) {
+        let compressed_input = UseCompression::No;
+        let compressed_output = UseCompression::Yes;
+        let (input, _) = generate_input(parameters, compressed_input, CheckForCorrectness::No);
+        let mut output = generate_output(parameters, compressed_output);
+
+        let current_accumulator_hash = blank_hash();
+        let mut rng = derive_rng_from_seed(b"verification_resume_test");
+        let (pub_key, priv_key) = Phase1::key_generation(&mut rng, current_accumulator_hash.as_ref()).unwrap();
+        Phase1::computation(
+            &input,
+            &mut output,
+            compressed_input,
+            compressed_output,
+            CheckForCorrectness::No,
+            BatchExpMode::Auto,
+            &priv_key,
+            parameters,
+        )
+        .unwrap();
+
+        let verify = |new_challenge: &mut [u8],
+                      resume_from: ComputationCheckpoint,
+                      checkpoints: &mut Vec<ComputationCheckpoint>,
+                      interrupt_after: Option<usize>| {
+            Phase1::verification_with_checkpoints(
+                &input,
+                &output,
+                new_challenge,
+                &pub_key,
+                &current_accumulator_hash,
+                compressed_input,
+                compressed_output,
+                UseCompression::No,
+                CheckForCorrectness::No,
+                CheckForCorrectness::Full,
+                SubgroupCheckMode::Auto,
+                false,
+                parameters,
+                resume_from,
+                |checkpoint| {
+                    checkpoints.push(checkpoint.clone());
+                    match interrupt_after {
+                        // simulate a crash
+                        Some(batches) if checkpoint.completed_batches == batches => Err(Error::InvalidChunk),
+                        _ => Ok(()),
+                    }
+                },
+            )
+        };
+
+        // verify the contribution in one go
+        let mut expected = generate_new_challenge(parameters, UseCompression::No);
+        let mut checkpoints = vec![];
+        verify(&mut expected, ComputationCheckpoint::default(), &mut checkpoints, None).unwrap();
+        assert!(checkpoints.len() > interrupt_after);
+
+        // interrupt it, and resume from the last checkpoint
+        let mut new_challenge = generate_new_challenge(parameters, UseCompression::No);
+        let mut partial_checkpoints = vec![];
+        assert!(verify(
+            &mut new_challenge,
+            ComputationCheckpoint::default(),
+            &mut partial_checkpoints,
+            Some(interrupt_after)
+        )
+        .is_err());
+        let last_checkpoint = partial_checkpoints.pop().unwrap();
+        assert_eq!(last_checkpoint, checkpoints[interrupt_after - 1]);
+
+        // a corrupted new challenge is detected
+        let mut corrupted = new_challenge.clone();
+        corrupted[parameters.hash_size] ^= 1;
+        assert!(matches!(
+            verify(&mut corrupted, last_checkpoint.clone(), &mut vec![], None),
+            Err(Error::CheckpointMismatch(batches)) if batches == interrupt_after
+        ));
+
+        // the verified batches are skipped
+        let mut resumed_checkpoints = vec![];
+        verify(&mut new_challenge, last_checkpoint, &mut resumed_checkpoints, None).unwrap();
+        assert_eq!(resumed_checkpoints[..], checkpoints[interrupt_after..]);
+        assert_eq!(new_challenge, expected);
+    }
+
+    #[test]
+    fn test_verification_resume_bls12_377() {
+        for proving_system in &[ProvingSystem::Groth16, ProvingSystem::Marlin] {
+            let parameters = Phase1Parameters::<Bls12_377>::new_full(*proving_system, 5, 3 + 3 * 5);
+            verification_resume_test(&parameters, 1);
+        }
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 4, 4);
+        verification_resume_test(&parameters, 5);
+        let parameters =
+            Phase1Parameters::<Bls12_377>::new_chunk(ContributionMode::Chunked, 1, 16, ProvingSystem::Groth16, 4, 4);
+        verification_resume_test(&parameters, 3);
+    }
+
+    #[test]
+    fn test_verification_reports_failed_batch() {
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 4, 4);
+        let (input, _) = generate_input(&parameters, UseCompression::No, CheckForCorrectness::No);
+        let mut output = generate_output(&parameters, UseCompression::No);
+        let mut new_challenge = generate_new_challenge(&parameters, UseCompression::No);
+
+        let digest = blank_hash();
+        let mut rng = derive_rng_from_seed(b"test_verification_reports_failed_batch");
+        let (pub_key, priv_key) = Phase1::key_generation(&mut rng, digest.as_ref()).unwrap();
+        Phase1::computation(
+            &input,
+            &mut output,
+            UseCompression::No,
+            UseCompression::No,
+            CheckForCorrectness::No,
+            BatchExpMode::Auto,
+            &priv_key,
+            &parameters,
+        )
+        .unwrap();
+
+        // replace tau^5 in G1 by tau^6, which is only checked by the batch from 3 to 7
+        let size = buffer_size::<<Bls12_377 as Pairing>::G1Affine>(UseCompression::No);
+        let (tau_5, tau_6) = (parameters.hash_size + 5 * size, parameters.hash_size + 6 * size);
+        output.copy_within(tau_6..tau_6 + size, tau_5);
+
+        let res = Phase1::verification(
+            &input,
+            &output,
+            &mut new_challenge,
+            &pub_key,
+            &digest,
+            UseCompression::No,
+            UseCompression::No,
+            UseCompression::No,
+            CheckForCorrectness::No,
+            CheckForCorrectness::Full,
+            SubgroupCheckMode::Auto,
+            true,
+            &parameters,
+        );
+        assert!(matches!(
+            res,
+            Err(Error::VerificationError(VerificationError::InvalidRatio {
+                check: RatioCheck::PowerPairs,
+                context: VerificationContext {
+                    element_type: Some(ElementType::TauG1),
+                    batch: Some((3, 7)),
+                    chunk_index: None,
+                    contribution_index: None,
+                },
+            }))
+        ));
+    }
+
     #[test]
     fn test_verification_bls12_377() {
         full_verification_test::<Bls12_377>(4, 3 + 3 * 4, UseCompression::Yes, UseCompression::Yes);
@@ -1113,6 +1387,11 @@ mod tests {
         full_verification_test::<Bls12_377>(4, 3 + 3 * 4, UseCompression::No, UseCompression::Yes);
     }
 
+    #[test]
+    fn test_verification_matrix_bn254() {
+        contribute_and_verify_matrix::<Bn254>(4, 3 + 3 * 4);
+    }
+
     #[test]
     fn test_verification_bw6_761() {
         full_verification_test::<BW6_761>(4, 3 + 3 * 4, UseCompression::Yes, UseCompression::Yes);
@@ -16,10 +16,10 @@ mod test {
         lc,
         r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
     };
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use ark_std::UniformRand;
     use blake2::Blake2s;
-    use itertools::Itertools;
-    use std::{collections::BTreeMap, ops::MulAssign};
+    use std::ops::MulAssign;
 
     #[derive(Copy, Clone)]
     struct Circuit<F: Field> {
@@ -90,41 +90,14 @@ mod test {
 
         let deserialized =
             Phase1::deserialize(&output, UseCompression::No, CheckForCorrectness::No, &parameters).unwrap();
-        let tau_powers_g1 = deserialized.tau_powers_g1.clone();
-        let tau_powers_g2 = deserialized.tau_powers_g2.clone();
-        let alpha_powers_g1 = deserialized.alpha_tau_powers_g1.clone();
-
-        let mut alpha_tau_powers_g1 = BTreeMap::new();
-        for i in 0..3 {
-            alpha_tau_powers_g1.insert(i, alpha_powers_g1[i]);
-        }
-        alpha_powers_g1[3..]
-            .iter()
-            .chunks(3)
-            .into_iter()
-            .enumerate()
-            .for_each(|(i, c)| {
-                let c = c.into_iter().collect::<Vec<_>>();
-                alpha_tau_powers_g1.insert(parameters.powers_length - 1 - (1 << i) + 2, *c[0]);
-                alpha_tau_powers_g1.insert(parameters.powers_length - 1 - (1 << i) + 3, *c[1]);
-                alpha_tau_powers_g1.insert(parameters.powers_length - 1 - (1 << i) + 4, *c[2]);
-            });
-
-        let mut prepared_neg_powers_of_h = BTreeMap::new();
-        tau_powers_g2[2..].iter().enumerate().for_each(|(i, p)| {
-            prepared_neg_powers_of_h.insert(parameters.powers_length - 1 - (1 << i) + 2, (*p).into());
-        });
-        let h = tau_powers_g2[0].clone();
-        let beta_h = tau_powers_g2[1].clone();
-        let universal_params = UniversalParams::<Bls12_377> {
-            powers_of_g: tau_powers_g1,
-            powers_of_gamma_g: alpha_tau_powers_g1,
-            h: h.clone(),
-            beta_h: beta_h.clone(),
-            neg_powers_of_h: prepared_neg_powers_of_h,
-            prepared_h: h.into(),
-            prepared_beta_h: beta_h.into(),
-        };
+        // the serialized parameters are loaded directly as the universal parameters of SonicKZG10
+        let mut serialized = vec![];
+        deserialized
+            .kzg_universal_params()
+            .unwrap()
+            .serialize_compressed(&mut serialized)
+            .unwrap();
+        let universal_params = UniversalParams::<Bls12_377>::deserialize_compressed(serialized.as_slice()).unwrap();
 
         for _ in 0..100 {
             let a = Fr::rand(&mut rng);
@@ -8,7 +8,7 @@ PROVING_SYSTEM=groth16
 POWER=18
 BATCH=131072
 CHUNK_SIZE=131072
-CURVE="bw6"
+CURVE=${CURVE:-"bw6"} # e.g. CURVE=bls12_381
 SEED1=$(tr -dc 'A-F0-9' < /dev/urandom | head -c32)
 echo $SEED1 > seed1
 SEED2=$(tr -dc 'A-F0-9' < /dev/urandom | head -c32)
@@ -1,6 +1,8 @@
 use std::marker::PhantomData;
 
 use ark_bls12_377::Bls12_377;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
 use ark_bw6_761::BW6_761;
 use ark_ec::pairing::Pairing;
 use ark_mnt4_753::MNT4_753;
@@ -56,7 +58,7 @@ fn create_circuit<E: Pairing>(opts: CircuitOpts) {
         .serialize_uncompressed(&mut serialized_matrices)
         .expect("Could not serialize matrices");
     let filename = format!("testcircuit_{}", opts.curve_kind);
-    write_to_file(&filename, &serialized_matrices);
+    write_to_file(&filename, &serialized_matrices).expect("Could not write the circuit");
     info!("Successfully created circuit at `{}`", filename);
 }
 
@@ -73,6 +75,8 @@ fn main() {
     info!("Creating circuit for {} curve", opts.curve_kind);
     match opts.curve_kind {
         CurveKind::Bls12_377 => create_circuit::<Bls12_377>(opts),
+        CurveKind::Bls12_381 => create_circuit::<Bls12_381>(opts),
+        CurveKind::Bn254 => create_circuit::<Bn254>(opts),
         CurveKind::BW6 => create_circuit::<BW6_761>(opts),
         CurveKind::MNT4_753 => create_circuit::<MNT4_753>(opts),
         CurveKind::MNT6_753 => create_circuit::<MNT6_753>(opts),
@@ -1,17 +1,44 @@
 use ark_mnt4_753::MNT4_753;
 use ark_mnt6_753::MNT6_753;
-use setup_utils::converters::CurveKind;
+use setup_utils::converters::{ContributionMode, CurveKind};
 
 use ark_bls12_377::Bls12_377;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
 use ark_bw6_761::BW6_761;
 use ark_ec::pairing::Pairing as Engine;
 
 use gumdrop::Options;
-use phase2_cli::{combine, contribute, new_challenge, verify, Command, Phase2Opts};
+use phase2_cli::{
+    check_circuit_manifest,
+    combine,
+    contribute,
+    contribute_streaming,
+    export_solidity,
+    export_zkey,
+    finalize,
+    new_challenge,
+    split,
+    verify,
+    verify_beacon,
+    verify_final,
+    verify_streaming,
+    Command,
+    Phase2Opts,
+    COMPRESS_CONTRIBUTE_INPUT,
+    STREAMING_IS_COMPRESSED,
+};
 use setup_utils::{
+    beacon_hash_from_str,
     derive_rng_from_seed,
+    snarkjs::SnarkjsPoint,
     upgrade_correctness_check_config,
+    write_failure_report,
+    BeaconCheckpoints,
+    CeremonyManifest,
     CheckForCorrectness,
+    Error,
+    Result,
     DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
     DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
 };
@@ -22,73 +49,199 @@ use tracing_subscriber::{
     fmt::{time::ChronoUtc, Subscriber},
 };
 
-fn execute_cmd<E: Engine>(opts: Phase2Opts)
+fn execute_cmd<E: Engine>(opts: Phase2Opts, manifest: Option<CeremonyManifest>) -> Result<()>
 where
-    E::G1Affine: Neg<Output = E::G1Affine>,
+    E::G1Affine: Neg<Output = E::G1Affine> + SnarkjsPoint,
+    E::G2Affine: SnarkjsPoint,
 {
     let command = opts.clone().command.unwrap_or_else(|| {
         error!("No command was provided.");
         error!("{}", Phase2Opts::usage());
-        process::exit(2)
+        process::exit(Error::EXIT_CODE_ARGS)
     });
 
     let now = Instant::now();
 
     match command {
         Command::New(opt) => {
+            if let Some(manifest) = &manifest {
+                check_circuit_manifest::<E>(&opt.circuit_fname, manifest)?;
+            }
             new_challenge::<E>(
                 &opt.challenge_fname,
                 &opt.challenge_hash_fname,
                 &opt.challenge_list_fname,
+                opts.contribution_mode,
                 opts.chunk_size,
                 &opt.phase1_fname,
                 &opt.circuit_fname,
-            );
+                opt.file_header,
+                if opt.streaming {
+                    STREAMING_IS_COMPRESSED
+                } else {
+                    COMPRESS_CONTRIBUTE_INPUT
+                },
+            )?;
         }
         Command::Contribute(opt) => {
-            let seed = hex::decode(&read_to_string(&opts.seed).expect("should have read seed").trim())
-                .expect("seed should be a hex string");
+            let seed = hex::decode(read_to_string(&opts.seed)?.trim())
+                .map_err(|_| Error::InvalidSeed(format!("{} should contain a hex string", opts.seed)))?;
             let rng = derive_rng_from_seed(&seed);
-            contribute::<E>(
-                &opt.challenge_fname,
-                &opt.challenge_hash_fname,
-                &opt.response_fname,
-                &opt.response_hash_fname,
-                upgrade_correctness_check_config(
-                    DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
-                    opts.force_correctness_checks,
-                ),
-                opts.batch_exp_mode,
-                rng,
+            let check_input_correctness = upgrade_correctness_check_config(
+                DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
+                opts.force_correctness_checks,
             );
+            if opt.streaming {
+                contribute_streaming::<E>(
+                    &opt.challenge_fname,
+                    &opt.challenge_hash_fname,
+                    &opt.response_fname,
+                    &opt.response_hash_fname,
+                    opts.batch_size,
+                    check_input_correctness,
+                    opts.batch_exp_mode,
+                    rng,
+                )?;
+            } else {
+                contribute::<E>(
+                    &opt.challenge_fname,
+                    &opt.challenge_hash_fname,
+                    &opt.response_fname,
+                    &opt.response_hash_fname,
+                    check_input_correctness,
+                    opts.batch_exp_mode,
+                    rng,
+                )?;
+            }
         }
-        Command::Verify(opt) => {
-            verify::<E>(
+        Command::Beacon(opt) => {
+            // use the beacon's randomness, after hashing it 2^iterations_exp times
+            let beacon_hash = beacon_hash_from_str(&opt.beacon_hash)?;
+            let checkpoints =
+                BeaconCheckpoints::read_or_compute(&opt.checkpoints_fname, beacon_hash, opt.iterations_exp)?;
+            let rng = derive_rng_from_seed(&checkpoints.result);
+            let check_input_correctness = upgrade_correctness_check_config(
+                DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
+                opts.force_correctness_checks,
+            );
+            if opt.streaming {
+                contribute_streaming::<E>(
+                    &opt.challenge_fname,
+                    &opt.challenge_hash_fname,
+                    &opt.response_fname,
+                    &opt.response_hash_fname,
+                    opts.batch_size,
+                    check_input_correctness,
+                    opts.batch_exp_mode,
+                    rng,
+                )?;
+            } else {
+                contribute::<E>(
+                    &opt.challenge_fname,
+                    &opt.challenge_hash_fname,
+                    &opt.response_fname,
+                    &opt.response_hash_fname,
+                    check_input_correctness,
+                    opts.batch_exp_mode,
+                    rng,
+                )?;
+            }
+        }
+        Command::VerifyBeacon(opt) => {
+            verify_beacon::<E>(
                 &opt.challenge_fname,
-                &opt.challenge_hash_fname,
-                DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
                 &opt.response_fname,
-                &opt.response_hash_fname,
-                CheckForCorrectness::OnlyNonZero,
-                &opt.new_challenge_fname,
-                &opt.new_challenge_hash_fname,
-                opts.subgroup_check_mode,
-                false,
-            );
+                &opt.checkpoints_fname,
+                beacon_hash_from_str(&opt.beacon_hash)?,
+                opt.iterations_exp,
+                opt.allow_low_iterations,
+                if opt.streaming {
+                    STREAMING_IS_COMPRESSED
+                } else {
+                    COMPRESS_CONTRIBUTE_INPUT
+                },
+            )?;
+        }
+        Command::Verify(opt) => {
+            if opt.streaming {
+                // The chunked verification reads both files with the same checks, so they are the
+                // ones of the response: decompressing the points checks that they're on the curve,
+                // and they're checked to be in the subgroup batch by batch
+                verify_streaming::<E>(
+                    &opt.challenge_fname,
+                    &opt.challenge_hash_fname,
+                    &opt.response_fname,
+                    &opt.response_hash_fname,
+                    &opt.new_challenge_fname,
+                    &opt.new_challenge_hash_fname,
+                    opts.batch_size,
+                    CheckForCorrectness::No,
+                    opts.subgroup_check_mode,
+                )?;
+            } else {
+                verify::<E>(
+                    &opt.challenge_fname,
+                    &opt.challenge_hash_fname,
+                    DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+                    &opt.response_fname,
+                    &opt.response_hash_fname,
+                    CheckForCorrectness::OnlyNonZero,
+                    &opt.new_challenge_fname,
+                    &opt.new_challenge_hash_fname,
+                    opts.subgroup_check_mode,
+                    false,
+                )?;
+            }
         }
-        Command::Combine(opt) => {
-            combine::<E>(
+        Command::Combine(opt) => match opts.contribution_mode {
+            ContributionMode::Full => {
+                finalize::<E>(&opt.initial_full_fname, &opt.response_fname, &opt.combined_fname)?;
+            }
+            ContributionMode::Chunked => {
+                combine::<E>(
+                    &opt.initial_query_fname,
+                    &opt.initial_full_fname,
+                    &opt.response_list_fname,
+                    &opt.combined_fname,
+                    false,
+                )?;
+            }
+        },
+        Command::VerifyFinal(opt) => {
+            verify_final::<E>(
                 &opt.initial_query_fname,
                 &opt.initial_full_fname,
-                &opt.response_list_fname,
                 &opt.combined_fname,
-                false,
-            );
+                DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+                CheckForCorrectness::OnlyNonZero,
+                opts.subgroup_check_mode,
+            )?;
+        }
+        Command::Split(opt) => {
+            split::<E>(
+                &opt.full_fname,
+                &opt.challenge_fname,
+                &opt.challenge_list_fname,
+                opts.chunk_size,
+                if opt.streaming {
+                    STREAMING_IS_COMPRESSED
+                } else {
+                    COMPRESS_CONTRIBUTE_INPUT
+                },
+            )?;
+        }
+        Command::ExportZkey(opt) => {
+            export_zkey::<E>(&opt.combined_fname, &opt.circuit_fname, &opt.zkey_fname)?;
+        }
+        Command::ExportSolidity(opt) => {
+            export_solidity(&opts.curve_kind, &opt.combined_fname, &opt.verifier_fname)?;
         }
     };
 
     let new_now = Instant::now();
     info!("Executing {:?} took: {:?}", opts, new_now.duration_since(now));
+
+    Ok(())
 }
 
 fn main() {
@@ -98,12 +251,28 @@ fn main() {
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
-    let opts: Phase2Opts = Phase2Opts::parse_args_default_or_exit();
+    let mut opts: Phase2Opts = Phase2Opts::parse_args_default_or_exit();
+    let failure_report = opts.failure_report.clone();
 
-    match opts.curve_kind {
-        CurveKind::Bls12_377 => execute_cmd::<Bls12_377>(opts),
-        CurveKind::BW6 => execute_cmd::<BW6_761>(opts),
-        CurveKind::MNT4_753 => execute_cmd::<MNT4_753>(opts),
-        CurveKind::MNT6_753 => execute_cmd::<MNT6_753>(opts),
-    };
+    let result = opts.apply_manifest().and_then(|manifest| match opts.curve_kind {
+        CurveKind::Bls12_377 => execute_cmd::<Bls12_377>(opts, manifest),
+        CurveKind::Bls12_381 => execute_cmd::<Bls12_381>(opts, manifest),
+        CurveKind::Bn254 => execute_cmd::<Bn254>(opts, manifest),
+        CurveKind::BW6 => execute_cmd::<BW6_761>(opts, manifest),
+        CurveKind::MNT4_753 => execute_cmd::<MNT4_753>(opts, manifest),
+        CurveKind::MNT6_753 => execute_cmd::<MNT6_753>(opts, manifest),
+    });
+
+    if let Err(e) = result {
+        error!("{}", e);
+        if let Some(failure_report) = failure_report {
+            if let Err(report_err) = write_failure_report(&failure_report, &e) {
+                error!(
+                    "Could not write the failure report to {}: {}",
+                    failure_report, report_err
+                );
+            }
+        }
+        process::exit(e.exit_code());
+    }
 }
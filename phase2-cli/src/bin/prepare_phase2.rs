@@ -5,14 +5,17 @@ use phase2_cli::prepare_phase2;
 use setup_utils::{
     converters::{curve_from_str, proving_system_from_str, CurveKind, ProvingSystem},
     Result,
+    DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
 };
 
 use ark_bls12_377::Bls12_377;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
 use ark_bw6_761::BW6_761;
 
 use gumdrop::Options;
-use std::time::Instant;
-use tracing::info;
+use std::{process, time::Instant};
+use tracing::{error, info};
 use tracing_subscriber::{
     filter::EnvFilter,
     fmt::{time::ChronoUtc, Subscriber},
@@ -45,32 +48,70 @@ struct PreparePhase2Opts {
     pub phase2_size: Option<usize>,
 }
 
-fn main() -> Result<()> {
-    Subscriber::builder()
-        .with_timer(ChronoUtc::rfc3339())
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
-
-    let opts = PreparePhase2Opts::parse_args_default_or_exit();
+fn execute_cmd(opts: &PreparePhase2Opts) -> Result<()> {
     let phase2_size = opts.phase2_size.unwrap_or(1 << opts.power);
 
     let now = Instant::now();
     match opts.curve_kind {
         CurveKind::Bls12_377 => {
             let parameters = Phase1Parameters::<Bls12_377>::new_full(opts.proving_system, opts.power, opts.batch_size);
-            prepare_phase2::<Bls12_377>(&opts.phase2_fname, &opts.response_fname, phase2_size, &parameters)?
+            prepare_phase2::<Bls12_377>(
+                &opts.phase2_fname,
+                &opts.response_fname,
+                phase2_size,
+                &parameters,
+                DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+            )?
+        }
+        CurveKind::Bls12_381 => {
+            let parameters = Phase1Parameters::<Bls12_381>::new_full(opts.proving_system, opts.power, opts.batch_size);
+            prepare_phase2::<Bls12_381>(
+                &opts.phase2_fname,
+                &opts.response_fname,
+                phase2_size,
+                &parameters,
+                DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+            )?
+        }
+        CurveKind::Bn254 => {
+            let parameters = Phase1Parameters::<Bn254>::new_full(opts.proving_system, opts.power, opts.batch_size);
+            prepare_phase2::<Bn254>(
+                &opts.phase2_fname,
+                &opts.response_fname,
+                phase2_size,
+                &parameters,
+                DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+            )?
         }
         CurveKind::BW6 => {
             let parameters = Phase1Parameters::<BW6_761>::new_full(opts.proving_system, opts.power, opts.batch_size);
-            prepare_phase2::<BW6_761>(&opts.phase2_fname, &opts.response_fname, phase2_size, &parameters)?
+            prepare_phase2::<BW6_761>(
+                &opts.phase2_fname,
+                &opts.response_fname,
+                phase2_size,
+                &parameters,
+                DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+            )?
         }
         CurveKind::MNT4_753 => {
             let parameters = Phase1Parameters::<MNT4_753>::new_full(opts.proving_system, opts.power, opts.batch_size);
-            prepare_phase2::<MNT4_753>(&opts.phase2_fname, &opts.response_fname, phase2_size, &parameters)?
+            prepare_phase2::<MNT4_753>(
+                &opts.phase2_fname,
+                &opts.response_fname,
+                phase2_size,
+                &parameters,
+                DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+            )?
         }
         CurveKind::MNT6_753 => {
             let parameters = Phase1Parameters::<MNT6_753>::new_full(opts.proving_system, opts.power, opts.batch_size);
-            prepare_phase2::<MNT6_753>(&opts.phase2_fname, &opts.response_fname, phase2_size, &parameters)?
+            prepare_phase2::<MNT6_753>(
+                &opts.phase2_fname,
+                &opts.response_fname,
+                phase2_size,
+                &parameters,
+                DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+            )?
         }
     }
 
@@ -79,3 +120,17 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+fn main() {
+    Subscriber::builder()
+        .with_timer(ChronoUtc::rfc3339())
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let opts = PreparePhase2Opts::parse_args_default_or_exit();
+
+    if let Err(e) = execute_cmd(&opts) {
+        error!("{}", e);
+        process::exit(e.exit_code());
+    }
+}
@@ -1,5 +1,14 @@
 use phase2::parameters::{verify_transcript, MPCParameters};
-use setup_utils::{print_hash, CheckForCorrectness, SubgroupCheckMode, UseCompression};
+use setup_utils::{
+    print_hash,
+    split_file_header,
+    CheckForCorrectness,
+    Error,
+    FileHeader,
+    Result,
+    SubgroupCheckMode,
+    UseCompression,
+};
 
 use ark_ec::pairing::Pairing;
 use ark_serialize::CanonicalSerialize;
@@ -11,42 +20,49 @@ use std::{
 };
 use tracing::info;
 
-use crate::{COMBINED_IS_COMPRESSED, COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT};
+use crate::{write_parameters, COMBINED_IS_COMPRESSED, COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT};
 
+/// Combines the responses to every chunk into full parameters. If the initial full parameters start
+/// with a file header, so do the combined parameters.
 pub fn combine<P: Pairing + Sync>(
     initial_query_filename: &str,
     initial_full_filename: &str,
     response_list_filename: &str,
     combined_filename: &str,
     combine_initial: bool,
-) where
+) -> Result<()>
+where
     P::G1Affine: Neg<Output = P::G1Affine>,
 {
     info!("Combining phase 2");
 
-    let response_list_reader =
-        BufReader::new(File::open(response_list_filename).expect("should have opened the response list"));
+    let response_list_reader = BufReader::new(File::open(response_list_filename)?);
+    let response_filenames = response_list_reader.lines().collect::<std::io::Result<Vec<_>>>()?;
+    if response_filenames.is_empty() {
+        return Err(Error::InvalidResponseList(format!(
+            "{} does not list any response",
+            response_list_filename
+        )));
+    }
 
-    let full_contents = std::fs::read(initial_full_filename).expect("should have initial full parameters");
+    let full_contents = std::fs::read(initial_full_filename)?;
+    let (full_header, _) = split_file_header(&full_contents)?;
     let full_parameters = MPCParameters::<P>::read_fast(
         full_contents.as_slice(),
         UseCompression::No,
         CheckForCorrectness::No,
         false,
         SubgroupCheckMode::Auto,
-    )
-    .expect("should have read full parameters");
+    )?;
 
-    let mut query_contents =
-        std::io::Cursor::new(std::fs::read(initial_query_filename).expect("should have read initial query"));
+    let mut query_contents = std::io::Cursor::new(std::fs::read(initial_query_filename)?);
     let query_parameters = MPCParameters::<P>::read_groth16_fast(
         &mut query_contents,
         UseCompression::No,
         CheckForCorrectness::No,
         false,
         SubgroupCheckMode::Auto,
-    )
-    .expect("should have deserialized initial query params");
+    )?;
 
     let parameters_compressed = if combine_initial {
         COMPRESS_CONTRIBUTE_INPUT
@@ -54,29 +70,39 @@ pub fn combine<P: Pairing + Sync>(
         COMPRESS_CONTRIBUTE_OUTPUT
     };
     let mut all_parameters = vec![];
-    for line in response_list_reader.lines() {
-        let line = line.expect("should have read line");
-        let contents = std::fs::read(line).expect("should have read response");
+    for (i, response_filename) in response_filenames.iter().enumerate() {
+        if response_filename.trim().is_empty() {
+            return Err(Error::InvalidResponseList(format!(
+                "line {} of {} is empty",
+                i + 1,
+                response_list_filename
+            )));
+        }
+        let contents = std::fs::read(response_filename)?;
+        if let (Some(header), _) = split_file_header(&contents)? {
+            if header.chunk_index != i as u64 {
+                return Err(Error::FileHeaderMismatch(format!(
+                    "{} is a response to chunk {}, expected chunk {}",
+                    response_filename, header.chunk_index, i
+                )));
+            }
+        }
         let parameters = MPCParameters::<P>::read_fast(
             contents.as_slice(),
             parameters_compressed,
             CheckForCorrectness::No,
             false,
             SubgroupCheckMode::Auto,
-        )
-        .expect("should have read parameters");
+        )?;
         all_parameters.push(parameters);
     }
 
-    let combined =
-        MPCParameters::<P>::combine(&query_parameters, &all_parameters).expect("should have combined parameters");
+    let combined = MPCParameters::<P>::combine(&query_parameters, &all_parameters)?;
 
     let contributions_hash = if combine_initial {
-        verify_transcript(full_parameters.cs_hash, &combined.contributions).expect("should have verified successfully")
+        verify_transcript(full_parameters.cs_hash, &combined.contributions)?
     } else {
-        full_parameters
-            .verify(&combined)
-            .expect("should have verified successfully")
+        full_parameters.verify(&combined)?
     };
 
     info!("Contributions hashes:");
@@ -84,17 +110,75 @@ pub fn combine<P: Pairing + Sync>(
         print_hash(&contribution_hash[..]);
     }
 
+    write_combined(&combined, full_header, combined_filename)
+}
+
+/// Finalizes a ceremony run in the full mode, where there are no chunks to combine: the last
+/// response is verified against the initial full parameters and written as the combined
+/// parameters. If the initial full parameters start with a file header, the response must describe
+/// the same parameters if it has one, and the combined parameters get one too.
+pub fn finalize<P: Pairing + Sync>(
+    initial_full_filename: &str,
+    response_filename: &str,
+    combined_filename: &str,
+) -> Result<()>
+where
+    P::G1Affine: Neg<Output = P::G1Affine>,
+{
+    info!("Finalizing phase 2");
+
+    let full_contents = std::fs::read(initial_full_filename)?;
+    let (full_header, _) = split_file_header(&full_contents)?;
+    let full_parameters = MPCParameters::<P>::read_fast(
+        full_contents.as_slice(),
+        COMPRESS_CONTRIBUTE_INPUT,
+        CheckForCorrectness::No,
+        false,
+        SubgroupCheckMode::Auto,
+    )?;
+
+    let response_contents = std::fs::read(response_filename)?;
+    let (response_header, _) = split_file_header(&response_contents)?;
+    if let (Some(full_header), Some(response_header)) = (&full_header, &response_header) {
+        response_header.check(&full_header.with_compression(COMPRESS_CONTRIBUTE_OUTPUT))?;
+    }
+    let final_parameters = MPCParameters::<P>::read_fast(
+        response_contents.as_slice(),
+        COMPRESS_CONTRIBUTE_OUTPUT,
+        CheckForCorrectness::Full,
+        true,
+        SubgroupCheckMode::Auto,
+    )?;
+
+    let contributions_hash = full_parameters.verify(&final_parameters)?;
+
+    info!("Contributions hashes:");
+    for contribution_hash in contributions_hash {
+        print_hash(&contribution_hash[..]);
+    }
+
+    write_combined(&final_parameters, full_header, combined_filename)
+}
+
+/// Writes the final parameters with their transcript, and the bare Groth16 parameters next to them
+fn write_combined<P: Pairing>(
+    combined: &MPCParameters<P>,
+    full_header: Option<FileHeader>,
+    combined_filename: &str,
+) -> Result<()>
+where
+    P::G1Affine: Neg<Output = P::G1Affine>,
+{
     let mut combined_contents = vec![];
-    combined
-        .write(&mut combined_contents, COMBINED_IS_COMPRESSED)
-        .expect("should have written combined");
-    std::fs::write(combined_filename, &combined_contents).expect("should have written combined file");
+    combined.write(&mut combined_contents, COMBINED_IS_COMPRESSED)?;
+    let combined_header = full_header.map(|header| header.with_compression(COMBINED_IS_COMPRESSED));
+    write_parameters(combined_filename, combined_header.as_ref(), &combined_contents)?;
 
     let mut combined_parameters_contents = vec![];
     combined
         .params
-        .serialize_uncompressed(&mut combined_parameters_contents)
-        .expect("should have serialized combined parameters");
-    std::fs::write(format!("{}.params", combined_filename), &combined_parameters_contents)
-        .expect("should have written combined parameters file");
+        .serialize_uncompressed(&mut combined_parameters_contents)?;
+    std::fs::write(format!("{}.params", combined_filename), &combined_parameters_contents)?;
+
+    Ok(())
 }
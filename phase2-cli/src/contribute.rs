@@ -1,13 +1,24 @@
 use phase2::parameters::MPCParameters;
-use setup_utils::{calculate_hash, print_hash, write_to_file, BatchExpMode, CheckForCorrectness, SubgroupCheckMode};
+use setup_utils::{
+    calculate_hash,
+    print_hash,
+    split_file_header,
+    write_to_file,
+    BatchExpMode,
+    CheckForCorrectness,
+    Result,
+    SubgroupCheckMode,
+};
 
 use ark_ec::pairing::Pairing;
 
-use crate::{COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT};
+use crate::{write_parameters, COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT};
 use rand::Rng;
 use std::ops::Neg;
 use tracing::info;
 
+/// Contributes to the challenge and writes the response. If the challenge starts with a file
+/// header, the response gets one too.
 pub fn contribute<P: Pairing + Sync>(
     challenge_filename: &str,
     challenge_hash_filename: &str,
@@ -16,14 +27,16 @@ pub fn contribute<P: Pairing + Sync>(
     check_input_correctness: CheckForCorrectness,
     batch_exp_mode: BatchExpMode,
     mut rng: impl Rng,
-) where
+) -> Result<()>
+where
     P::G1Affine: Neg<Output = P::G1Affine>,
 {
     info!("Contributing to phase 2");
 
-    let challenge_contents = std::fs::read(challenge_filename).expect("should have read challenge");
-    let challenge_hash = calculate_hash(&challenge_contents);
-    write_to_file(challenge_hash_filename, &challenge_hash);
+    let challenge_contents = std::fs::read(challenge_filename)?;
+    let (challenge_header, challenge_body) = split_file_header(&challenge_contents)?;
+    let challenge_hash = calculate_hash(challenge_body);
+    write_to_file(challenge_hash_filename, &challenge_hash)?;
 
     info!("`challenge` file contains decompressed points and has a hash:");
     print_hash(&challenge_hash);
@@ -34,22 +47,20 @@ pub fn contribute<P: Pairing + Sync>(
         check_input_correctness,
         false,
         SubgroupCheckMode::Auto,
-    )
-    .expect("should have read parameters");
-    parameters
-        .contribute(batch_exp_mode, &mut rng)
-        .expect("should have successfully contributed");
+    )?;
+    parameters.contribute(batch_exp_mode, &mut rng)?;
     let mut serialized_response = vec![];
-    parameters
-        .write(&mut serialized_response, COMPRESS_CONTRIBUTE_OUTPUT)
-        .expect("should have written input");
-    write_to_file(response_filename, &serialized_response);
+    parameters.write(&mut serialized_response, COMPRESS_CONTRIBUTE_OUTPUT)?;
+    let response_header = challenge_header.map(|header| header.with_compression(COMPRESS_CONTRIBUTE_OUTPUT));
+    write_parameters(response_filename, response_header.as_ref(), &serialized_response)?;
     let response_hash = calculate_hash(&serialized_response);
-    write_to_file(response_hash_filename, &response_hash);
+    write_to_file(response_hash_filename, &response_hash)?;
     info!(
         "Done!\n\n\
               Your contribution has been written to response file\n\n\
               The BLAKE2b hash of response file is:\n"
     );
     print_hash(&response_hash);
+
+    Ok(())
 }
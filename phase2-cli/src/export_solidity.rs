@@ -0,0 +1,364 @@
+//! Export of the verifying key of a finished ceremony to a Solidity Groth16 verifier.
+//!
+//! EVM chains only have precompiles for BN254 (`ecAdd` at 0x06, `ecMul` at 0x07 and `ecPairing`
+//! at 0x08), so verifiers can only be generated for that curve. The contract exposes
+//!
+//! ```ignore
+//! function verifyProof(uint256[2] a, uint256[2][2] b, uint256[2] c, uint256[N] input) returns (bool)
+//! ```
+//!
+//! which takes the proof and the `N` public inputs as 32 byte big-endian words. Solidity has no
+//! zero-length arrays, so `input` is left out for circuits without public inputs. Like the
+//! precompiles, it expects each G2 coordinate as the imaginary part followed by the real part,
+//! and [`proof_calldata`] encodes proofs accordingly.
+use phase2::parameters::MPCParameters;
+use setup_utils::{converters::CurveKind, CheckForCorrectness, Error, Result, SubgroupCheckMode};
+
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+use std::{fmt::Write as _, fs::OpenOptions, io::Write};
+use tracing::info;
+
+use crate::COMBINED_IS_COMPRESSED;
+
+/// Reads the combined parameters of a finished ceremony over BN254 and writes a Solidity
+/// contract verifying proofs for its circuit.
+pub fn export_solidity(curve_kind: &CurveKind, combined_filename: &str, verifier_filename: &str) -> Result<()> {
+    if !matches!(curve_kind, CurveKind::Bn254) {
+        return Err(Error::UnsupportedCurve(format!(
+            "Solidity verifiers rely on the BN254 precompiles and cannot be generated for {}",
+            curve_kind
+        )));
+    }
+
+    info!("Exporting the verifying key to a Solidity verifier");
+
+    let combined_contents = std::fs::read(combined_filename)?;
+    let parameters = MPCParameters::<Bn254>::read_fast(
+        combined_contents.as_slice(),
+        COMBINED_IS_COMPRESSED,
+        CheckForCorrectness::No,
+        false,
+        SubgroupCheckMode::Auto,
+    )?;
+
+    let mut writer = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(verifier_filename)?;
+    writer.write_all(solidity_verifier(&parameters.params.vk).as_bytes())?;
+
+    info!(
+        "Exported a verifier for {} public inputs to {}",
+        parameters.params.vk.gamma_abc_g1.len() - 1,
+        verifier_filename
+    );
+
+    Ok(())
+}
+
+/// Returns the source of a Solidity contract verifying Groth16 proofs under `vk`
+pub fn solidity_verifier(vk: &VerifyingKey<Bn254>) -> String {
+    let num_inputs = vk.gamma_abc_g1.len() - 1;
+
+    let mut constants = String::new();
+    write_g1_constant(&mut constants, "ALPHA", &vk.alpha_g1);
+    write_g2_constant(&mut constants, "BETA", &vk.beta_g2);
+    write_g2_constant(&mut constants, "GAMMA", &vk.gamma_g2);
+    write_g2_constant(&mut constants, "DELTA", &vk.delta_g2);
+    for (i, ic) in vk.gamma_abc_g1.iter().enumerate() {
+        write_g1_constant(&mut constants, &format!("IC{}", i), ic);
+    }
+
+    // Solidity rejects zero-length arrays, so a circuit without public inputs takes none
+    let input_parameter = match num_inputs {
+        0 => String::new(),
+        _ => format!(",\n        uint256[{}] calldata input", num_inputs),
+    };
+
+    // vk_x = IC0 + sum_i input[i] * IC(i + 1)
+    let mut linear_combination = String::new();
+    for i in 0..num_inputs {
+        let _ = writeln!(
+            linear_combination,
+            "        require(input[{i}] < SNARK_SCALAR_FIELD, \"input is not in the scalar field\");\n        \
+             vkX = ecAdd(vkX, ecMul([IC{j}_X, IC{j}_Y], input[{i}]));",
+            i = i,
+            j = i + 1
+        );
+    }
+
+    format!(
+        r#"// SPDX-License-Identifier: MIT
+// Generated by the snark-setup phase2 CLI from the parameters of a finished ceremony.
+pragma solidity ^0.8.0;
+
+contract Verifier {{
+    uint256 constant SNARK_SCALAR_FIELD = {r};
+    uint256 constant PRIME_Q = {q};
+
+{constants}
+    /// Returns whether the proof (a, b, c) is valid for the public inputs
+    function verifyProof(
+        uint256[2] calldata a,
+        uint256[2][2] calldata b,
+        uint256[2] calldata c{input_parameter}
+    ) public view returns (bool) {{
+        uint256[2] memory vkX = [IC0_X, IC0_Y];
+{linear_combination}
+        // e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+        uint256[24] memory pairingInput = [
+            a[0], negate(a[1]), b[0][0], b[0][1], b[1][0], b[1][1],
+            ALPHA_X, ALPHA_Y, BETA_X_IM, BETA_X_RE, BETA_Y_IM, BETA_Y_RE,
+            vkX[0], vkX[1], GAMMA_X_IM, GAMMA_X_RE, GAMMA_Y_IM, GAMMA_Y_RE,
+            c[0], c[1], DELTA_X_IM, DELTA_X_RE, DELTA_Y_IM, DELTA_Y_RE
+        ];
+        uint256[1] memory result;
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x08, pairingInput, 768, result, 32)
+        }}
+        require(success, "pairing failed");
+        return result[0] == 1;
+    }}
+
+    function negate(uint256 y) internal pure returns (uint256) {{
+        return y == 0 ? 0 : PRIME_Q - (y % PRIME_Q);
+    }}
+
+    function ecAdd(uint256[2] memory p, uint256[2] memory q) internal view returns (uint256[2] memory r) {{
+        uint256[4] memory input = [p[0], p[1], q[0], q[1]];
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x06, input, 128, r, 64)
+        }}
+        require(success, "ecAdd failed");
+    }}
+
+    function ecMul(uint256[2] memory p, uint256 s) internal view returns (uint256[2] memory r) {{
+        uint256[3] memory input = [p[0], p[1], s];
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x07, input, 96, r, 64)
+        }}
+        require(success, "ecMul failed");
+    }}
+}}
+"#,
+        r = word_hex(&Fr::MODULUS.to_bytes_be()),
+        q = word_hex(&Fq::MODULUS.to_bytes_be()),
+        constants = constants,
+        input_parameter = input_parameter,
+        linear_combination = linear_combination,
+    )
+}
+
+/// Returns the calldata of a `verifyProof` call for `proof` and `public_inputs`: the function
+/// selector followed by the ABI encoding of the arguments. Without public inputs, the function
+/// takes no `input` argument.
+pub fn proof_calldata(proof: &Proof<Bn254>, public_inputs: &[Fr]) -> Vec<u8> {
+    let signature = match public_inputs.len() {
+        0 => "verifyProof(uint256[2],uint256[2][2],uint256[2])".to_string(),
+        num_inputs => format!(
+            "verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{}])",
+            num_inputs
+        ),
+    };
+    let mut calldata = Keccak256::digest(signature.as_bytes())[..4].to_vec();
+    let words = g1_words(&proof.a)
+        .into_iter()
+        .chain(g2_words(&proof.b))
+        .chain(g1_words(&proof.c))
+        .chain(public_inputs.iter().map(field_word));
+    for word in words {
+        calldata.extend_from_slice(&word);
+    }
+    calldata
+}
+
+/// The words of a G1 point, with the point at infinity encoded as (0, 0) like the precompiles do
+fn g1_words(p: &G1Affine) -> [[u8; 32]; 2] {
+    match p.xy() {
+        Some((x, y)) => [field_word(x), field_word(y)],
+        None => [[0; 32]; 2],
+    }
+}
+
+/// The words of a G2 point, with the imaginary part of each coordinate first
+fn g2_words(p: &G2Affine) -> [[u8; 32]; 4] {
+    match p.xy() {
+        Some((x, y)) => [
+            field_word(&x.c1),
+            field_word(&x.c0),
+            field_word(&y.c1),
+            field_word(&y.c0),
+        ],
+        None => [[0; 32]; 4],
+    }
+}
+
+/// The 32 byte big-endian word of a field element
+fn field_word<F: PrimeField>(f: &F) -> [u8; 32] {
+    let bytes = f.into_bigint().to_bytes_be();
+    let mut word = [0; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+fn word_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn write_g1_constant(out: &mut String, name: &str, p: &G1Affine) {
+    let [x, y] = g1_words(p);
+    let _ = writeln!(out, "    uint256 constant {}_X = {};", name, word_hex(&x));
+    let _ = writeln!(out, "    uint256 constant {}_Y = {};", name, word_hex(&y));
+}
+
+fn write_g2_constant(out: &mut String, name: &str, p: &G2Affine) {
+    let [x_im, x_re, y_im, y_re] = g2_words(p);
+    let _ = writeln!(out, "    uint256 constant {}_X_IM = {};", name, word_hex(&x_im));
+    let _ = writeln!(out, "    uint256 constant {}_X_RE = {};", name, word_hex(&x_re));
+    let _ = writeln!(out, "    uint256 constant {}_Y_IM = {};", name, word_hex(&y_im));
+    let _ = writeln!(out, "    uint256 constant {}_Y_RE = {};", name, word_hex(&y_re));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phase2::helpers::testing::TestCircuit;
+
+    use ark_bn254::{Fq2, G1Projective};
+    use ark_ec::{pairing::Pairing, CurveGroup};
+    use ark_groth16::Groth16;
+    use ark_relations::{
+        lc,
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    };
+    use ark_snark::SNARK;
+    use ark_std::{UniformRand, Zero};
+
+    use rand::thread_rng;
+
+    /// Reads the value of a constant of the generated contract
+    fn constant(source: &str, name: &str) -> Fq {
+        let prefix = format!("uint256 constant {} = 0x", name);
+        let line = source
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(&prefix))
+            .unwrap();
+        Fq::from_be_bytes_mod_order(&hex::decode(line.trim_end_matches(';')).unwrap())
+    }
+
+    fn g1_constant(source: &str, name: &str) -> G1Affine {
+        G1Affine::new(
+            constant(source, &format!("{}_X", name)),
+            constant(source, &format!("{}_Y", name)),
+        )
+    }
+
+    fn g2_constant(source: &str, name: &str) -> G2Affine {
+        G2Affine::new(
+            Fq2::new(
+                constant(source, &format!("{}_X_RE", name)),
+                constant(source, &format!("{}_X_IM", name)),
+            ),
+            Fq2::new(
+                constant(source, &format!("{}_Y_RE", name)),
+                constant(source, &format!("{}_Y_IM", name)),
+            ),
+        )
+    }
+
+    /// Checks the calldata against the generated contract the way the contract does
+    fn verify_calldata(source: &str, calldata: &[u8], num_inputs: usize) -> bool {
+        let words: Vec<Fq> = calldata[4..].chunks(32).map(Fq::from_be_bytes_mod_order).collect();
+        let a = G1Affine::new(words[0], words[1]);
+        let b = G2Affine::new(Fq2::new(words[3], words[2]), Fq2::new(words[5], words[4]));
+        let c = G1Affine::new(words[6], words[7]);
+
+        let mut vk_x = G1Projective::from(g1_constant(source, "IC0"));
+        for i in 0..num_inputs {
+            let input = Fr::from_be_bytes_mod_order(&calldata[4 + (8 + i) * 32..4 + (9 + i) * 32]);
+            vk_x += g1_constant(source, &format!("IC{}", i + 1)) * input;
+        }
+
+        Bn254::multi_pairing([-a, g1_constant(source, "ALPHA"), vk_x.into_affine(), c], [
+            b,
+            g2_constant(source, "BETA"),
+            g2_constant(source, "GAMMA"),
+            g2_constant(source, "DELTA"),
+        ])
+        .is_zero()
+    }
+
+    #[test]
+    fn test_solidity_calldata() {
+        let rng = &mut thread_rng();
+        let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(TestCircuit::<Bn254>(None), rng).unwrap();
+
+        let x = Fr::rand(rng);
+        let public_inputs = [x * x];
+        let proof = Groth16::<Bn254>::prove(&pk, TestCircuit::<Bn254>(Some(x)), rng).unwrap();
+        assert!(Groth16::<Bn254>::verify(&vk, &public_inputs, &proof).unwrap());
+
+        let calldata = proof_calldata(&proof, &public_inputs);
+        assert_eq!(calldata.len(), 4 + (8 + public_inputs.len()) * 32);
+        // keccak256("verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[1])")
+        assert_eq!(calldata[..4], [0x43, 0x75, 0x3b, 0x4d]);
+        // the imaginary part of the x coordinate of B comes first
+        let b_x = proof.b.xy().unwrap().0;
+        assert_eq!(calldata[4 + 2 * 32..4 + 3 * 32], field_word(&b_x.c1));
+        assert_eq!(calldata[4 + 3 * 32..4 + 4 * 32], field_word(&b_x.c0));
+        assert_eq!(calldata[4 + 8 * 32..], field_word(&public_inputs[0]));
+
+        let source = solidity_verifier(&vk);
+        assert!(source.contains("uint256[1] calldata input"));
+        assert!(verify_calldata(&source, &calldata, public_inputs.len()));
+        let wrong_input = proof_calldata(&proof, &[x]);
+        assert!(!verify_calldata(&source, &wrong_input, public_inputs.len()));
+    }
+
+    /// Knowledge of a square root of a private value, without any public input
+    struct PrivateSquareRoot(Option<Fr>);
+
+    impl ConstraintSynthesizer<Fr> for PrivateSquareRoot {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> std::result::Result<(), SynthesisError> {
+            let x = cs.new_witness_variable(|| self.0.ok_or(SynthesisError::AssignmentMissing))?;
+            let x_squared =
+                cs.new_witness_variable(|| self.0.map(|x| x * x).ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + x_squared)
+        }
+    }
+
+    #[test]
+    fn test_solidity_without_public_inputs() {
+        let rng = &mut thread_rng();
+        let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(PrivateSquareRoot(None), rng).unwrap();
+        assert_eq!(vk.gamma_abc_g1.len(), 1);
+        let proof = Groth16::<Bn254>::prove(&pk, PrivateSquareRoot(Some(Fr::rand(rng))), rng).unwrap();
+        assert!(Groth16::<Bn254>::verify(&vk, &[], &proof).unwrap());
+
+        let calldata = proof_calldata(&proof, &[]);
+        assert_eq!(calldata.len(), 4 + 8 * 32);
+        assert_eq!(
+            calldata[..4],
+            Keccak256::digest(b"verifyProof(uint256[2],uint256[2][2],uint256[2])")[..4]
+        );
+
+        let source = solidity_verifier(&vk);
+        assert!(!source.contains("calldata input"), "{}", source);
+        assert!(source.contains("uint256[2] calldata c\n    ) public view returns (bool)"));
+        assert!(verify_calldata(&source, &calldata, 0));
+    }
+
+    #[test]
+    fn test_solidity_rejects_other_curves() {
+        let err = export_solidity(&CurveKind::Bls12_377, "combined", "Verifier.sol").unwrap_err();
+        assert!(matches!(err, Error::UnsupportedCurve(_)));
+    }
+}
@@ -0,0 +1,48 @@
+use phase2::{load_circuit::Matrices, parameters::MPCParameters};
+use setup_utils::{snarkjs::SnarkjsPoint, CheckForCorrectness, Result, SubgroupCheckMode};
+
+use ark_ec::pairing::Pairing;
+
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    ops::Neg,
+};
+use tracing::info;
+
+use crate::COMBINED_IS_COMPRESSED;
+
+/// Converts the combined parameters of a finished ceremony and the constraints of the circuit
+/// to a snarkjs `.zkey` file.
+pub fn export_zkey<P: Pairing>(combined_filename: &str, circuit_filename: &str, zkey_filename: &str) -> Result<()>
+where
+    P::G1Affine: Neg<Output = P::G1Affine> + SnarkjsPoint,
+    P::G2Affine: SnarkjsPoint,
+{
+    info!("Exporting the combined parameters to a zkey file");
+
+    let combined_contents = std::fs::read(combined_filename)?;
+    let parameters = MPCParameters::<P>::read_fast(
+        combined_contents.as_slice(),
+        COMBINED_IS_COMPRESSED,
+        CheckForCorrectness::No,
+        false,
+        SubgroupCheckMode::Auto,
+    )?;
+
+    let circuit_contents = std::fs::read(circuit_filename)?;
+    let matrices = Matrices::<P>::read(&circuit_contents)?;
+
+    let writer = OpenOptions::new().write(true).create_new(true).open(zkey_filename)?;
+    let mut writer = BufWriter::new(writer);
+    parameters.write_zkey(&mut writer, &matrices)?;
+    writer.flush()?;
+
+    info!(
+        "Exported the parameters with {} contributions to {}",
+        parameters.contributions.len(),
+        zkey_filename
+    );
+
+    Ok(())
+}
@@ -3,7 +3,7 @@
 #![cfg_attr(nightly, doc(include = "../README.md"))]
 
 mod new_challenge;
-pub use new_challenge::new_challenge;
+pub use new_challenge::{check_circuit_manifest, new_challenge};
 
 mod contribute;
 pub use contribute::contribute;
@@ -11,12 +11,35 @@ pub use contribute::contribute;
 mod verify;
 pub use verify::verify;
 
+mod verify_beacon;
+pub use verify_beacon::verify_beacon;
+
+mod verify_final;
+pub use verify_final::verify_final;
+
+mod streaming;
+pub use streaming::{contribute_streaming, verify_streaming};
+
 mod combine;
-pub use combine::combine;
+pub use combine::{combine, finalize};
+
+mod split;
+pub use split::split;
+
+mod export_zkey;
+pub use export_zkey::export_zkey;
+
+mod export_solidity;
+pub use export_solidity::{export_solidity, proof_calldata, solidity_verifier};
 
 mod prepare_phase2;
 pub use prepare_phase2::prepare_phase2;
 
+// the integration tests share the helper
+#[cfg(test)]
+#[path = "../tests/common/test_dir.rs"]
+mod test_dir;
+
 use setup_utils::converters::{ContributionMode, CurveKind, ProvingSystem};
 
 use gumdrop::Options;
@@ -28,7 +51,11 @@ use setup_utils::{
         proving_system_from_str,
         subgroup_check_mode_from_str,
     },
+    write_to_file,
     BatchExpMode,
+    CeremonyManifest,
+    FileHeader,
+    Result,
     SubgroupCheckMode,
     UseCompression,
 };
@@ -37,6 +64,17 @@ use std::default::Default;
 pub const COMPRESS_CONTRIBUTE_INPUT: UseCompression = UseCompression::No;
 pub const COMPRESS_CONTRIBUTE_OUTPUT: UseCompression = UseCompression::Yes;
 pub const COMBINED_IS_COMPRESSED: UseCompression = UseCompression::No;
+/// The chunked functions locate the queries from the sizes of compressed points, so the
+/// challenges and responses of the streaming mode are all compressed
+pub const STREAMING_IS_COMPRESSED: UseCompression = UseCompression::Yes;
+
+/// Writes serialized parameters to a file, after their file header if they have one
+pub(crate) fn write_parameters(filename: &str, header: Option<&FileHeader>, contents: &[u8]) -> Result<()> {
+    match header {
+        Some(header) => write_to_file(filename, &[&header.to_bytes()[..], contents].concat()),
+        None => write_to_file(filename, contents),
+    }
+}
 
 #[derive(Debug, Options, Clone)]
 pub struct Phase2Opts {
@@ -86,6 +124,32 @@ pub struct Phase2Opts {
         parse(try_from_str = "subgroup_check_mode_from_str")
     )]
     pub subgroup_check_mode: SubgroupCheckMode,
+    #[options(help = "the file to which a JSON report is written if the command fails")]
+    pub failure_report: Option<String>,
+    #[options(
+        help = "the ceremony manifest to read the curve, proving system, contribution mode, batch size and chunk size from, instead of their flags"
+    )]
+    pub manifest: Option<String>,
+}
+
+impl Phase2Opts {
+    /// Reads the manifest given with `--manifest`, if any, and replaces the settings of the
+    /// ceremony with the ones it describes. The batch size and the chunk size are taken from its
+    /// `[phase2]` section. The inputs of `new` are checked against it with
+    /// `check_circuit_manifest`.
+    pub fn apply_manifest(&mut self) -> Result<Option<CeremonyManifest>> {
+        let manifest = match &self.manifest {
+            Some(manifest_filename) => CeremonyManifest::read(manifest_filename)?,
+            None => return Ok(None),
+        };
+        let phase2 = manifest.phase2()?;
+        self.curve_kind = manifest.curve_kind()?;
+        self.proving_system = manifest.proving_system()?;
+        self.contribution_mode = manifest.contribution_mode()?;
+        self.batch_size = phase2.batch_size;
+        self.chunk_size = phase2.chunk_size;
+        Ok(Some(manifest))
+    }
 }
 
 // The supported commands
@@ -96,10 +160,26 @@ pub enum Command {
     New(NewOpts),
     #[options(help = "contribute to ceremony by producing a response to a challenge")]
     Contribute(ContributeOpts),
-    #[options(help = "verify the contributions so far and generate a new challenge, for a single chunk")]
+    #[options(help = "contribute randomness via a random beacon (e.g. a bitcoin block header hash)")]
+    Beacon(BeaconOpts),
+    #[options(help = "verify the iterated hash work of a beacon and that the beacon contribution was derived from it")]
+    VerifyBeacon(VerifyBeaconOpts),
+    #[options(
+        help = "verify the contributions so far and generate a new challenge, for a single chunk or the full parameters"
+    )]
     Verify(VerifyOpts),
-    #[options(help = "combine the contributions and verify the final parameters")]
+    #[options(
+        help = "combine the contributions and verify the final parameters, or verify the last response in the full mode"
+    )]
     Combine(CombineOpts),
+    #[options(help = "verify the combined parameters against the initial ones, without the files in between")]
+    VerifyFinal(VerifyFinalOpts),
+    #[options(help = "split full parameters into chunks, to carry on with chunked contributions")]
+    Split(SplitOpts),
+    #[options(help = "export the combined parameters to a snarkjs zkey file")]
+    ExportZkey(ExportZkeyOpts),
+    #[options(help = "export the verifying key of the combined parameters to a Solidity verifier (bn254 only)")]
+    ExportSolidity(ExportSolidityOpts),
 }
 
 // Options for the Contribute command
@@ -114,17 +194,39 @@ pub struct NewOpts {
     pub challenge_list_fname: String,
     #[options(help = "phase 1 file name", default = "phase1")]
     pub phase1_fname: String,
+    #[options(help = "start the challenges and the files derived from them with a header describing the ceremony")]
+    pub file_header: bool,
+    #[options(help = "write compressed chunks, to be contributed to with --streaming (chunked mode only)")]
+    pub streaming: bool,
     #[options(help = "number of validators")]
     pub num_validators: usize,
     #[options(help = "number of epochs")]
     pub num_epochs: usize,
-    #[options(help = "circuit file name", default = "circuit.constraints")]
+    #[options(
+        help = "circuit file name, either serialized matrices or a circom .r1cs file",
+        default = "circuit.constraints"
+    )]
     pub circuit_fname: String,
 }
 
 // Options for the Contribute command
 #[derive(Debug, Options, Clone)]
 pub struct ContributeOpts {
+    help: bool,
+    #[options(help = "the provided challenge file", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(help = "the provided challenge file hash", default = "challenge.hash")]
+    pub challenge_hash_fname: String,
+    #[options(help = "the response file which will be generated")]
+    pub response_fname: String,
+    #[options(help = "the response file which will be generated hash", default = "response.hash")]
+    pub response_hash_fname: String,
+    #[options(help = "contribute to a compressed challenge in place over memory-mapped files, in batches")]
+    pub streaming: bool,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct BeaconOpts {
     help: bool,
     #[options(help = "the provided challenge file", default = "challenge")]
     pub challenge_fname: String,
@@ -135,10 +237,44 @@ pub struct ContributeOpts {
     #[options(help = "the response file which will be generated hash", default = "response.hash")]
     pub response_hash_fname: String,
     #[options(
-        help = "the beacon hash to be used if running a beacon contribution",
+        help = "the beacon hash to be used for the beacon contribution",
         default = "0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620"
     )]
     pub beacon_hash: String,
+    #[options(
+        help = "the number of times the beacon hash is hashed, as a power of two",
+        default = "42"
+    )]
+    pub iterations_exp: u32,
+    #[options(
+        help = "the file to which the intermediate states of the beacon are written, or read from if it exists",
+        default = "beacon.checkpoints"
+    )]
+    pub checkpoints_fname: String,
+    #[options(help = "contribute to a compressed challenge in place over memory-mapped files, in batches")]
+    pub streaming: bool,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyBeaconOpts {
+    help: bool,
+    #[options(help = "the challenge file of the beacon contribution", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(help = "the response file of the beacon contribution", default = "response")]
+    pub response_fname: String,
+    #[options(help = "the intermediate states of the beacon", default = "beacon.checkpoints")]
+    pub checkpoints_fname: String,
+    #[options(help = "the challenge is compressed, as in the streaming mode")]
+    pub streaming: bool,
+    #[options(help = "the beacon hash the beacon contribution should have used", required)]
+    pub beacon_hash: String,
+    #[options(
+        help = "the number of times the beacon hash should have been hashed, as a power of two",
+        required
+    )]
+    pub iterations_exp: u32,
+    #[options(help = "accept a beacon hashed fewer than 2^40 times, e.g. in tests")]
+    pub allow_low_iterations: bool,
 }
 
 #[derive(Debug, Options, Clone)]
@@ -159,6 +295,8 @@ pub struct VerifyOpts {
     pub new_challenge_fname: String,
     #[options(help = "the new challenge file hash", default = "response.verified.hash")]
     pub new_challenge_hash_fname: String,
+    #[options(help = "verify compressed parameters over memory-mapped files, in batches")]
+    pub streaming: bool,
 }
 
 #[derive(Debug, Options, Clone)]
@@ -170,6 +308,55 @@ pub struct CombineOpts {
     pub initial_full_fname: String,
     #[options(help = "the response files which will be combined", default = "response_list")]
     pub response_list_fname: String,
+    #[options(
+        help = "the last response, which is verified instead in the full mode",
+        default = "response"
+    )]
+    pub response_fname: String,
     #[options(help = "the combined response file", default = "combined")]
     pub combined_fname: String,
 }
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyFinalOpts {
+    help: bool,
+    #[options(help = "the provided query initial file", default = "challenge.query")]
+    pub initial_query_fname: String,
+    #[options(help = "the provided full initial file", default = "challenge.full")]
+    pub initial_full_fname: String,
+    #[options(help = "the combined parameters file which will be verified", default = "combined")]
+    pub combined_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct SplitOpts {
+    help: bool,
+    #[options(help = "the full parameters which will be split", default = "challenge.full")]
+    pub full_fname: String,
+    #[options(help = "the prefix of the chunk files which will be created", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(help = "the list of chunk files", default = "new_challenge_list")]
+    pub challenge_list_fname: String,
+    #[options(help = "write compressed chunks, to be contributed to and verified with --streaming")]
+    pub streaming: bool,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct ExportZkeyOpts {
+    help: bool,
+    #[options(help = "the combined parameters file", default = "combined")]
+    pub combined_fname: String,
+    #[options(help = "circuit file name", default = "circuit.constraints")]
+    pub circuit_fname: String,
+    #[options(help = "the zkey file which will be generated", default = "combined.zkey")]
+    pub zkey_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct ExportSolidityOpts {
+    help: bool,
+    #[options(help = "the combined parameters file", default = "combined")]
+    pub combined_fname: String,
+    #[options(help = "the Solidity verifier which will be generated", default = "Verifier.sol")]
+    pub verifier_fname: String,
+}
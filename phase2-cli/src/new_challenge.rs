@@ -1,9 +1,22 @@
 use phase2::{load_circuit::Matrices, parameters::MPCParameters};
-use setup_utils::{calculate_hash, domain_size, print_hash, write_to_file, CheckForCorrectness, UseCompression};
+use setup_utils::{
+    calculate_hash,
+    converters::{ContributionMode, ProvingSystem},
+    domain_size,
+    print_hash,
+    write_to_file,
+    CeremonyManifest,
+    CheckForCorrectness,
+    Error,
+    FileHeader,
+    Result,
+    UseCompression,
+};
 
-use crate::COMPRESS_CONTRIBUTE_INPUT;
+use crate::{write_parameters, COMPRESS_CONTRIBUTE_INPUT};
 use ark_ec::pairing::Pairing;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_groth16::ProvingKey;
+use ark_serialize::CanonicalSerialize;
 use memmap::*;
 use std::{
     fs::{File, OpenOptions},
@@ -12,91 +25,207 @@ use std::{
 };
 use tracing::info;
 
+/// Creates the initial parameters and, in the chunked mode, splits them into chunks. In the full
+/// mode, the full parameters are the only challenge and no chunks or challenge list are written.
+/// If `file_header` is set, the parameters and their chunks start with a header describing them,
+/// which is carried over to the responses and the following challenges. The chunks are written
+/// with `chunk_compression`, while the full parameters and the queries are always decompressed, so
+/// the full mode can't be asked for compressed chunks. Returns the number of chunks.
+#[allow(clippy::too_many_arguments)]
 pub fn new_challenge<P: Pairing + Sync>(
     challenge_filename: &str,
     challenge_hash_filename: &str,
     challenge_list_filename: &str,
+    contribution_mode: ContributionMode,
     chunk_size: usize,
     phase2_init_filename: &str,
     circuit_filename: &str,
-) -> usize
+    file_header: bool,
+    chunk_compression: UseCompression,
+) -> Result<usize>
 where
     P::G1Affine: Neg<Output = P::G1Affine>,
 {
     info!("Generating phase 2");
 
-    let mut file = File::open(circuit_filename).unwrap();
-    let mut buffer = Vec::<u8>::new();
-    file.read_to_end(&mut buffer).unwrap();
-    let m = Matrices::<P>::deserialize_compressed(&*buffer).unwrap();
-
-    info!("Loaded circuit with {} constraints", m.num_constraints);
+    if contribution_mode == ContributionMode::Full && chunk_compression != COMPRESS_CONTRIBUTE_INPUT {
+        return Err(Error::InvalidArgument(
+            "the full parameters are always written decompressed, so they can't be contributed to in streaming mode"
+                .to_string(),
+        ));
+    }
 
-    // The FFT domain size should >= num_constraints + num_instance_variables of the *original* circuit.
-    // Since we add to the original circuits num_instance_variables constraints,
-    // we can directly use num_constraints instead.
-    let phase2_size = std::cmp::max(m.num_constraints, m.num_witness_variables + m.num_instance_variables);
-    let domain_size = domain_size::<P>(phase2_size);
+    let (m, domain_size) = read_circuit::<P>(circuit_filename)?;
 
     let chunk_size = std::cmp::min(chunk_size, domain_size);
 
-    let reader = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(&phase2_init_filename)
-        .expect("unable open phase 1 file in this directory");
-    let mut phase1_readable_map = unsafe {
-        MmapOptions::new()
-            .map_mut(&reader)
-            .expect("unable to create a memory map for input")
+    let reader = OpenOptions::new().read(true).write(true).open(phase2_init_filename)?;
+    let mut phase1_readable_map = unsafe { MmapOptions::new().map_mut(&reader)? };
+
+    let (full_mpc_parameters, query_parameters, all_mpc_parameters) = match contribution_mode {
+        ContributionMode::Full => {
+            let full_mpc_parameters = MPCParameters::<P>::new_from_buffer(
+                m,
+                &mut phase1_readable_map,
+                UseCompression::No,
+                CheckForCorrectness::No,
+                domain_size,
+            )?;
+            let query_parameters = ProvingKey::<P> {
+                h_query: vec![],
+                l_query: vec![],
+                ..full_mpc_parameters.params.clone()
+            };
+            (full_mpc_parameters, query_parameters, vec![])
+        }
+        ContributionMode::Chunked => MPCParameters::<P>::new_from_buffer_chunked(
+            m,
+            &mut phase1_readable_map,
+            UseCompression::No,
+            CheckForCorrectness::No,
+            domain_size,
+            chunk_size,
+        )?,
     };
 
-    let (full_mpc_parameters, query_parameters, all_mpc_parameters) = MPCParameters::<P>::new_from_buffer_chunked(
-        m,
-        &mut phase1_readable_map,
-        UseCompression::No,
-        CheckForCorrectness::No,
-        domain_size,
-        chunk_size,
-    )
-    .unwrap();
-
     let mut serialized_mpc_parameters = vec![];
-    full_mpc_parameters
-        .write(&mut serialized_mpc_parameters, COMPRESS_CONTRIBUTE_INPUT)
-        .unwrap();
+    full_mpc_parameters.write(&mut serialized_mpc_parameters, COMPRESS_CONTRIBUTE_INPUT)?;
 
     let mut serialized_query_parameters = vec![];
-    query_parameters
-        .serialize_with_mode(&mut serialized_query_parameters, COMPRESS_CONTRIBUTE_INPUT)
-        .unwrap();
+    query_parameters.serialize_with_mode(&mut serialized_query_parameters, COMPRESS_CONTRIBUTE_INPUT)?;
+
+    // The power of the header is the one of the FFT domain, which is a power of two
+    let power = domain_size.trailing_zeros() as usize;
+    let header = |contribution_mode, compressed, chunk_index, chunk_size| {
+        file_header.then(|| {
+            FileHeader::new::<P>(
+                ProvingSystem::Groth16,
+                contribution_mode,
+                compressed,
+                power,
+                chunk_index,
+                chunk_size,
+            )
+        })
+    };
 
     let contribution_hash = {
-        write_to_file(format!("{}.full", challenge_filename), &serialized_mpc_parameters);
+        write_parameters(
+            &format!("{}.full", challenge_filename),
+            header(ContributionMode::Full, COMPRESS_CONTRIBUTE_INPUT, 0, 0).as_ref(),
+            &serialized_mpc_parameters,
+        )?;
         // Get the hash of the contribution, so the user can compare later
         calculate_hash(&serialized_mpc_parameters)
     };
 
-    write_to_file(format!("{}.query", challenge_filename), &serialized_query_parameters);
-
-    let mut challenge_list_file =
-        std::fs::File::create(challenge_list_filename).expect("unable to open new challenge list file");
-
-    for (i, chunk) in all_mpc_parameters.iter().enumerate() {
-        let mut serialized_chunk = vec![];
-        chunk
-            .write(&mut serialized_chunk, COMPRESS_CONTRIBUTE_INPUT)
-            .expect("unable to write chunk");
-        write_to_file(format!("{}.{}", challenge_filename, i), &serialized_chunk);
-        challenge_list_file
-            .write(format!("{}.{}\n", challenge_filename, i).as_bytes())
-            .expect("unable to write challenge list");
+    write_to_file(format!("{}.query", challenge_filename), &serialized_query_parameters)?;
+
+    if contribution_mode == ContributionMode::Chunked {
+        let mut challenge_list_file = std::fs::File::create(challenge_list_filename)?;
+
+        for (i, chunk) in all_mpc_parameters.iter().enumerate() {
+            let mut serialized_chunk = vec![];
+            chunk.write(&mut serialized_chunk, chunk_compression)?;
+            write_parameters(
+                &format!("{}.{}", challenge_filename, i),
+                header(ContributionMode::Chunked, chunk_compression, i, chunk_size).as_ref(),
+                &serialized_chunk,
+            )?;
+            challenge_list_file.write_all(format!("{}.{}\n", challenge_filename, i).as_bytes())?;
+        }
     }
 
-    write_to_file(challenge_hash_filename, contribution_hash.as_slice());
+    write_to_file(challenge_hash_filename, contribution_hash.as_slice())?;
 
     info!("Empty contribution is formed with a hash:");
     print_hash(&contribution_hash);
     info!("Wrote a fresh accumulator to challenge file");
-    all_mpc_parameters.len()
+    Ok(all_mpc_parameters.len())
+}
+
+/// Checks that a circuit can be set up in the ceremony described by a manifest: the manifest must
+/// describe a Groth16 ceremony whose powers of tau cover the FFT domain of the circuit. The
+/// manifest records neither the circuit nor the Phase 1 transcript, so which ones are used is not
+/// checked.
+pub fn check_circuit_manifest<P: Pairing>(circuit_filename: &str, manifest: &CeremonyManifest) -> Result<()> {
+    if manifest.proving_system()? != ProvingSystem::Groth16 {
+        return Err(Error::InvalidManifest(format!(
+            "Phase 2 only supports Groth16, but the manifest describes a {} ceremony",
+            manifest.proving_system
+        )));
+    }
+    let (_, domain_size) = read_circuit::<P>(circuit_filename)?;
+    if domain_size > 1 << manifest.power {
+        return Err(Error::InvalidManifest(format!(
+            "the circuit needs 2^{} powers of tau, but the manifest describes a ceremony of power {}",
+            domain_size.trailing_zeros(),
+            manifest.power
+        )));
+    }
+    Ok(())
+}
+
+/// Reads the matrices of a circuit, and returns them along with the size of their FFT domain
+fn read_circuit<P: Pairing>(circuit_filename: &str) -> Result<(Matrices<P>, usize)> {
+    let mut file = File::open(circuit_filename)?;
+    let mut buffer = Vec::<u8>::new();
+    file.read_to_end(&mut buffer)?;
+    let m = Matrices::<P>::read(&buffer)?;
+
+    info!("Loaded circuit with {} constraints", m.num_constraints);
+
+    // The FFT domain size should >= num_constraints + num_instance_variables of the *original* circuit.
+    // Since we add to the original circuits num_instance_variables constraints,
+    // we can directly use num_constraints instead.
+    let phase2_size = std::cmp::max(m.num_constraints, m.num_witness_variables + m.num_instance_variables);
+    let domain_size = domain_size::<P>(phase2_size);
+    Ok((m, domain_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use phase2::{helpers::testing::TestCircuit, parameters::circuit_to_qap};
+    use setup_utils::Phase2Manifest;
+
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn test_check_circuit_manifest() {
+        let dir = TestDir::new("phase2-circuit-manifest");
+        let circuit_filename = dir.path("circuit");
+        let matrices = Matrices::<Bls12_377>::from(
+            circuit_to_qap::<Bls12_377, _>(TestCircuit::<Bls12_377>(None))
+                .unwrap()
+                .to_matrices()
+                .unwrap(),
+        );
+        let mut serialized = vec![];
+        matrices.serialize_uncompressed(&mut serialized).unwrap();
+        std::fs::write(&circuit_filename, serialized).unwrap();
+        let (_, domain_size) = read_circuit::<Bls12_377>(&circuit_filename).unwrap();
+        let power = domain_size.trailing_zeros() as usize;
+
+        let manifest = |proving_system: &str, power: usize| CeremonyManifest {
+            version: setup_utils::MANIFEST_VERSION,
+            curve_kind: "bls12_377".to_string(),
+            proving_system: proving_system.to_string(),
+            contribution_mode: "chunked".to_string(),
+            power,
+            batch_size: 64,
+            chunk_size: 512,
+            phase2: Some(Phase2Manifest {
+                batch_size: 128,
+                chunk_size: 1024,
+            }),
+        };
+        check_circuit_manifest::<Bls12_377>(&circuit_filename, &manifest("groth16", power)).unwrap();
+        check_circuit_manifest::<Bls12_377>(&circuit_filename, &manifest("groth16", power + 1)).unwrap();
+        for manifest in [manifest("groth16", power - 1), manifest("marlin", power)] {
+            let err = check_circuit_manifest::<Bls12_377>(&circuit_filename, &manifest).unwrap_err();
+            assert!(matches!(err, Error::InvalidManifest(_)), "{}", err);
+        }
+    }
 }
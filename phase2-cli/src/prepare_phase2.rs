@@ -1,5 +1,5 @@
 use phase1::{parameters::*, Phase1};
-use setup_utils::{CheckForCorrectness, Groth16Params, Result, UseCompression};
+use setup_utils::{check_file_size, CheckForCorrectness, Groth16Params, Result, UseCompression};
 use std::ops::Neg;
 
 use ark_ec::pairing::Pairing as Engine;
@@ -24,32 +24,24 @@ where
     T::G1Affine: Neg<Output = T::G1Affine>,
 {
     // Try to load response file from disk.
-    let reader = OpenOptions::new()
-        .read(true)
-        .open(response_filename)
-        .expect("unable open response file in this directory");
-    let response_readable_map = unsafe {
-        MmapOptions::new()
-            .map(&reader)
-            .expect("unable to create a memory map for input")
-    };
+    let reader = OpenOptions::new().read(true).open(response_filename)?;
+    check_file_size(&reader, response_filename, parameters.accumulator_size)?;
+    let response_readable_map = unsafe { MmapOptions::new().map(&reader)? };
 
     // Create the parameter file
     let mut writer = OpenOptions::new()
         .read(false)
         .write(true)
         .create_new(true)
-        .open(phase2_filename)
-        .expect("unable to create parameter file in this directory");
+        .open(phase2_filename)?;
 
     // Deserialize the accumulator
     let current_accumulator = Phase1::deserialize(
         &response_readable_map,
         INPUT_IS_COMPRESSED,
         check_correctness,
-        &parameters,
-    )
-    .expect("unable to read uncompressed accumulator");
+        parameters,
+    )?;
 
     // Load the elements to the Groth16 utility
     let groth16_params = Groth16Params::<T>::new(
@@ -59,8 +51,7 @@ where
         current_accumulator.alpha_tau_powers_g1,
         current_accumulator.beta_tau_powers_g1,
         current_accumulator.beta_g2,
-    )
-    .expect("could not create Groth16 Lagrange coefficients");
+    )?;
 
     // Write the parameters
     groth16_params.write(&mut writer, OUTPUT_IS_COMPRESSED)?;
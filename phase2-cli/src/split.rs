@@ -0,0 +1,158 @@
+use phase2::parameters::MPCParameters;
+use setup_utils::{
+    calculate_hash,
+    converters::ContributionMode,
+    print_hash,
+    split_file_header,
+    CheckForCorrectness,
+    FileHeader,
+    Result,
+    SubgroupCheckMode,
+    UseCompression,
+};
+
+use ark_ec::pairing::Pairing;
+
+use crate::{write_parameters, COMPRESS_CONTRIBUTE_INPUT};
+use std::{io::Write, ops::Neg};
+use tracing::info;
+
+/// Splits full parameters, e.g. the new challenge following a full contribution, into chunks of
+/// `chunk_size` elements of the H and L queries, like `new_challenge` does for the initial
+/// parameters. The chunks are written to `{challenge_filename}.{i}` with `chunk_compression` and
+/// listed in `challenge_list_filename`. If the full parameters start with a file header, so do the
+/// chunks. Returns the number of chunks.
+pub fn split<P: Pairing + Sync>(
+    full_filename: &str,
+    challenge_filename: &str,
+    challenge_list_filename: &str,
+    chunk_size: usize,
+    chunk_compression: UseCompression,
+) -> Result<usize>
+where
+    P::G1Affine: Neg<Output = P::G1Affine>,
+{
+    info!(
+        "Splitting the full phase 2 parameters into chunks of size {}",
+        chunk_size
+    );
+
+    let full_contents = std::fs::read(full_filename)?;
+    let (full_header, full_body) = split_file_header(&full_contents)?;
+    info!("`full` file has a hash:");
+    print_hash(&calculate_hash(full_body));
+
+    let full_parameters = MPCParameters::<P>::read_fast(
+        full_contents.as_slice(),
+        COMPRESS_CONTRIBUTE_INPUT,
+        CheckForCorrectness::No,
+        false,
+        SubgroupCheckMode::Auto,
+    )?;
+    let chunks = full_parameters.split(chunk_size)?;
+
+    let mut challenge_list_file = std::fs::File::create(challenge_list_filename)?;
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut serialized_chunk = vec![];
+        chunk.write(&mut serialized_chunk, chunk_compression)?;
+        let header = full_header.map(|header| {
+            FileHeader {
+                contribution_mode: ContributionMode::Chunked,
+                chunk_index: i as u64,
+                chunk_size: chunk_size as u64,
+                ..header
+            }
+            .with_compression(chunk_compression)
+        });
+        write_parameters(
+            &format!("{}.{}", challenge_filename, i),
+            header.as_ref(),
+            &serialized_chunk,
+        )?;
+        challenge_list_file.write_all(format!("{}.{}\n", challenge_filename, i).as_bytes())?;
+    }
+
+    info!(
+        "Wrote {} chunks with {} contributions so far",
+        chunks.len(),
+        full_parameters.contributions.len()
+    );
+    Ok(chunks.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use phase2::helpers::testing::TestCircuit;
+
+    use setup_utils::{converters::ProvingSystem, BatchExpMode};
+
+    use ark_bls12_377::Bls12_377;
+    use ark_groth16::{Groth16, ProvingKey};
+    use ark_snark::SNARK;
+    use rand::thread_rng;
+
+    use std::fs;
+
+    #[test]
+    fn test_split() {
+        let dir = TestDir::new("phase2-split");
+
+        let rng = &mut thread_rng();
+        let (params, _) = Groth16::<Bls12_377>::circuit_specific_setup(TestCircuit::<Bls12_377>(None), rng).unwrap();
+        let mut full = MPCParameters {
+            params,
+            cs_hash: [0; 64],
+            contributions: vec![],
+        };
+        full.contribute(BatchExpMode::Auto, rng).unwrap();
+        let header = FileHeader::new::<Bls12_377>(
+            ProvingSystem::Groth16,
+            ContributionMode::Full,
+            COMPRESS_CONTRIBUTE_INPUT,
+            3,
+            0,
+            0,
+        );
+        let mut serialized = vec![];
+        full.write(&mut serialized, COMPRESS_CONTRIBUTE_INPUT).unwrap();
+        write_parameters(&dir.path("full"), Some(&header), &serialized).unwrap();
+
+        let num_chunks = split::<Bls12_377>(
+            &dir.path("full"),
+            &dir.path("challenge"),
+            &dir.path("challenge_list"),
+            2,
+            COMPRESS_CONTRIBUTE_INPUT,
+        )
+        .unwrap();
+        let challenge_list = fs::read_to_string(dir.path("challenge_list")).unwrap();
+        assert_eq!(challenge_list.lines().count(), num_chunks);
+
+        let mut chunks = vec![];
+        for (i, chunk_filename) in challenge_list.lines().enumerate() {
+            let contents = fs::read(chunk_filename).unwrap();
+            let (chunk_header, _) = split_file_header(&contents).unwrap();
+            let chunk_header = chunk_header.unwrap();
+            assert_eq!(chunk_header.contribution_mode, ContributionMode::Chunked);
+            assert_eq!((chunk_header.chunk_index, chunk_header.chunk_size), (i as u64, 2));
+            chunks.push(
+                MPCParameters::<Bls12_377>::read_fast(
+                    contents.as_slice(),
+                    COMPRESS_CONTRIBUTE_INPUT,
+                    CheckForCorrectness::Full,
+                    false,
+                    SubgroupCheckMode::Auto,
+                )
+                .unwrap(),
+            );
+        }
+        let queries = ProvingKey {
+            h_query: vec![],
+            l_query: vec![],
+            ..full.params.clone()
+        };
+        assert_eq!(MPCParameters::combine(&queries, &chunks).unwrap(), full);
+    }
+}
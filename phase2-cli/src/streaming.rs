@@ -0,0 +1,157 @@
+use phase2::{chunked_groth16, keypair::PublicKey};
+use setup_utils::{
+    calculate_hash,
+    header_length,
+    print_hash,
+    write_to_file,
+    BatchExpMode,
+    CheckForCorrectness,
+    FileHeader,
+    Result,
+    SubgroupCheckMode,
+};
+
+use ark_ec::pairing::Pairing;
+
+use crate::STREAMING_IS_COMPRESSED;
+use memmap::{MmapMut, MmapOptions};
+use rand::Rng;
+use std::fs::{File, OpenOptions};
+use tracing::info;
+
+/// Contributes to a compressed challenge without loading it in memory. The challenge is copied to
+/// the response file, whose memory map is then updated in place by
+/// `chunked_groth16::contribute` in `batch_size` batches. The response keeps the header of the
+/// challenge, if it has one.
+#[allow(clippy::too_many_arguments)]
+pub fn contribute_streaming<P: Pairing + Sync>(
+    challenge_filename: &str,
+    challenge_hash_filename: &str,
+    response_filename: &str,
+    response_hash_filename: &str,
+    batch_size: usize,
+    check_input_correctness: CheckForCorrectness,
+    batch_exp_mode: BatchExpMode,
+    mut rng: impl Rng,
+) -> Result<()> {
+    info!("Contributing to phase 2 in streaming mode");
+
+    let (challenge_map, challenge_header) = map_parameters::<P>(challenge_filename)?;
+    let offset = header_length(challenge_header.as_ref());
+    let challenge_hash = calculate_hash(&challenge_map[offset..]);
+    write_to_file(challenge_hash_filename, &challenge_hash)?;
+
+    info!("`challenge` file contains compressed points and has a hash:");
+    print_hash(&challenge_hash);
+
+    // The contribution is made in place and appends the contributor's public key
+    let response_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(response_filename)?;
+    response_file.set_len((challenge_map.len() + PublicKey::<P>::size()) as u64)?;
+    let mut response_map = unsafe { MmapOptions::new().map_mut(&response_file)? };
+    response_map[..challenge_map.len()].copy_from_slice(&challenge_map);
+    drop(challenge_map);
+
+    chunked_groth16::contribute::<P, _>(
+        &mut response_map[offset..],
+        &mut rng,
+        batch_size,
+        STREAMING_IS_COMPRESSED,
+        check_input_correctness,
+        batch_exp_mode,
+    )?;
+    response_map.flush()?;
+
+    let response_hash = calculate_hash(&response_map[offset..]);
+    write_to_file(response_hash_filename, &response_hash)?;
+    info!(
+        "Done!\n\n\
+              Your contribution has been written to response file\n\n\
+              The BLAKE2b hash of response file is:\n"
+    );
+    print_hash(&response_hash);
+
+    Ok(())
+}
+
+/// Verifies the response to a compressed challenge without loading either in memory, with
+/// `chunked_groth16::verify` over their memory maps in `batch_size` batches. Since the response is
+/// compressed too, it is copied as it is to the new challenge once verified. If both files start
+/// with a header, they must describe the same parameters. Unless `check_correctness` is `Full`,
+/// the points of the response are checked to be in the prime order subgroup with
+/// `subgroup_check_mode`.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_streaming<P: Pairing>(
+    challenge_filename: &str,
+    challenge_hash_filename: &str,
+    response_filename: &str,
+    response_hash_filename: &str,
+    new_challenge_filename: &str,
+    new_challenge_hash_filename: &str,
+    batch_size: usize,
+    check_correctness: CheckForCorrectness,
+    subgroup_check_mode: SubgroupCheckMode,
+) -> Result<()> {
+    info!("Verifying phase 2 in streaming mode");
+
+    let (mut challenge_map, challenge_header) = map_parameters::<P>(challenge_filename)?;
+    let challenge_body = &mut challenge_map[header_length(challenge_header.as_ref())..];
+    let challenge_hash = calculate_hash(challenge_body);
+    write_to_file(challenge_hash_filename, &challenge_hash)?;
+
+    info!("`challenge` file contains compressed points and has a hash:");
+    print_hash(&challenge_hash);
+
+    let (mut response_map, response_header) = map_parameters::<P>(response_filename)?;
+    if let (Some(challenge_header), Some(response_header)) = (&challenge_header, &response_header) {
+        response_header.check(challenge_header)?;
+    }
+    let response_body = &mut response_map[header_length(response_header.as_ref())..];
+    let response_hash = calculate_hash(response_body);
+    write_to_file(response_hash_filename, &response_hash)?;
+
+    info!("`response` file contains compressed points and has a hash:");
+    print_hash(&response_hash);
+
+    if let Err(e) = chunked_groth16::verify::<P>(
+        challenge_body,
+        response_body,
+        batch_size,
+        STREAMING_IS_COMPRESSED,
+        check_correctness,
+        subgroup_check_mode,
+    ) {
+        info!("Verification failed: {}", e);
+        return Err(e);
+    }
+
+    let mut new_challenge = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(new_challenge_filename)?;
+    std::io::copy(&mut File::open(response_filename)?, &mut new_challenge)?;
+    write_to_file(new_challenge_hash_filename, &response_hash)?;
+
+    info!(
+        "Done!\n\n\
+              The BLAKE2b hash of response file is:\n"
+    );
+    print_hash(&response_hash);
+
+    Ok(())
+}
+
+/// Maps a file copy-on-write, so that the chunked functions can be given mutable buffers while
+/// the file is left untouched, and reads its header if it has one
+fn map_parameters<P: Pairing>(filename: &str) -> Result<(MmapMut, Option<FileHeader>)> {
+    let file = File::open(filename)?;
+    let map = unsafe { MmapOptions::new().map_copy(&file)? };
+    let header = FileHeader::from_bytes(&map)?;
+    if let Some(header) = &header {
+        header.check_curve_and_compression::<P>(STREAMING_IS_COMPRESSED)?;
+    }
+    Ok((map, header))
+}
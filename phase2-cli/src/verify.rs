@@ -1,13 +1,24 @@
 use phase2::parameters::MPCParameters;
-use setup_utils::{calculate_hash, print_hash, write_to_file, CheckForCorrectness, SubgroupCheckMode};
+use setup_utils::{
+    calculate_hash,
+    print_hash,
+    split_file_header,
+    write_to_file,
+    CheckForCorrectness,
+    Result,
+    SubgroupCheckMode,
+};
 
 use ark_ec::pairing::Pairing;
 
 use crate::{COMBINED_IS_COMPRESSED, COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT};
 use memmap::MmapOptions;
-use std::{fs::OpenOptions, ops::Neg};
+use std::{fs::OpenOptions, io::Write, ops::Neg};
 use tracing::info;
 
+/// Verifies the response to a challenge and writes the new challenge. If the challenge starts with
+/// a file header, the response must describe the same parameters if it has one, and the new
+/// challenge gets one too.
 pub fn verify<P: Pairing + Sync>(
     challenge_filename: &str,
     challenge_hash_filename: &str,
@@ -19,14 +30,16 @@ pub fn verify<P: Pairing + Sync>(
     new_challenge_hash_filename: &str,
     subgroup_check_mode: SubgroupCheckMode,
     verifying_full_contribution: bool,
-) where
+) -> Result<()>
+where
     P::G1Affine: Neg<Output = P::G1Affine>,
 {
     info!("Verifying phase 2");
 
-    let challenge_contents = std::fs::read(challenge_filename).expect("should have read challenge");
-    let challenge_hash = calculate_hash(&challenge_contents);
-    write_to_file(challenge_hash_filename, &challenge_hash);
+    let challenge_contents = std::fs::read(challenge_filename)?;
+    let (challenge_header, challenge_body) = split_file_header(&challenge_contents)?;
+    let challenge_hash = calculate_hash(challenge_body);
+    write_to_file(challenge_hash_filename, &challenge_hash)?;
 
     info!("`challenge` file contains decompressed points and has a hash:");
     print_hash(&challenge_hash);
@@ -37,12 +50,12 @@ pub fn verify<P: Pairing + Sync>(
         check_input_correctness,
         true,
         subgroup_check_mode,
-    )
-    .expect("should have read parameters");
+    )?;
 
-    let response_contents = std::fs::read(response_filename).expect("should have read response");
-    let response_hash = calculate_hash(&response_contents);
-    write_to_file(response_hash_filename, &response_hash);
+    let response_contents = std::fs::read(response_filename)?;
+    let (response_header, response_body) = split_file_header(&response_contents)?;
+    let response_hash = calculate_hash(response_body);
+    write_to_file(response_hash_filename, &response_hash)?;
 
     info!("`response` file contains decompressed points and has a hash:");
     print_hash(&response_hash);
@@ -52,45 +65,44 @@ pub fn verify<P: Pairing + Sync>(
     } else {
         COMPRESS_CONTRIBUTE_OUTPUT
     };
+    if let (Some(challenge_header), Some(response_header)) = (&challenge_header, &response_header) {
+        response_header.check(&challenge_header.with_compression(after_compressed))?;
+    }
     let parameters_after = MPCParameters::<P>::read_fast(
         response_contents.as_slice(),
         after_compressed,
         check_output_correctness,
         true,
         subgroup_check_mode,
-    )
-    .expect("should have read parameters");
+    )?;
 
-    let writer = OpenOptions::new()
+    let mut writer = OpenOptions::new()
         .read(true)
         .write(true)
         .create_new(true)
-        .open(new_challenge_filename)
-        .expect("unable to create new challenge file in this directory");
-    parameters_after
-        .write(writer, COMPRESS_CONTRIBUTE_INPUT)
-        .expect("unable to write new challenge file");
+        .open(new_challenge_filename)?;
+    if let Some(challenge_header) = &challenge_header {
+        writer.write_all(&challenge_header.with_compression(COMPRESS_CONTRIBUTE_INPUT).to_bytes())?;
+    }
+    parameters_after.write(writer, COMPRESS_CONTRIBUTE_INPUT)?;
 
     // Read new challenge to create hash
-    let new_challenge_reader = OpenOptions::new()
-        .read(true)
-        .open(new_challenge_filename)
-        .expect("unable open challenge file in this directory");
-    let new_challenge_readable_map = unsafe {
-        MmapOptions::new()
-            .map(&new_challenge_reader)
-            .expect("unable to create a memory map for input")
-    };
+    let new_challenge_reader = OpenOptions::new().read(true).open(new_challenge_filename)?;
+    let new_challenge_readable_map = unsafe { MmapOptions::new().map(&new_challenge_reader)? };
 
-    let new_challenge_hash = calculate_hash(&new_challenge_readable_map);
-    write_to_file(new_challenge_hash_filename, new_challenge_hash.as_slice());
+    let (_, new_challenge_body) = split_file_header(&new_challenge_readable_map)?;
+    let new_challenge_hash = calculate_hash(new_challenge_body);
+    write_to_file(new_challenge_hash_filename, new_challenge_hash.as_slice())?;
 
-    parameters_before
-        .verify(&parameters_after)
-        .expect("should have successfully verified");
+    if let Err(e) = parameters_before.verify(&parameters_after) {
+        info!("Verification failed: {}", e);
+        return Err(e);
+    }
     info!(
         "Done!\n\n\
               The BLAKE2b hash of response file is:\n"
     );
     print_hash(&response_hash);
+
+    Ok(())
 }
@@ -0,0 +1,187 @@
+use phase2::{keypair::Keypair, parameters::MPCParameters};
+use setup_utils::{
+    derive_rng_from_seed,
+    split_file_header,
+    BeaconCheckpoints,
+    CheckForCorrectness,
+    Error,
+    Result,
+    SubgroupCheckMode,
+    UseCompression,
+};
+
+use ark_ec::pairing::Pairing;
+
+use crate::COMPRESS_CONTRIBUTE_OUTPUT;
+use std::ops::Neg;
+use tracing::info;
+
+/// Verifies a beacon contribution: the checkpoints must be the ones of the expected beacon and
+/// number of iterations, their intermediate states are re-checked in parallel, and the public key
+/// the response adds to the challenge must be the one derived from the final state. The challenge
+/// is read with `challenge_compressed`, which depends on whether the contribution was made in the
+/// streaming mode. The contribution itself is verified by `verify`.
+pub fn verify_beacon<P: Pairing + Sync>(
+    challenge_filename: &str,
+    response_filename: &str,
+    checkpoints_filename: &str,
+    beacon_hash: [u8; 32],
+    iterations_exp: u32,
+    allow_low_iterations: bool,
+    challenge_compressed: UseCompression,
+) -> Result<()>
+where
+    P::G1Affine: Neg<Output = P::G1Affine>,
+{
+    info!("Verifying the phase 2 beacon contribution");
+
+    let checkpoints = BeaconCheckpoints::read(checkpoints_filename)?;
+    info!(
+        "Verifying the {} checkpoints of 2^{} iterations over the beacon hash {}",
+        checkpoints.checkpoints.len(),
+        checkpoints.iterations_exp,
+        hex::encode(checkpoints.beacon_hash)
+    );
+    checkpoints.check_beacon(beacon_hash, iterations_exp, allow_low_iterations)?;
+    checkpoints.verify()?;
+
+    let challenge_contents = std::fs::read(challenge_filename)?;
+    let (challenge_header, _) = split_file_header(&challenge_contents)?;
+    let before = MPCParameters::<P>::read_fast(
+        challenge_contents.as_slice(),
+        challenge_compressed,
+        CheckForCorrectness::No,
+        false,
+        SubgroupCheckMode::Auto,
+    )?;
+
+    let response_contents = std::fs::read(response_filename)?;
+    let (response_header, _) = split_file_header(&response_contents)?;
+    if let (Some(challenge_header), Some(response_header)) = (&challenge_header, &response_header) {
+        response_header.check(&challenge_header.with_compression(COMPRESS_CONTRIBUTE_OUTPUT))?;
+    }
+    let after = MPCParameters::<P>::read_fast(
+        response_contents.as_slice(),
+        COMPRESS_CONTRIBUTE_OUTPUT,
+        CheckForCorrectness::No,
+        false,
+        SubgroupCheckMode::Auto,
+    )?;
+
+    let public_key = match after.contributions.split_last() {
+        Some((public_key, previous)) if previous == &before.contributions[..] => public_key,
+        _ => {
+            return Err(Error::InvalidBeacon(format!(
+                "{} does not add a single contribution to {}",
+                response_filename, challenge_filename
+            )))
+        }
+    };
+
+    let mut rng = derive_rng_from_seed(&checkpoints.result);
+    let expected = Keypair::new(before.params.delta_g1, before.cs_hash, &before.contributions, &mut rng);
+    if *public_key != expected.public_key {
+        return Err(Error::InvalidBeacon(format!(
+            "the public key of {} was not derived from the beacon",
+            response_filename
+        )));
+    }
+
+    info!(
+        "The beacon contribution was derived from the beacon result {}",
+        hex::encode(checkpoints.result)
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, test_dir::TestDir, write_parameters, COMPRESS_CONTRIBUTE_INPUT};
+    use phase2::helpers::testing::TestCircuit;
+    use setup_utils::{beacon_hash_from_str, BatchExpMode};
+
+    use ark_bls12_377::Bls12_377;
+    use ark_groth16::Groth16;
+    use ark_snark::SNARK;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_verify_beacon() {
+        let dir = TestDir::new("phase2-verify-beacon");
+
+        let (params, _) =
+            Groth16::<Bls12_377>::circuit_specific_setup(TestCircuit::<Bls12_377>(None), &mut thread_rng()).unwrap();
+        let mut challenge = MPCParameters {
+            params,
+            cs_hash: [0; 64],
+            contributions: vec![],
+        };
+        challenge.contribute(BatchExpMode::Auto, &mut thread_rng()).unwrap();
+        let mut serialized = vec![];
+        challenge.write(&mut serialized, COMPRESS_CONTRIBUTE_INPUT).unwrap();
+        write_parameters(&dir.path("challenge"), None, &serialized).unwrap();
+
+        let beacon_hash =
+            beacon_hash_from_str("0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620").unwrap();
+        let checkpoints = BeaconCheckpoints::read_or_compute(dir.path("beacon.checkpoints"), beacon_hash, 4).unwrap();
+        let beacon_contribution = |response: &str, seed: &[u8]| {
+            contribute::<Bls12_377>(
+                &dir.path("challenge"),
+                &dir.path("challenge.hash"),
+                &dir.path(response),
+                &dir.path(&format!("{}.hash", response)),
+                CheckForCorrectness::Full,
+                BatchExpMode::Auto,
+                derive_rng_from_seed(seed),
+            )
+            .unwrap()
+        };
+        let verify_with = |response: &str, iterations_exp: u32, allow_low_iterations: bool| {
+            verify_beacon::<Bls12_377>(
+                &dir.path("challenge"),
+                &dir.path(response),
+                &dir.path("beacon.checkpoints"),
+                beacon_hash,
+                iterations_exp,
+                allow_low_iterations,
+                COMPRESS_CONTRIBUTE_INPUT,
+            )
+        };
+        let verify = |response: &str| verify_with(response, 4, true);
+
+        beacon_contribution("response", &checkpoints.result);
+        verify("response").unwrap();
+
+        // so few iterations are only accepted when asked for
+        let err = verify_with("response", 4, false).unwrap_err();
+        assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+
+        // checkpoints which follow from each other, but for another beacon or fewer iterations
+        let mut other_beacon = beacon_hash;
+        other_beacon[31] ^= 1;
+        for (response, other_hash, other_exp) in
+            [("other_beacon", other_beacon, 4), ("fewer_iterations", beacon_hash, 3)]
+        {
+            let other = BeaconCheckpoints::compute(other_hash, other_exp).unwrap();
+            other.write(dir.path("beacon.checkpoints")).unwrap();
+            beacon_contribution(response, &other.result);
+            let err = verify(response).unwrap_err();
+            assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+        }
+        checkpoints.write(dir.path("beacon.checkpoints")).unwrap();
+
+        // a contribution which wasn't seeded by the beacon
+        beacon_contribution("other_response", b"not the beacon");
+        let err = verify("other_response").unwrap_err();
+        assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+
+        // checkpoints which don't follow from each other
+        let mut tampered = checkpoints;
+        tampered.result[0] ^= 1;
+        tampered.write(dir.path("beacon.checkpoints")).unwrap();
+        let err = verify("response").unwrap_err();
+        assert_eq!(err.exit_code(), Error::EXIT_CODE_VERIFICATION);
+    }
+}
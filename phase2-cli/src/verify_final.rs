@@ -0,0 +1,80 @@
+use phase2::parameters::MPCParameters;
+use setup_utils::{calculate_hash, print_hash, split_file_header, CheckForCorrectness, Result, SubgroupCheckMode};
+
+use ark_ec::pairing::Pairing;
+
+use crate::{COMBINED_IS_COMPRESSED, COMPRESS_CONTRIBUTE_INPUT};
+use std::ops::Neg;
+use tracing::info;
+
+/// Verifies the combined parameters at the end of a ceremony against the initial full parameters
+/// and queries, without any of the challenges or responses in between. If the initial full
+/// parameters start with a file header, the combined parameters must describe the same parameters
+/// if they have one.
+pub fn verify_final<P: Pairing + Sync>(
+    initial_query_filename: &str,
+    initial_full_filename: &str,
+    combined_filename: &str,
+    check_input_correctness: CheckForCorrectness,
+    check_output_correctness: CheckForCorrectness,
+    subgroup_check_mode: SubgroupCheckMode,
+) -> Result<()>
+where
+    P::G1Affine: Neg<Output = P::G1Affine>,
+{
+    info!("Verifying the final phase 2 parameters");
+
+    let full_contents = std::fs::read(initial_full_filename)?;
+    let (full_header, _) = split_file_header(&full_contents)?;
+    let initial_parameters = MPCParameters::<P>::read_fast(
+        full_contents.as_slice(),
+        COMPRESS_CONTRIBUTE_INPUT,
+        check_input_correctness,
+        true,
+        subgroup_check_mode,
+    )?;
+
+    let mut query_contents = std::io::Cursor::new(std::fs::read(initial_query_filename)?);
+    let query_parameters = MPCParameters::<P>::read_groth16_fast(
+        &mut query_contents,
+        COMPRESS_CONTRIBUTE_INPUT,
+        check_input_correctness,
+        true,
+        subgroup_check_mode,
+    )?;
+
+    let combined_contents = std::fs::read(combined_filename)?;
+    let (combined_header, combined_body) = split_file_header(&combined_contents)?;
+    if let (Some(full_header), Some(combined_header)) = (&full_header, &combined_header) {
+        combined_header.check(&full_header.with_compression(COMBINED_IS_COMPRESSED))?;
+    }
+    info!("`combined` file has a hash:");
+    print_hash(&calculate_hash(combined_body));
+
+    let final_parameters = MPCParameters::<P>::read_fast(
+        combined_contents.as_slice(),
+        COMBINED_IS_COMPRESSED,
+        check_output_correctness,
+        true,
+        subgroup_check_mode,
+    )?;
+
+    let contributions_hash = match initial_parameters.verify_final(&query_parameters, &final_parameters) {
+        Ok(contributions_hash) => contributions_hash,
+        Err(e) => {
+            info!("Verification failed: {}", e);
+            return Err(e);
+        }
+    };
+
+    info!("Contributions hashes:");
+    for contribution_hash in &contributions_hash {
+        print_hash(&contribution_hash[..]);
+    }
+    info!(
+        "Verified the {} contributions to the final parameters!",
+        contributions_hash.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use ark_ec::{pairing::Pairing, scalar_mul::fixed_base::FixedBase, CurveGroup, Group};
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_serialize::CanonicalSerialize;
+use phase2::{load_circuit::Matrices, parameters::circuit_to_qap};
+use setup_utils::{derive_rng_from_seed, domain_size, Groth16Params, UseCompression};
+
+use std::{fs, ops::Neg};
+
+mod test_dir;
+pub use test_dir::TestDir;
+
+/// Writes the matrices of the circuit and a phase 1 transcript prepared for them, as the inputs of
+/// `new_challenge`, and returns the size of their FFT domain
+pub fn write_circuit_and_transcript<E: Pairing, C: ConstraintSynthesizer<E::ScalarField>>(
+    circuit: C,
+    circuit_filename: &str,
+    phase1_filename: &str,
+) -> usize
+where
+    E::G1Affine: Neg<Output = E::G1Affine>,
+{
+    let matrices = Matrices::<E>::from(circuit_to_qap::<E, _>(circuit).unwrap().to_matrices().unwrap());
+    let phase2_size = std::cmp::max(
+        matrices.num_constraints,
+        matrices.num_witness_variables + matrices.num_instance_variables,
+    );
+    let mut serialized_matrices = vec![];
+    matrices.serialize_uncompressed(&mut serialized_matrices).unwrap();
+    fs::write(circuit_filename, serialized_matrices).unwrap();
+
+    // prepare the phase 2 transcript
+    let domain_size = domain_size::<E>(phase2_size);
+    let groth_params = prepared_transcript::<E>(domain_size);
+    let mut transcript = vec![];
+    groth_params.write(&mut transcript, UseCompression::No).unwrap();
+    fs::write(phase1_filename, transcript).unwrap();
+
+    domain_size
+}
+
+/// Computes the phase 1 transcript prepared for phase 2 for a random tau, alpha and beta, straight
+/// from the Lagrange coefficients at tau rather than from the powers of tau
+fn prepared_transcript<E: Pairing>(domain_size: usize) -> Groth16Params<E> {
+    let rng = &mut derive_rng_from_seed(b"prepared transcript");
+    let (tau, alpha, beta) = (
+        E::ScalarField::rand(rng),
+        E::ScalarField::rand(rng),
+        E::ScalarField::rand(rng),
+    );
+    let domain = GeneralEvaluationDomain::<E::ScalarField>::new(domain_size).unwrap();
+    let coeffs = domain.evaluate_all_lagrange_coefficients(tau);
+    let scaled = |factor: E::ScalarField| coeffs.iter().map(|coeff| *coeff * factor).collect::<Vec<_>>();
+    // tau^i * (tau^m - 1) for i in 0..m-1
+    let vanishing = tau.pow([domain_size as u64]) - E::ScalarField::ONE;
+    let h = std::iter::successors(Some(vanishing), |power| Some(*power * tau))
+        .take(domain_size - 1)
+        .collect::<Vec<_>>();
+
+    let scalar_size = E::ScalarField::MODULUS_BIT_SIZE as usize;
+    let window = FixedBase::get_mul_window_size(domain_size);
+    let g1_table = FixedBase::get_window_table(scalar_size, window, E::G1::generator());
+    let g2_table = FixedBase::get_window_table(scalar_size, window, E::G2::generator());
+    let g1_msm = |scalars: &[E::ScalarField]| {
+        E::G1::normalize_batch(&FixedBase::msm::<E::G1>(scalar_size, window, &g1_table, scalars))
+    };
+
+    Groth16Params {
+        alpha_g1: (E::G1::generator() * alpha).into_affine(),
+        beta_g1: (E::G1::generator() * beta).into_affine(),
+        beta_g2: (E::G2::generator() * beta).into_affine(),
+        coeffs_g1: g1_msm(&coeffs),
+        coeffs_g2: E::G2::normalize_batch(&FixedBase::msm::<E::G2>(scalar_size, window, &g2_table, &coeffs)),
+        alpha_coeffs_g1: g1_msm(&scaled(alpha)),
+        beta_coeffs_g1: g1_msm(&scaled(beta)),
+        h_g1: g1_msm(&h),
+    }
+}
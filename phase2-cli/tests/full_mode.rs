@@ -0,0 +1,110 @@
+use std::fs;
+
+use ark_bls12_377::Bls12_377;
+use phase2::helpers::testing::TestCircuit;
+use phase2_cli::{
+    contribute,
+    finalize,
+    new_challenge,
+    verify,
+    verify_final,
+    COMPRESS_CONTRIBUTE_INPUT,
+    STREAMING_IS_COMPRESSED,
+};
+use setup_utils::{
+    converters::ContributionMode,
+    derive_rng_from_seed,
+    split_file_header,
+    BatchExpMode,
+    CheckForCorrectness,
+    Error,
+    SubgroupCheckMode,
+};
+
+mod common;
+use common::{write_circuit_and_transcript, TestDir};
+
+#[test]
+fn full_mode_ceremony() {
+    let dir = TestDir::new("phase2-full-mode");
+
+    let domain_size = write_circuit_and_transcript::<Bls12_377, _>(
+        TestCircuit::<Bls12_377>(None),
+        &dir.path("circuit"),
+        &dir.path("phase1"),
+    );
+
+    // the full parameters are the only challenge, and they can't be compressed for the streaming mode
+    let new_full_challenge = |chunk_compression| {
+        new_challenge::<Bls12_377>(
+            &dir.path("challenge"),
+            &dir.path("challenge.hash"),
+            &dir.path("challenge_list"),
+            ContributionMode::Full,
+            0,
+            &dir.path("phase1"),
+            &dir.path("circuit"),
+            true,
+            chunk_compression,
+        )
+    };
+    let err = new_full_challenge(STREAMING_IS_COMPRESSED).unwrap_err();
+    assert!(matches!(err, Error::InvalidArgument(_)), "{}", err);
+    assert!(!dir.join("challenge.full").exists());
+    let num_chunks = new_full_challenge(COMPRESS_CONTRIBUTE_INPUT).unwrap();
+    assert_eq!(num_chunks, 0);
+    assert!(!dir.join("challenge.0").exists());
+    assert!(!dir.join("challenge_list").exists());
+    let contents = fs::read(dir.path("challenge.full")).unwrap();
+    let header = split_file_header(&contents).unwrap().0.unwrap();
+    assert_eq!(header.contribution_mode, ContributionMode::Full);
+    assert_eq!(1 << header.power, domain_size);
+
+    // two rounds of contributions straight to the full parameters
+    let mut challenge = "challenge.full".to_string();
+    for (round, seed) in [&b"alice"[..], &b"bob"[..]].iter().enumerate() {
+        let response = format!("response.{}", round);
+        contribute::<Bls12_377>(
+            &dir.path(&challenge),
+            &dir.path(&format!("{}.hash", challenge)),
+            &dir.path(&response),
+            &dir.path(&format!("{}.hash", response)),
+            CheckForCorrectness::Full,
+            BatchExpMode::Auto,
+            derive_rng_from_seed(seed),
+        )
+        .unwrap();
+        let new_challenge = format!("new_challenge.{}", round);
+        verify::<Bls12_377>(
+            &dir.path(&challenge),
+            &dir.path(&format!("{}.verified.hash", challenge)),
+            CheckForCorrectness::Full,
+            &dir.path(&response),
+            &dir.path(&format!("{}.verified.hash", response)),
+            CheckForCorrectness::Full,
+            &dir.path(&new_challenge),
+            &dir.path(&format!("{}.hash", new_challenge)),
+            SubgroupCheckMode::Auto,
+            false,
+        )
+        .unwrap();
+        challenge = new_challenge;
+    }
+
+    // the last response is finalized instead of combined
+    finalize::<Bls12_377>(
+        &dir.path("challenge.full"),
+        &dir.path("response.1"),
+        &dir.path("combined"),
+    )
+    .unwrap();
+    verify_final::<Bls12_377>(
+        &dir.path("challenge.query"),
+        &dir.path("challenge.full"),
+        &dir.path("combined"),
+        CheckForCorrectness::Full,
+        CheckForCorrectness::Full,
+        SubgroupCheckMode::Auto,
+    )
+    .unwrap();
+}
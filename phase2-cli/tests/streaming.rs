@@ -0,0 +1,185 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    mem::size_of,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use ark_bls12_377::{Bls12_377, Fr};
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use phase2::parameters::MPCParameters;
+use phase2_cli::{contribute_streaming, new_challenge, verify_streaming, STREAMING_IS_COMPRESSED};
+use setup_utils::{
+    converters::ContributionMode,
+    derive_rng_from_seed,
+    BatchExpMode,
+    CheckForCorrectness,
+    SubgroupCheckMode,
+};
+
+mod common;
+use common::{write_circuit_and_transcript, TestDir};
+
+const BATCH_SIZE: usize = 256;
+
+/// The heap a streaming step may use besides its batches, for the verifying key, the hashers, the
+/// buffers of the thread pool and such, which don't depend on the size of the parameters
+const FIXED_ALLOWANCE: usize = 1 << 19;
+
+/// Squares a witness the given number of times, with one constraint per squaring, so that the size of the
+/// parameters can be chosen. `TestHashCircuit` makes parameters of 5 MB, whose contributions take the test
+/// over half an hour in a debug build on a single core, for the same peaks of the streaming steps.
+struct SquaringChain(usize);
+
+impl ConstraintSynthesizer<Fr> for SquaringChain {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> std::result::Result<(), SynthesisError> {
+        let mut value = Fr::from(2u64);
+        let mut x = cs.new_witness_variable(|| Ok(value))?;
+        for _ in 0..self.0 {
+            value.square_in_place();
+            let y = cs.new_witness_variable(|| Ok(value))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)?;
+            x = y;
+        }
+        Ok(())
+    }
+}
+
+/// Counts the bytes allocated on the heap, and the most allocated at once since the last
+/// `peak_heap_growth_of`. Memory-mapped files aren't allocated, so the parameters the streaming
+/// steps map don't count, unlike the copies they would make of them.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Runs the step and returns how much more was allocated at its peak than before it
+fn peak_heap_growth_of(step: impl FnOnce()) -> usize {
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    step();
+    PEAK.load(Ordering::SeqCst) - before
+}
+
+#[test]
+fn streaming_contribution_has_bounded_memory() {
+    let dir = TestDir::new("phase2-streaming");
+
+    // parameters of several hundred batches
+    let domain_size =
+        write_circuit_and_transcript::<Bls12_377, _>(SquaringChain(1 << 12), &dir.path("circuit"), &dir.path("phase1"));
+
+    // a single chunk holds the whole parameters
+    let num_chunks = new_challenge::<Bls12_377>(
+        &dir.path("challenge"),
+        &dir.path("challenge.hash"),
+        &dir.path("challenge_list"),
+        ContributionMode::Chunked,
+        domain_size,
+        &dir.path("phase1"),
+        &dir.path("circuit"),
+        true,
+        STREAMING_IS_COMPRESSED,
+    )
+    .unwrap();
+    assert_eq!(num_chunks, 1);
+    let challenge_size = fs::metadata(dir.path("challenge.0")).unwrap().len() as usize;
+
+    // a batch is read, then updated in projective form, of which a few copies may be alive at once
+    let batch_size = BATCH_SIZE * size_of::<<Bls12_377 as Pairing>::G1>();
+    let ceiling = 4 * batch_size + FIXED_ALLOWANCE;
+
+    let contribution_peak = peak_heap_growth_of(|| {
+        contribute_streaming::<Bls12_377>(
+            &dir.path("challenge.0"),
+            &dir.path("challenge.0.hash"),
+            &dir.path("response"),
+            &dir.path("response.hash"),
+            BATCH_SIZE,
+            CheckForCorrectness::Full,
+            BatchExpMode::Auto,
+            derive_rng_from_seed(b"alice"),
+        )
+        .unwrap()
+    });
+    let verification_peak = peak_heap_growth_of(|| {
+        verify_streaming::<Bls12_377>(
+            &dir.path("challenge.0"),
+            &dir.path("challenge.0.verified.hash"),
+            &dir.path("response"),
+            &dir.path("response.verified.hash"),
+            &dir.path("new_challenge"),
+            &dir.path("new_challenge.hash"),
+            BATCH_SIZE,
+            CheckForCorrectness::No,
+            SubgroupCheckMode::Auto,
+        )
+        .unwrap()
+    });
+    // the same contribution with the parameters in memory, which the ceiling must rule out
+    let read = |name: &str| {
+        MPCParameters::<Bls12_377>::read_fast(
+            fs::read(dir.path(name)).unwrap().as_slice(),
+            STREAMING_IS_COMPRESSED,
+            CheckForCorrectness::Full,
+            false,
+            SubgroupCheckMode::Auto,
+        )
+        .unwrap()
+    };
+    let in_memory_peak = peak_heap_growth_of(|| {
+        read("challenge.0")
+            .contribute(BatchExpMode::Auto, &mut derive_rng_from_seed(b"alice"))
+            .unwrap();
+    });
+    println!(
+        "peak heap growth for parameters of {} bytes: {} to contribute, {} to verify, {} in memory",
+        challenge_size, contribution_peak, verification_peak, in_memory_peak
+    );
+    assert!(in_memory_peak > ceiling);
+    for peak in [contribution_peak, verification_peak] {
+        assert!(
+            peak <= ceiling,
+            "the peak heap grew by {} bytes for parameters of {} bytes, more than {} bytes",
+            peak,
+            challenge_size,
+            ceiling
+        );
+    }
+
+    // the response is valid for the in-memory verification too, and the new challenge is the response
+    let contributions_hash = read("challenge.0").verify(&read("response")).unwrap();
+    assert_eq!(contributions_hash.len(), 1);
+    assert_eq!(
+        fs::read(dir.path("new_challenge")).unwrap(),
+        fs::read(dir.path("response")).unwrap()
+    );
+    assert_eq!(
+        fs::read(dir.path("new_challenge.hash")).unwrap(),
+        fs::read(dir.path("response.hash")).unwrap()
+    );
+}
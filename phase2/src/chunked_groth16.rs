@@ -10,6 +10,7 @@ use crate::{
 use setup_utils::{
     batch_mul,
     check_same_ratio,
+    check_subgroup,
     deserialize,
     merge_pairs,
     serialize,
@@ -17,7 +18,9 @@ use setup_utils::{
     CheckForCorrectness,
     InvariantKind,
     Phase2Error,
+    RatioCheck,
     Result,
+    SubgroupCheckMode,
     UseCompression,
 };
 
@@ -36,7 +39,9 @@ use tracing::{debug, info, info_span, trace};
 
 /// Given two serialized contributions to the ceremony, this will check that `after`
 /// has been correctly calculated from `before`. Large vectors will be read in
-/// `batch_size` batches
+/// `batch_size` batches. Unless `check_correctness` is `Full`, which already checks it, the
+/// elements of `after` are checked to be in the prime order subgroup with `subgroup_check_mode`,
+/// batch by batch
 #[allow(clippy::cognitive_complexity)]
 pub fn verify<E: Pairing>(
     before: &mut [u8],
@@ -44,6 +49,7 @@ pub fn verify<E: Pairing>(
     batch_size: usize,
     compressed: UseCompression,
     check_correctness: CheckForCorrectness,
+    subgroup_check_mode: SubgroupCheckMode,
 ) -> Result<Vec<[u8; 64]>> {
     let span = info_span!("phase2-verify");
     let _enter = span.enter();
@@ -61,6 +67,14 @@ pub fn verify<E: Pairing>(
     let vk_after = deserialize::<VerifyingKey<E>, _>(&mut after, compressed, check_correctness)?;
     let beta_g1_after = deserialize::<E::G1Affine, _>(&mut after, compressed, check_correctness)?;
     let delta_g1_after = deserialize::<E::G1Affine, _>(&mut after, compressed, check_correctness)?;
+    if check_correctness != CheckForCorrectness::Full {
+        check_subgroup(&[vk_after.alpha_g1, beta_g1_after, delta_g1_after], subgroup_check_mode)?;
+        check_subgroup(
+            &[vk_after.beta_g2, vk_after.gamma_g2, vk_after.delta_g2],
+            subgroup_check_mode,
+        )?;
+        check_subgroup(&vk_after.gamma_abc_g1, subgroup_check_mode)?;
+    }
 
     // VK parameters remain unchanged, except for Delta G2
     // which we check at the end of the function against the new contribution's
@@ -111,6 +125,7 @@ pub fn verify<E: Pairing>(
                 &InvariantKind::AlphaG1Query,
                 compressed,
                 check_correctness,
+                subgroup_check_mode,
             )
         }));
         threads.push(s.spawn(|_| {
@@ -124,6 +139,7 @@ pub fn verify<E: Pairing>(
                 &InvariantKind::BetaG1Query,
                 compressed,
                 check_correctness,
+                subgroup_check_mode,
             )
         }));
         threads.push(s.spawn(|_| {
@@ -137,6 +153,7 @@ pub fn verify<E: Pairing>(
                 &InvariantKind::BetaG2Query,
                 compressed,
                 check_correctness,
+                subgroup_check_mode,
             )
         }));
 
@@ -153,7 +170,8 @@ pub fn verify<E: Pairing>(
                 batch_size,
                 compressed,
                 check_correctness,
-                "H_query ratio check failed",
+                subgroup_check_mode,
+                RatioCheck::HQuery,
             )
         }));
         threads.push(s.spawn(|_| {
@@ -168,7 +186,8 @@ pub fn verify<E: Pairing>(
                 batch_size,
                 compressed,
                 check_correctness,
-                "L_query ratio check failed",
+                subgroup_check_mode,
+                RatioCheck::LQuery,
             )
         }));
 
@@ -217,7 +236,7 @@ pub fn verify<E: Pairing>(
     check_same_ratio::<E>(
         &(E::G1Affine::generator(), pub_key.delta_after),
         &(E::G2Affine::generator(), vk_after.delta_g2),
-        "Inconsistent G2 Delta".to_string(),
+        RatioCheck::DeltaG1G2,
     )?;
 
     debug!("verifying key was updated correctly");
@@ -474,6 +493,7 @@ fn chunked_ensure_unchanged_vec<C: AffineRepr>(
     kind: &InvariantKind,
     compressed: UseCompression,
     check_correctness: CheckForCorrectness,
+    subgroup_check_mode: SubgroupCheckMode,
 ) -> Result<()> {
     let span = info_span!("unchanged_vec");
     let _enter = span.enter();
@@ -493,8 +513,14 @@ fn chunked_ensure_unchanged_vec<C: AffineRepr>(
         let span1 = info_span!("iter", i);
         let _enter = span1.enter();
 
-        let (els_before, els_after) =
-            read_batch::<C, _>(&mut before, &mut after, batch_size, compressed, check_correctness)?;
+        let (els_before, els_after) = read_batch::<C, _>(
+            &mut before,
+            &mut after,
+            batch_size,
+            compressed,
+            check_correctness,
+            subgroup_check_mode,
+        )?;
         ensure_unchanged_vec(&els_before, &els_after, kind)?;
 
         trace!("ok");
@@ -505,8 +531,14 @@ fn chunked_ensure_unchanged_vec<C: AffineRepr>(
         let span1 = info_span!("iter", i = iters);
         let _enter = span1.enter();
 
-        let (els_before, els_after) =
-            read_batch::<C, _>(&mut before, &mut after, leftovers, compressed, check_correctness)?;
+        let (els_before, els_after) = read_batch::<C, _>(
+            &mut before,
+            &mut after,
+            leftovers,
+            compressed,
+            check_correctness,
+            subgroup_check_mode,
+        )?;
         ensure_unchanged_vec(&els_before, &els_after, kind)?;
 
         trace!("ok");
@@ -526,7 +558,8 @@ fn chunked_check_ratio<E: Pairing>(
     batch_size: usize,
     compressed: UseCompression,
     check_correctness: CheckForCorrectness,
-    err: &'static str,
+    subgroup_check_mode: SubgroupCheckMode,
+    check: RatioCheck,
 ) -> Result<()> {
     let span = info_span!("check_ratio");
     let _enter = span.enter();
@@ -546,18 +579,34 @@ fn chunked_check_ratio<E: Pairing>(
 
     let iters = len_before / batch_size;
     let leftovers = len_before % batch_size;
-    for _ in 0..iters {
-        let (els_before, els_after) =
-            read_batch::<E::G1Affine, _>(&mut before, &mut after, batch_size, compressed, check_correctness)?;
+    for i in 0..iters {
+        let (start, end) = (i * batch_size, (i + 1) * batch_size);
+        let (els_before, els_after) = read_batch::<E::G1Affine, _>(
+            &mut before,
+            &mut after,
+            batch_size,
+            compressed,
+            check_correctness,
+            subgroup_check_mode,
+        )?;
         let pairs = merge_pairs(&els_before, &els_after);
-        check_same_ratio::<E>(&pairs, &(after_delta_g2, before_delta_g2), err.to_string())?;
+        check_same_ratio::<E>(&pairs, &(after_delta_g2, before_delta_g2), check)
+            .map_err(|e| e.with_batch(start, end))?;
     }
     // in case the batch size did not evenly divide the number of queries
     if leftovers > 0 {
-        let (els_before, els_after) =
-            read_batch::<E::G1Affine, _>(&mut before, &mut after, leftovers, compressed, check_correctness)?;
+        let (start, end) = (iters * batch_size, len_before);
+        let (els_before, els_after) = read_batch::<E::G1Affine, _>(
+            &mut before,
+            &mut after,
+            leftovers,
+            compressed,
+            check_correctness,
+            subgroup_check_mode,
+        )?;
         let pairs = merge_pairs(&els_before, &els_after);
-        check_same_ratio::<E>(&pairs, &(after_delta_g2, before_delta_g2), err.to_string())?;
+        check_same_ratio::<E>(&pairs, &(after_delta_g2, before_delta_g2), check)
+            .map_err(|e| e.with_batch(start, end))?;
     }
 
     debug!("done.");
@@ -571,6 +620,7 @@ fn read_batch<C: AffineRepr, B: Read + Write + Seek>(
     batch_size: usize,
     compressed: UseCompression,
     check_correctness: CheckForCorrectness,
+    subgroup_check_mode: SubgroupCheckMode,
 ) -> Result<(Vec<C>, Vec<C>)> {
     let els_before = (0..batch_size)
         .map(|_| deserialize::<C, _>(&mut before, compressed, check_correctness))
@@ -578,6 +628,9 @@ fn read_batch<C: AffineRepr, B: Read + Write + Seek>(
     let els_after = (0..batch_size)
         .map(|_| deserialize::<C, _>(&mut after, compressed, check_correctness))
         .collect::<std::result::Result<Vec<_>, _>>()?;
+    if check_correctness != CheckForCorrectness::Full {
+        check_subgroup(&els_after, subgroup_check_mode)?;
+    }
     Ok((els_before, els_after))
 }
 
@@ -12,46 +12,71 @@ pub mod load_circuit;
 
 pub mod chunked_groth16;
 
+#[cfg(not(feature = "wasm"))]
+pub mod zkey;
+
 cfg_if! {
     if #[cfg(feature = "wasm")] {
         use wasm_bindgen::prelude::*;
         use itertools::Itertools;
         use parameters::MPCParameters;
         use ark_bls12_377::Bls12_377;
+        use ark_bls12_381::Bls12_381;
+        use ark_bn254::Bn254;
         use ark_bw6_761::BW6_761;
         use ark_ec::pairing::Pairing;
-        use setup_utils::{BatchExpMode, CheckForCorrectness, get_rng, SubgroupCheckMode, user_system_randomness, UseCompression};
+        use std::ops::Neg;
+        use setup_utils::{
+            converters::{curve_from_str, CurveKind},
+            BatchExpMode, CheckForCorrectness, get_rng, SubgroupCheckMode, user_system_randomness, UseCompression,
+        };
 
         macro_rules! log {
             ($($t:tt)*) => (web_sys::console::log_1(&format_args!($($t)*).to_string().into()))
         }
 
+        /// Contributes to the parameters of the inner (BLS12-377) or outer (BW6-761) circuit
         #[wasm_bindgen]
         pub fn contribute(is_inner: bool, params: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+            let curve_kind = if is_inner { "bls12_377" } else { "bw6" };
+            contribute_with_curve(curve_kind, params)
+        }
+
+        /// Contributes to the parameters of a circuit over the curve named `curve_kind`
+        #[wasm_bindgen]
+        pub fn contribute_with_curve(curve_kind: &str, params: Vec<u8>) -> Result<Vec<u8>, JsValue> {
             console_error_panic_hook::set_once();
 
             log!("Initializing phase2");
-            let res = match is_inner {
-                true => contribute_challenge(&mut MPCParameters::<Bls12_377>::read(
-                    &*params,
-                    UseCompression::No,
-                    CheckForCorrectness::Full,
-                    false,
-                    SubgroupCheckMode::Auto,
-                ).unwrap()),
-                false => contribute_challenge(&mut MPCParameters::<BW6_761>::read(
-                    &*params,
-                    UseCompression::No,
-                    CheckForCorrectness::Full,
-                    false,
-                    SubgroupCheckMode::Auto,
-                ).unwrap()),
+            let res = match curve_from_str(curve_kind)? {
+                CurveKind::Bls12_377 => contribute_challenge(&mut read_parameters::<Bls12_377>(&params)?),
+                CurveKind::Bls12_381 => contribute_challenge(&mut read_parameters::<Bls12_381>(&params)?),
+                CurveKind::Bn254 => contribute_challenge(&mut read_parameters::<Bn254>(&params)?),
+                CurveKind::BW6 => contribute_challenge(&mut read_parameters::<BW6_761>(&params)?),
+                curve_kind => return Err(format!("unsupported curve: {}", curve_kind).into()),
             };
 
             Ok(res)
         }
 
-        fn contribute_challenge<E: Pairing>(params: &mut MPCParameters<E>) -> Vec<u8> {
+        fn read_parameters<E: Pairing>(params: &[u8]) -> Result<MPCParameters<E>, JsValue>
+        where
+            E::G1Affine: Neg<Output = E::G1Affine>,
+        {
+            MPCParameters::<E>::read(
+                params,
+                UseCompression::No,
+                CheckForCorrectness::Full,
+                false,
+                SubgroupCheckMode::Auto,
+            )
+            .map_err(|e| e.to_string().into())
+        }
+
+        fn contribute_challenge<E: Pairing>(params: &mut MPCParameters<E>) -> Vec<u8>
+        where
+            E::G1Affine: Neg<Output = E::G1Affine>,
+        {
             let mut rng = get_rng(&user_system_randomness());
             log!("Contributing...");
             let hash = params.contribute(BatchExpMode::Auto, &mut rng);
@@ -1,7 +1,17 @@
 use ark_ec::pairing::Pairing;
+use ark_ff::One;
 use ark_relations::r1cs::{ConstraintMatrices, Matrix};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use setup_utils::Error;
+use setup_utils::{
+    snarkjs::{field_from_bytes, field_size, modulus_bytes, read_u32, read_u64, take, Sections},
+    Error,
+};
+
+/// The magic bytes every circom `.r1cs` file starts with
+pub const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+
+const R1CS_SECTION_HEADER: u32 = 1;
+const R1CS_SECTION_CONSTRAINTS: u32 = 2;
 
 // For serialization of the constraint system
 #[derive(Debug, PartialEq, CanonicalDeserialize, CanonicalSerialize, Clone)]
@@ -32,9 +42,101 @@ pub struct Matrices<E: Pairing> {
 }
 
 impl<E: Pairing> Matrices<E> {
+    /// Reads serialized matrices, or a circom `.r1cs` file if the input starts with its magic
     pub fn read(input_map: &[u8]) -> Result<Self, Error> {
+        if input_map.starts_with(R1CS_MAGIC) {
+            return Self::read_r1cs(input_map);
+        }
         Ok(Matrices::deserialize_compressed(&mut &input_map[..])?)
     }
+
+    /// Reads the constraints of a circuit compiled by circom to the `.r1cs` binary format.
+    ///
+    /// The header holds the field size and prime, which must be the scalar field of `E`, followed
+    /// by the number of wires, public outputs, public inputs, private inputs, labels and
+    /// constraints. Each constraint is made of the A, B and C linear combinations, written as
+    /// the number of terms followed by (wire, coefficient) pairs.
+    ///
+    /// Circom orders the wires as the constant one, the public outputs, the public inputs and the
+    /// private wires, which maps directly to the instance and witness variables. As in
+    /// `circuit_to_qap`, an `x * 0 = 0` constraint is added for every instance variable.
+    pub fn read_r1cs(input: &[u8]) -> Result<Self, Error> {
+        let sections = Sections::read(input, R1CS_MAGIC)?;
+
+        let mut header = sections.require(R1CS_SECTION_HEADER)?;
+        let n8 = read_u32(&mut header)? as usize;
+        if n8 != field_size::<E::ScalarField>() || take(&mut header, n8)? != modulus_bytes::<E::ScalarField>() {
+            return Err(Error::InvalidSnarkjsFile(
+                "the field of the circuit does not match the scalar field of the selected curve".to_string(),
+            ));
+        }
+        let num_wires = read_u32(&mut header)? as usize;
+        let num_public_outputs = read_u32(&mut header)? as usize;
+        let num_public_inputs = read_u32(&mut header)? as usize;
+        let _num_private_inputs = read_u32(&mut header)?;
+        let _num_labels = read_u64(&mut header)?;
+        let num_constraints = read_u32(&mut header)? as usize;
+
+        let num_instance_variables = 1 + num_public_outputs + num_public_inputs;
+        if num_wires < num_instance_variables {
+            return Err(Error::InvalidSnarkjsFile(format!(
+                "the circuit has {} wires but {} public variables",
+                num_wires, num_instance_variables
+            )));
+        }
+
+        let mut reader = sections.require(R1CS_SECTION_CONSTRAINTS)?;
+        let mut read_linear_combination = || -> Result<Vec<(E::ScalarField, usize)>, Error> {
+            let num_terms = read_u32(&mut reader)? as usize;
+            let mut terms = Vec::with_capacity(num_terms);
+            for _ in 0..num_terms {
+                let wire = read_u32(&mut reader)? as usize;
+                if wire >= num_wires {
+                    return Err(Error::InvalidSnarkjsFile(format!(
+                        "wire {} is out of range, the circuit has {} wires",
+                        wire, num_wires
+                    )));
+                }
+                let coefficient = field_from_bytes(take(&mut reader, n8)?)?;
+                terms.push((coefficient, wire));
+            }
+            Ok(terms)
+        };
+
+        let mut a = Vec::with_capacity(num_constraints + num_instance_variables);
+        let mut b = Vec::with_capacity(num_constraints + num_instance_variables);
+        let mut c = Vec::with_capacity(num_constraints + num_instance_variables);
+        for _ in 0..num_constraints {
+            a.push(read_linear_combination()?);
+            b.push(read_linear_combination()?);
+            c.push(read_linear_combination()?);
+        }
+        if !reader.is_empty() {
+            return Err(Error::InvalidSnarkjsFile(
+                "trailing data after the constraints".to_string(),
+            ));
+        }
+
+        // Input constraints to ensure full density of IC query
+        for i in 0..num_instance_variables {
+            a.push(vec![(E::ScalarField::one(), i)]);
+            b.push(vec![]);
+            c.push(vec![]);
+        }
+
+        let num_non_zero = |matrix: &Matrix<E::ScalarField>| matrix.iter().map(|row| row.len()).sum();
+        Ok(Self {
+            num_instance_variables,
+            num_witness_variables: num_wires - num_instance_variables,
+            num_constraints: a.len(),
+            a_num_non_zero: num_non_zero(&a),
+            b_num_non_zero: num_non_zero(&b),
+            c_num_non_zero: num_non_zero(&c),
+            a,
+            b,
+            c,
+        })
+    }
 }
 
 impl<E: Pairing> From<ConstraintMatrices<E::ScalarField>> for Matrices<E> {
@@ -52,3 +154,86 @@ impl<E: Pairing> From<ConstraintMatrices<E::ScalarField>> for Matrices<E> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{helpers::testing::TestCircuit, parameters::circuit_to_qap};
+    use setup_utils::snarkjs::{write_file_header, write_section_header};
+
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ff::{BigInteger, PrimeField};
+
+    // writes the matrices as circom would, without the constraints added for the instance variables
+    fn to_r1cs<E: Pairing>(matrices: &Matrices<E>) -> Vec<u8> {
+        let n8 = field_size::<E::ScalarField>();
+        let num_wires = matrices.num_instance_variables + matrices.num_witness_variables;
+        let num_constraints = matrices.num_constraints - matrices.num_instance_variables;
+
+        let mut constraints = vec![];
+        for i in 0..num_constraints {
+            for row in [&matrices.a[i], &matrices.b[i], &matrices.c[i]] {
+                constraints.extend_from_slice(&(row.len() as u32).to_le_bytes());
+                for (coefficient, wire) in row {
+                    constraints.extend_from_slice(&(*wire as u32).to_le_bytes());
+                    constraints.extend_from_slice(&coefficient.into_bigint().to_bytes_le());
+                }
+            }
+        }
+
+        let mut output = vec![];
+        write_file_header(&mut output, R1CS_MAGIC, 3).unwrap();
+        write_section_header(&mut output, R1CS_SECTION_HEADER, 4 + n8 + 4 * 4 + 8 + 4).unwrap();
+        output.extend_from_slice(&(n8 as u32).to_le_bytes());
+        output.extend_from_slice(&modulus_bytes::<E::ScalarField>());
+        output.extend_from_slice(&(num_wires as u32).to_le_bytes());
+        // all public variables are outputs
+        output.extend_from_slice(&(matrices.num_instance_variables as u32 - 1).to_le_bytes());
+        output.extend_from_slice(&0u32.to_le_bytes());
+        output.extend_from_slice(&(matrices.num_witness_variables as u32).to_le_bytes());
+        output.extend_from_slice(&(num_wires as u64).to_le_bytes());
+        output.extend_from_slice(&(num_constraints as u32).to_le_bytes());
+        write_section_header(&mut output, R1CS_SECTION_CONSTRAINTS, constraints.len()).unwrap();
+        output.extend_from_slice(&constraints);
+        // the wire to label map is not used
+        write_section_header(&mut output, 3, num_wires * 8).unwrap();
+        for wire in 0..num_wires as u64 {
+            output.extend_from_slice(&wire.to_le_bytes());
+        }
+        output
+    }
+
+    fn read_r1cs_curve<E: Pairing>() {
+        let cs = circuit_to_qap::<E, _>(TestCircuit::<E>(None)).unwrap();
+        let matrices = Matrices::<E>::from(cs.to_matrices().unwrap());
+
+        let r1cs = to_r1cs(&matrices);
+        assert_eq!(Matrices::<E>::read_r1cs(&r1cs).unwrap(), matrices);
+    }
+
+    #[test]
+    fn read_r1cs() {
+        read_r1cs_curve::<Bls12_377>();
+        read_r1cs_curve::<Bls12_381>();
+    }
+
+    #[test]
+    fn read_r1cs_rejects_other_field() {
+        let cs = circuit_to_qap::<Bls12_377, _>(TestCircuit::<Bls12_377>(None)).unwrap();
+        let matrices = Matrices::<Bls12_377>::from(cs.to_matrices().unwrap());
+
+        let r1cs = to_r1cs(&matrices);
+        assert!(Matrices::<Bls12_381>::read_r1cs(&r1cs).is_err());
+    }
+
+    #[test]
+    fn read_r1cs_rejects_out_of_range_wire() {
+        let cs = circuit_to_qap::<Bls12_377, _>(TestCircuit::<Bls12_377>(None)).unwrap();
+        let mut matrices = Matrices::<Bls12_377>::from(cs.to_matrices().unwrap());
+        matrices.a[0][0].1 = matrices.num_instance_variables + matrices.num_witness_variables;
+
+        let r1cs = to_r1cs(&matrices);
+        assert!(Matrices::<Bls12_377>::read_r1cs(&r1cs).is_err());
+    }
+}
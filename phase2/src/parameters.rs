@@ -234,9 +234,21 @@ where
             contributions: vec![],
         };
 
+        let chunks = full_mpc.split(chunk_size)?;
+        Ok((full_mpc, query_parameters, chunks))
+    }
+
+    /// Splits full parameters into chunks of `chunk_size` elements of the H and L queries. Every
+    /// chunk carries the verifying key, delta and the contributions so far, while the A and B
+    /// queries are left to the query parameters, so that `combine` puts the chunks back together.
+    pub fn split(&self, chunk_size: usize) -> Result<Vec<MPCParameters<E>>> {
+        if chunk_size == 0 {
+            return Err(Error::InvalidArgument("the chunk size must be positive".to_string()));
+        }
+        let params = &self.params;
         let mut chunks = vec![];
         let max_query = std::cmp::max(params.h_query.len(), params.l_query.len());
-        let num_chunks = (max_query + chunk_size - 1) / chunk_size;
+        let num_chunks = max_query.div_ceil(chunk_size);
         for i in 0..num_chunks {
             let chunk_start = i * chunk_size;
             let chunk_end = (i + 1) * chunk_size;
@@ -253,20 +265,20 @@ where
             let chunk_params = MPCParameters {
                 params: ProvingKey::<E> {
                     vk: params.vk.clone(),
-                    beta_g1: params.beta_g1.clone(),
-                    delta_g1: params.delta_g1.clone(),
+                    beta_g1: params.beta_g1,
+                    delta_g1: params.delta_g1,
                     a_query: vec![],
                     b_g1_query: vec![],
                     b_g2_query: vec![],
                     h_query: h_query_for_chunk,
                     l_query: l_query_for_chunk,
                 },
-                cs_hash,
-                contributions: vec![],
+                cs_hash: self.cs_hash,
+                contributions: self.contributions.clone(),
             };
             chunks.push(chunk_params);
         }
-        Ok((full_mpc, query_parameters, chunks))
+        Ok(chunks)
     }
 
     /// Get the underlying Groth16 `Parameters`
@@ -326,7 +338,7 @@ where
         check_same_ratio::<E>(
             &(E::G1Affine::generator(), pub_key.delta_after),
             &(E::G2Affine::generator(), after.params.vk.delta_g2),
-            "Inconsistent G2 Delta".to_string(),
+            RatioCheck::DeltaG1G2,
         )?;
 
         // None of the previous transformations should change
@@ -394,7 +406,7 @@ where
             check_same_ratio::<E>(
                 &merge_pairs(&before.params.h_query, &after.params.h_query),
                 &(after.params.vk.delta_g2, before.params.vk.delta_g2), // reversed for inverse
-                "H_query ratio check failed".to_string(),
+                RatioCheck::HQuery,
             )?;
         }
 
@@ -402,7 +414,7 @@ where
             check_same_ratio::<E>(
                 &merge_pairs(&before.params.l_query, &after.params.l_query),
                 &(after.params.vk.delta_g2, before.params.vk.delta_g2), // reversed for inverse
-                "L_query ratio check failed".to_string(),
+                RatioCheck::LQuery,
             )?;
         }
 
@@ -410,6 +422,49 @@ where
         verify_transcript(before.cs_hash, &after.contributions)
     }
 
+    /// Verifies the final parameters of a ceremony against the initial full parameters and the
+    /// initial queries created for the same circuit, without the parameters in between. The
+    /// initial parameters must have no contributions and agree with the queries, and the final
+    /// parameters are then checked like in `verify`: the whole chain of contributions, the final
+    /// delta against the last public key, and a single ratio check of the final H and L queries
+    /// against the initial ones. Returns the hashes of every contribution.
+    pub fn verify_final(&self, queries: &ProvingKey<E>, after: &Self) -> Result<Vec<[u8; 64]>> {
+        let initial = self;
+
+        // The initial parameters come before any contribution
+        ensure_unchanged(initial.contributions.len(), 0, InvariantKind::Contributions)?;
+        ensure_unchanged(
+            initial.params.delta_g1,
+            E::G1Affine::generator(),
+            InvariantKind::DeltaG1,
+        )?;
+        ensure_unchanged(
+            initial.params.vk.delta_g2,
+            E::G2Affine::generator(),
+            InvariantKind::DeltaG2,
+        )?;
+
+        // and were created with the same queries
+        ensure_unchanged_vec(
+            &queries.vk.gamma_abc_g1,
+            &initial.params.vk.gamma_abc_g1,
+            &InvariantKind::GammaAbcG1,
+        )?;
+        ensure_unchanged_vec(&queries.a_query, &initial.params.a_query, &InvariantKind::AlphaG1Query)?;
+        ensure_unchanged_vec(
+            &queries.b_g1_query,
+            &initial.params.b_g1_query,
+            &InvariantKind::BetaG1Query,
+        )?;
+        ensure_unchanged_vec(
+            &queries.b_g2_query,
+            &initial.params.b_g2_query,
+            &InvariantKind::BetaG2Query,
+        )?;
+
+        initial.verify(after)
+    }
+
     pub fn combine(queries: &ProvingKey<E>, mpcs: &[MPCParameters<E>]) -> Result<MPCParameters<E>> {
         let mut combined_mpc = MPCParameters::<E> {
             params: ProvingKey::<E> {
@@ -443,9 +498,10 @@ where
         Ok(())
     }
 
-    /// Deserialize these parameters.
+    /// Deserialize these parameters. If they start with a file header, it must describe Groth16
+    /// parameters on this curve with the given compression.
     pub fn read<R: Read>(
-        mut reader: R,
+        reader: R,
         compressed: UseCompression,
         check_correctness: CheckForCorrectness,
         check_subgroup_membership: bool,
@@ -454,6 +510,7 @@ where
         if matches!(check_correctness, CheckForCorrectness::OnlyInGroup) {
             return Err(SerializationError::InvalidData.into());
         }
+        let mut reader = skip_file_header::<E, _>(reader, compressed)?;
         let params = ProvingKey::deserialize_with_mode(&mut reader, compressed, check_correctness.into())?;
 
         // In the Full mode, this is already checked
@@ -486,13 +543,15 @@ where
         })
     }
 
+    /// Deserialize these parameters like `read`, with faster checks of the group elements
     pub fn read_fast<R: Read>(
-        mut reader: R,
+        reader: R,
         compressed: UseCompression,
         check_correctness: CheckForCorrectness,
         check_subgroup_membership: bool,
         subgroup_check_mode: SubgroupCheckMode,
     ) -> Result<MPCParameters<E>> {
+        let mut reader = skip_file_header::<E, _>(reader, compressed)?;
         let params = Self::read_groth16_fast(
             &mut reader,
             compressed,
@@ -584,6 +643,22 @@ where
     }
 }
 
+/// Skips the file header at the beginning of serialized parameters, if they have one, after checking
+/// that it describes Groth16 parameters on the curve `E` with the given compression
+fn skip_file_header<E: Pairing, R: Read>(reader: R, compressed: UseCompression) -> Result<impl Read> {
+    let (header, reader) = read_file_header(reader)?;
+    if let Some(header) = header {
+        header.check_curve_and_compression::<E>(compressed)?;
+        if header.proving_system != converters::ProvingSystem::Groth16 {
+            return Err(Error::FileHeaderMismatch(format!(
+                "the file was written for {}, not groth16",
+                header.proving_system
+            )));
+        }
+    }
+    Ok(reader)
+}
+
 /// This is a cheap helper utility that exists purely
 /// because Rust still doesn't have type-level integers
 /// and so doesn't implement `PartialEq` for `[T; 64]`
@@ -637,15 +712,17 @@ pub fn verify_transcript<E: Pairing>(cs_hash: [u8; 64], contributions: &[PublicK
         check_same_ratio::<E>(
             &(pub_key.s, pub_key.s_delta),
             &(r, pub_key.r_delta),
-            "Incorrect signature of knowledge".to_string(),
-        )?;
+            RatioCheck::SignatureOfKnowledge,
+        )
+        .map_err(|e| e.with_contribution_index(i))?;
 
         // Check the change with the previous G1 Delta is consistent
         check_same_ratio::<E>(
             &(old_delta, pub_key.delta_after),
             &(r, pub_key.r_delta),
-            "Inconsistent G1 Delta".to_string(),
-        )?;
+            RatioCheck::DeltaUpdate,
+        )
+        .map_err(|e| e.with_contribution_index(i))?;
         old_delta = pub_key.delta_after;
 
         result.push(pub_key.hash());
@@ -692,9 +769,10 @@ mod tests {
         helpers::testing::TestCircuit,
     };
     use phase1::{helpers::testing::setup_verify, Phase1, Phase1Parameters, ProvingSystem};
-    use setup_utils::{Groth16Params, UseCompression};
+    use setup_utils::{converters::ContributionMode, Groth16Params, UseCompression};
 
     use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
 
     use rand::thread_rng;
     use tracing_subscriber::{filter::EnvFilter, fmt::Subscriber};
@@ -704,6 +782,11 @@ mod tests {
         serialize_ceremony_curve::<Bls12_377>()
     }
 
+    #[test]
+    fn serialize_ceremony_bls12_381() {
+        serialize_ceremony_curve::<Bls12_381>()
+    }
+
     fn serialize_ceremony_curve<E: Pairing + PartialEq>()
     where
         E::G1Affine: Neg<Output = E::G1Affine>,
@@ -725,6 +808,41 @@ mod tests {
         assert_eq!(deserialized, mpc)
     }
 
+    #[test]
+    fn read_with_file_header() {
+        let mpc = generate_ceremony::<Bls12_377>();
+        let header = |compressed| {
+            FileHeader::new::<Bls12_377>(ProvingSystem::Groth16, ContributionMode::Chunked, compressed, 4, 0, 8)
+        };
+
+        let mut serialized = header(UseCompression::Yes).to_bytes().to_vec();
+        mpc.write(&mut serialized, UseCompression::Yes).unwrap();
+        let read = |serialized: &[u8]| {
+            MPCParameters::<Bls12_377>::read_fast(
+                serialized,
+                UseCompression::Yes,
+                CheckForCorrectness::Full,
+                false,
+                SubgroupCheckMode::Auto,
+            )
+        };
+        assert_eq!(read(&serialized).unwrap(), mpc);
+
+        let mut uncompressed = header(UseCompression::No).to_bytes().to_vec();
+        mpc.write(&mut uncompressed, UseCompression::Yes).unwrap();
+        let err = read(&uncompressed).unwrap_err();
+        assert!(matches!(err, Error::FileHeaderMismatch(_)), "{}", err);
+
+        let mut other_curve = FileHeader {
+            curve_id: curve_id::<Bls12_381>(),
+            ..header(UseCompression::Yes)
+        }
+        .to_bytes()
+        .to_vec();
+        mpc.write(&mut other_curve, UseCompression::Yes).unwrap();
+        assert!(matches!(read(&other_curve), Err(Error::FileHeaderMismatch(_))));
+    }
+
     #[test]
     fn verify_with_self_fails() {
         verify_with_self_fails_curve::<Bls12_377>()
@@ -751,6 +869,11 @@ mod tests {
         verify_curve::<Bls12_377>()
     }
 
+    #[test]
+    fn verify_contribution_bls12_381() {
+        verify_curve::<Bls12_381>()
+    }
+
     // contributing once and comparing with the previous step passes
     fn verify_curve<E: Pairing>()
     where
@@ -759,7 +882,8 @@ mod tests {
         Subscriber::builder()
             .with_target(false)
             .with_env_filter(EnvFilter::from_default_env())
-            .init();
+            .try_init()
+            .ok();
 
         let rng = &mut thread_rng();
         // original
@@ -783,6 +907,7 @@ mod tests {
             4,
             UseCompression::Yes,
             CheckForCorrectness::Full,
+            SubgroupCheckMode::Auto,
         )
         .unwrap();
         // after each call on the cursors the cursor's position is at the end,
@@ -812,6 +937,7 @@ mod tests {
             4,
             UseCompression::Yes,
             CheckForCorrectness::Full,
+            SubgroupCheckMode::Auto,
         )
         .unwrap();
         c1_cursor.set_position(0);
@@ -824,11 +950,23 @@ mod tests {
             4,
             UseCompression::Yes,
             CheckForCorrectness::Full,
+            SubgroupCheckMode::Auto,
         )
         .unwrap();
         mpc_cursor.set_position(0);
         c2_cursor.set_position(0);
 
+        // the subgroup checks can be made batch by batch instead of when reading the points
+        verify::<E>(
+            &mut mpc_serialized.as_mut(),
+            &mut c2_buf.as_mut(),
+            4,
+            UseCompression::Yes,
+            CheckForCorrectness::No,
+            SubgroupCheckMode::Direct,
+        )
+        .unwrap();
+
         // the de-serialized versions are also compatible
         let contribution2 = MPCParameters::<E>::read(
             &mut c2_cursor,
@@ -852,6 +990,93 @@ mod tests {
         contribution2.verify(&contribution3).unwrap();
     }
 
+    #[test]
+    fn verify_final() {
+        verify_final_curve::<Bls12_377>()
+    }
+
+    // the final parameters verify against the initial ones, after any number of contributions
+    fn verify_final_curve<E: Pairing>()
+    where
+        E::G1Affine: Neg<Output = E::G1Affine>,
+    {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<E>();
+        let queries = ProvingKey::<E> {
+            h_query: vec![],
+            l_query: vec![],
+            ..mpc.params.clone()
+        };
+
+        let mut last = mpc.clone();
+        let mut hashes = vec![];
+        for _ in 0..3 {
+            hashes.push(last.contribute(BatchExpMode::Auto, rng).unwrap());
+        }
+        assert_eq!(mpc.verify_final(&queries, &last).unwrap(), hashes);
+
+        // parameters which already have contributions are not initial
+        let err = last.verify_final(&queries, &last).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Phase 2 Error: Parameter should not change: Contributions"
+        );
+
+        // the queries must be the ones of the initial parameters
+        let mut other_queries = queries.clone();
+        other_queries.a_query[0] = (other_queries.a_query[0] + E::G1Affine::generator()).into_affine();
+        let err = mpc.verify_final(&other_queries, &last).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Phase 2 Error: Parameter should not change: AlphaG1Query"
+        );
+
+        // a tampered H query doesn't have the ratio of delta
+        let mut tampered = last.clone();
+        tampered.params.h_query[0] = (tampered.params.h_query[0] + E::G1Affine::generator()).into_affine();
+        mpc.verify_final(&queries, &tampered).unwrap_err();
+    }
+
+    #[test]
+    fn split_after_full_contribution() {
+        split_after_full_contribution_curve::<Bls12_377>()
+    }
+
+    // a ceremony can carry on in chunks after a full contribution, and combine back
+    fn split_after_full_contribution_curve<E: Pairing>()
+    where
+        E::G1Affine: Neg<Output = E::G1Affine>,
+    {
+        let mpc = generate_ceremony::<E>();
+        let queries = ProvingKey::<E> {
+            h_query: vec![],
+            l_query: vec![],
+            ..mpc.params.clone()
+        };
+        let mut full = mpc.clone();
+        full.contribute(BatchExpMode::Auto, &mut thread_rng()).unwrap();
+
+        let chunk_size = 3;
+        let mut chunks = full.split(chunk_size).unwrap();
+        let max_query = std::cmp::max(full.params.h_query.len(), full.params.l_query.len());
+        assert_eq!(chunks.len(), max_query.div_ceil(chunk_size));
+        assert_eq!(MPCParameters::combine(&queries, &chunks).unwrap(), full);
+
+        // the same contribution to every chunk
+        for chunk in chunks.iter_mut() {
+            let before = chunk.clone();
+            chunk
+                .contribute(BatchExpMode::Auto, &mut derive_rng_from_seed(b"bob"))
+                .unwrap();
+            before.verify(chunk).unwrap();
+        }
+        let combined = MPCParameters::combine(&queries, &chunks).unwrap();
+        assert_eq!(mpc.verify(&combined).unwrap().len(), 2);
+
+        let err = full.split(0).unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)), "{}", err);
+    }
+
     // helper which generates the initial phase 2 params
     // for the TestCircuit
     fn generate_ceremony<E: Pairing>() -> MPCParameters<E>
@@ -0,0 +1,446 @@
+//! Export of the Phase 2 parameters to the `.zkey` format used by snarkjs.
+//!
+//! A `.zkey` file uses the snarkjs container format (see `setup_utils::snarkjs`) with the magic
+//! `zkey`. The sections written here are:
+//!
+//! 1. header: the protocol (1 for Groth16)
+//! 2. Groth16 header: the sizes and moduli of both fields, the number of variables, of public
+//!    inputs and the domain size, followed by alpha_g1, beta_g1, beta_g2, gamma_g2, delta_g1, delta_g2
+//! 3. IC: the `gamma_abc_g1` query
+//! 4. the coefficients of the A and B matrices
+//! 5. A: the `a_query`
+//! 6. B1: the `b_g1_query`
+//! 7. B2: the `b_g2_query`
+//! 8. C: the `l_query`
+//! 9. H: the quotient query, in the Lagrange form expected by the snarkjs prover
+//! 10. the cs hash and the list of contributions
+//!
+//! The contributions section records the public keys of the contributors so that the history
+//! of the ceremony travels with the key. The cs hash and the transcripts are the ones computed
+//! by this crate, so `snarkjs zkey verify` cannot check them against a `.ptau` file.
+use crate::{keypair::PublicKey, load_circuit::Matrices, parameters::MPCParameters};
+use setup_utils::{
+    snarkjs::{
+        field_size,
+        field_to_bytes,
+        modulus_bytes,
+        montgomery_r,
+        point_size,
+        write_file_header,
+        write_point,
+        write_points,
+        write_section_header,
+        SnarkjsPoint,
+    },
+    Error,
+    Result,
+};
+
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{FftField, Field, PrimeField};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_std::{cfg_into_iter, Zero};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use std::io::Write;
+
+/// The magic bytes every `.zkey` file starts with
+pub const ZKEY_MAGIC: &[u8; 4] = b"zkey";
+/// The protocol id snarkjs uses for Groth16
+pub const ZKEY_GROTH16_PROTOCOL: u32 = 1;
+
+const SECTION_HEADER: u32 = 1;
+const SECTION_GROTH16_HEADER: u32 = 2;
+const SECTION_IC: u32 = 3;
+const SECTION_COEFFS: u32 = 4;
+const SECTION_A: u32 = 5;
+const SECTION_B_G1: u32 = 6;
+const SECTION_B_G2: u32 = 7;
+const SECTION_C: u32 = 8;
+const SECTION_H: u32 = 9;
+const SECTION_CONTRIBUTIONS: u32 = 10;
+
+/// The matrix ids used in the coefficients section
+const MATRIX_A: u32 = 0;
+const MATRIX_B: u32 = 1;
+
+impl<E: Pairing> MPCParameters<E>
+where
+    E::G1Affine: SnarkjsPoint,
+    E::G2Affine: SnarkjsPoint,
+{
+    /// Writes the parameters, the constraints of the circuit they were generated for and
+    /// the contributions in the snarkjs `.zkey` format.
+    pub fn write_zkey<W: Write>(&self, mut writer: W, matrices: &Matrices<E>) -> Result<()> {
+        let params = &self.params;
+        let vk = &params.vk;
+
+        let num_variables = matrices.num_instance_variables + matrices.num_witness_variables;
+        ensure_length(params.a_query.len(), num_variables)?;
+        ensure_length(vk.gamma_abc_g1.len(), matrices.num_instance_variables)?;
+        ensure_length(params.l_query.len(), matrices.num_witness_variables)?;
+        let h_query = h_query_to_lagrange::<E>(&params.h_query)?;
+        let domain_size = h_query.len();
+        if matrices.a.len() > domain_size || matrices.b.len() > domain_size {
+            return Err(Error::InvalidSnarkjsFile(format!(
+                "the circuit has {} constraints, which do not fit in a domain of size {}",
+                std::cmp::max(matrices.a.len(), matrices.b.len()),
+                domain_size
+            )));
+        }
+
+        let n8q = field_size::<BaseField<E>>();
+        let n8r = field_size::<E::ScalarField>();
+        let g1_size = point_size::<E::G1Affine>();
+        let g2_size = point_size::<E::G2Affine>();
+
+        write_file_header(&mut writer, ZKEY_MAGIC, 10)?;
+
+        write_section_header(&mut writer, SECTION_HEADER, 4)?;
+        writer.write_all(&ZKEY_GROTH16_PROTOCOL.to_le_bytes())?;
+
+        // Groth16 header: n8q, q, n8r, r, number of variables, public inputs and domain size, keys
+        write_section_header(
+            &mut writer,
+            SECTION_GROTH16_HEADER,
+            4 + n8q + 4 + n8r + 3 * 4 + 3 * g1_size + 3 * g2_size,
+        )?;
+        writer.write_all(&(n8q as u32).to_le_bytes())?;
+        writer.write_all(&modulus_bytes::<BaseField<E>>())?;
+        writer.write_all(&(n8r as u32).to_le_bytes())?;
+        writer.write_all(&modulus_bytes::<E::ScalarField>())?;
+        writer.write_all(&(num_variables as u32).to_le_bytes())?;
+        // the constant one is not counted as a public input
+        writer.write_all(&((matrices.num_instance_variables - 1) as u32).to_le_bytes())?;
+        writer.write_all(&(domain_size as u32).to_le_bytes())?;
+        write_point(&mut writer, &vk.alpha_g1)?;
+        write_point(&mut writer, &params.beta_g1)?;
+        write_point(&mut writer, &vk.beta_g2)?;
+        write_point(&mut writer, &vk.gamma_g2)?;
+        write_point(&mut writer, &params.delta_g1)?;
+        write_point(&mut writer, &vk.delta_g2)?;
+
+        write_section_header(&mut writer, SECTION_IC, vk.gamma_abc_g1.len() * g1_size)?;
+        write_points(&mut writer, &vk.gamma_abc_g1)?;
+
+        let coeffs = coeffs_to_bytes(matrices);
+        write_section_header(&mut writer, SECTION_COEFFS, coeffs.len())?;
+        writer.write_all(&coeffs)?;
+
+        write_section_header(&mut writer, SECTION_A, params.a_query.len() * g1_size)?;
+        write_points(&mut writer, &params.a_query)?;
+        write_section_header(&mut writer, SECTION_B_G1, params.b_g1_query.len() * g1_size)?;
+        write_points(&mut writer, &params.b_g1_query)?;
+        write_section_header(&mut writer, SECTION_B_G2, params.b_g2_query.len() * g2_size)?;
+        write_points(&mut writer, &params.b_g2_query)?;
+        write_section_header(&mut writer, SECTION_C, params.l_query.len() * g1_size)?;
+        write_points(&mut writer, &params.l_query)?;
+        write_section_header(&mut writer, SECTION_H, h_query.len() * g1_size)?;
+        write_points(&mut writer, &h_query)?;
+
+        // cs hash, then for each contribution: delta_after, s, s_delta, r_delta,
+        // the transcript, the type (0 for a regular contribution) and empty parameters
+        let contribution_size = 3 * g1_size + g2_size + 64 + 4 + 4;
+        write_section_header(
+            &mut writer,
+            SECTION_CONTRIBUTIONS,
+            64 + 4 + self.contributions.len() * contribution_size,
+        )?;
+        writer.write_all(&self.cs_hash)?;
+        writer.write_all(&(self.contributions.len() as u32).to_le_bytes())?;
+        for contribution in &self.contributions {
+            write_contribution(&mut writer, contribution)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The prime field the coordinates of the points are defined over
+type BaseField<E> = <<<E as Pairing>::G1Affine as AffineRepr>::BaseField as Field>::BasePrimeField;
+
+fn ensure_length(got: usize, expected: usize) -> Result<()> {
+    if got != expected {
+        return Err(Error::InvalidLength { expected, got });
+    }
+    Ok(())
+}
+
+/// Serializes the non-zero entries of the A and B matrices. snarkjs stores every coefficient
+/// multiplied twice by the Montgomery constant.
+fn coeffs_to_bytes<E: Pairing>(matrices: &Matrices<E>) -> Vec<u8> {
+    let r = montgomery_r::<E::ScalarField>();
+    let r_squared = r.square();
+    let num_coeffs: usize = matrices.a.iter().chain(matrices.b.iter()).map(|row| row.len()).sum();
+
+    let mut output = Vec::with_capacity(4 + num_coeffs * (3 * 4 + field_size::<E::ScalarField>()));
+    output.extend_from_slice(&(num_coeffs as u32).to_le_bytes());
+    for (matrix_id, matrix) in [(MATRIX_A, &matrices.a), (MATRIX_B, &matrices.b)] {
+        for (constraint, row) in matrix.iter().enumerate() {
+            for (coeff, variable) in row {
+                output.extend_from_slice(&matrix_id.to_le_bytes());
+                output.extend_from_slice(&(constraint as u32).to_le_bytes());
+                output.extend_from_slice(&(*variable as u32).to_le_bytes());
+                field_to_bytes(coeff, &r_squared, &mut output);
+            }
+        }
+    }
+    output
+}
+
+fn write_contribution<E: Pairing, W: Write>(writer: &mut W, contribution: &PublicKey<E>) -> Result<()> {
+    write_point(writer, &contribution.delta_after)?;
+    write_point(writer, &contribution.s)?;
+    write_point(writer, &contribution.s_delta)?;
+    write_point(writer, &contribution.r_delta)?;
+    writer.write_all(&contribution.transcript)?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    Ok(())
+}
+
+/// Converts the H query from the form used by arkworks, `tau^j * Z(tau) / delta` for
+/// `j` in `0..n - 1`, to the one used by snarkjs.
+///
+/// The snarkjs prover evaluates the quotient polynomial on the coset `c_i = w^{2i + 1}`, with `w`
+/// a `2n`-th root of unity, and expects `H_i = L_{2i + 1}(tau) / delta` for the Lagrange basis
+/// of the domain of size `2n`. Since `L_{2i + 1}(X) = -Z(X) / (2n) * sum_j (X / c_i)^j`, this is
+/// an inverse FFT of the points `-1/2 * w^{-j} * tau^j * Z(tau) / delta`. The missing
+/// `tau^{n - 1}` term is never used by a prover, as the quotient has degree at most `n - 2`.
+fn h_query_to_lagrange<E: Pairing>(h_query: &[E::G1Affine]) -> Result<Vec<E::G1Affine>> {
+    let domain_size = h_query.len() + 1;
+    let domain = Radix2EvaluationDomain::<E::ScalarField>::new(domain_size)
+        .filter(|domain| domain.size() == domain_size)
+        .ok_or_else(|| {
+            Error::InvalidSnarkjsFile(format!(
+                "the H query must have a power of two minus one elements, got {}",
+                h_query.len()
+            ))
+        })?;
+    let root_inverse = E::ScalarField::get_root_of_unity(2 * domain_size as u64)
+        .and_then(|root| root.inverse())
+        .ok_or_else(|| Error::InvalidSnarkjsFile(format!("no root of unity of order {}", 2 * domain_size)))?;
+
+    let mut scalars = Vec::with_capacity(h_query.len());
+    let mut scalar = -E::ScalarField::from(2u64).inverse().expect("2 is invertible");
+    for _ in 0..h_query.len() {
+        scalars.push(scalar);
+        scalar *= root_inverse;
+    }
+    let mut points = cfg_into_iter!(0..h_query.len())
+        .map(|j| h_query[j].mul_bigint(scalars[j].into_bigint()))
+        .collect::<Vec<_>>();
+    points.push(E::G1::zero());
+
+    let points = domain.ifft(&points);
+    Ok(E::G1::normalize_batch(&points))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        helpers::testing::TestCircuit,
+        parameters::{circuit_to_qap, MPCParameters},
+    };
+    use setup_utils::{
+        snarkjs::{read_point, read_points, read_u32, take, Sections},
+        BatchExpMode,
+        CheckForCorrectness,
+    };
+
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::Groth16;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+    use ark_std::UniformRand;
+
+    use rand::thread_rng;
+
+    fn zkey_layout_curve_test<E: Pairing>()
+    where
+        E::G1Affine: SnarkjsPoint + std::ops::Neg<Output = E::G1Affine>,
+        E::G2Affine: SnarkjsPoint,
+    {
+        let rng = &mut thread_rng();
+        let cs = circuit_to_qap::<E, _>(TestCircuit::<E>(None)).unwrap();
+        let matrices: Matrices<E> = cs.to_matrices().unwrap().into();
+        let params = Groth16::<E>::generate_random_parameters_with_reduction(TestCircuit::<E>(None), rng).unwrap();
+        let mut mpc = MPCParameters {
+            params,
+            cs_hash: [1; 64],
+            contributions: vec![],
+        };
+        mpc.contribute(BatchExpMode::Auto, rng).unwrap();
+
+        let mut zkey = vec![];
+        mpc.write_zkey(&mut zkey, &matrices).unwrap();
+        let sections = Sections::read(&zkey, ZKEY_MAGIC).unwrap();
+        let params = &mpc.params;
+
+        let mut header = sections.require(SECTION_HEADER).unwrap();
+        assert_eq!(read_u32(&mut header).unwrap(), ZKEY_GROTH16_PROTOCOL);
+
+        let mut header = sections.require(SECTION_GROTH16_HEADER).unwrap();
+        let n8q = read_u32(&mut header).unwrap() as usize;
+        assert_eq!(take(&mut header, n8q).unwrap(), modulus_bytes::<BaseField<E>>());
+        let n8r = read_u32(&mut header).unwrap() as usize;
+        assert_eq!(take(&mut header, n8r).unwrap(), modulus_bytes::<E::ScalarField>());
+        let num_variables = read_u32(&mut header).unwrap() as usize;
+        assert_eq!(num_variables, params.a_query.len());
+        assert_eq!(
+            read_u32(&mut header).unwrap() as usize,
+            matrices.num_instance_variables - 1
+        );
+        let domain_size = read_u32(&mut header).unwrap() as usize;
+        assert_eq!(domain_size, params.h_query.len() + 1);
+        let check = CheckForCorrectness::Full;
+        assert_eq!(
+            read_point::<E::G1Affine>(&mut header, check).unwrap(),
+            params.vk.alpha_g1
+        );
+        assert_eq!(read_point::<E::G1Affine>(&mut header, check).unwrap(), params.beta_g1);
+        assert_eq!(
+            read_point::<E::G2Affine>(&mut header, check).unwrap(),
+            params.vk.beta_g2
+        );
+        assert_eq!(
+            read_point::<E::G2Affine>(&mut header, check).unwrap(),
+            params.vk.gamma_g2
+        );
+        assert_eq!(read_point::<E::G1Affine>(&mut header, check).unwrap(), params.delta_g1);
+        assert_eq!(
+            read_point::<E::G2Affine>(&mut header, check).unwrap(),
+            params.vk.delta_g2
+        );
+        assert!(header.is_empty());
+
+        let ic = sections.require(SECTION_IC).unwrap();
+        assert_eq!(
+            read_points::<E::G1Affine>(ic, matrices.num_instance_variables, check).unwrap(),
+            params.vk.gamma_abc_g1
+        );
+        let check = CheckForCorrectness::No;
+        let a = sections.require(SECTION_A).unwrap();
+        assert_eq!(
+            read_points::<E::G1Affine>(a, num_variables, check).unwrap(),
+            params.a_query
+        );
+        let b_g1 = sections.require(SECTION_B_G1).unwrap();
+        assert_eq!(
+            read_points::<E::G1Affine>(b_g1, num_variables, check).unwrap(),
+            params.b_g1_query
+        );
+        let b_g2 = sections.require(SECTION_B_G2).unwrap();
+        assert_eq!(
+            read_points::<E::G2Affine>(b_g2, num_variables, check).unwrap(),
+            params.b_g2_query
+        );
+        let c = sections.require(SECTION_C).unwrap();
+        assert_eq!(
+            read_points::<E::G1Affine>(c, matrices.num_witness_variables, check).unwrap(),
+            params.l_query
+        );
+        let h = sections.require(SECTION_H).unwrap();
+        assert_eq!(
+            read_points::<E::G1Affine>(h, domain_size, check).unwrap().len(),
+            domain_size
+        );
+
+        // every coefficient is stored as coeff * R^2
+        let mut coeffs = sections.require(SECTION_COEFFS).unwrap();
+        let num_coeffs = read_u32(&mut coeffs).unwrap() as usize;
+        let r_inv = montgomery_r::<E::ScalarField>().inverse().unwrap();
+        let mut decoded = vec![];
+        for _ in 0..num_coeffs {
+            let matrix = read_u32(&mut coeffs).unwrap();
+            let constraint = read_u32(&mut coeffs).unwrap() as usize;
+            let variable = read_u32(&mut coeffs).unwrap() as usize;
+            let value = E::ScalarField::from_le_bytes_mod_order(take(&mut coeffs, n8r).unwrap());
+            decoded.push((matrix, constraint, variable, value * r_inv * r_inv));
+        }
+        assert!(coeffs.is_empty());
+        let mut expected = vec![];
+        for (matrix_id, matrix) in [(MATRIX_A, &matrices.a), (MATRIX_B, &matrices.b)] {
+            for (constraint, row) in matrix.iter().enumerate() {
+                for (coeff, variable) in row {
+                    expected.push((matrix_id, constraint, *variable, *coeff));
+                }
+            }
+        }
+        assert_eq!(decoded, expected);
+
+        let mut contributions = sections.require(SECTION_CONTRIBUTIONS).unwrap();
+        assert_eq!(take(&mut contributions, 64).unwrap(), &mpc.cs_hash[..]);
+        assert_eq!(read_u32(&mut contributions).unwrap(), 1);
+        let contribution = &mpc.contributions[0];
+        let check = CheckForCorrectness::Full;
+        assert_eq!(
+            read_point::<E::G1Affine>(&mut contributions, check).unwrap(),
+            contribution.delta_after
+        );
+        assert_eq!(
+            read_point::<E::G1Affine>(&mut contributions, check).unwrap(),
+            contribution.s
+        );
+        assert_eq!(
+            read_point::<E::G1Affine>(&mut contributions, check).unwrap(),
+            contribution.s_delta
+        );
+        assert_eq!(
+            read_point::<E::G2Affine>(&mut contributions, check).unwrap(),
+            contribution.r_delta
+        );
+        assert_eq!(take(&mut contributions, 64).unwrap(), &contribution.transcript[..]);
+        assert_eq!(read_u32(&mut contributions).unwrap(), 0);
+        assert_eq!(read_u32(&mut contributions).unwrap(), 0);
+        assert!(contributions.is_empty());
+    }
+
+    #[test]
+    fn test_zkey_layout_bls12_377() {
+        zkey_layout_curve_test::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_zkey_layout_bls12_381() {
+        zkey_layout_curve_test::<Bls12_381>();
+    }
+
+    // the snarkjs prover multiplies the evaluations of A * B - C = h * Z on the coset
+    // with the H query, and must end up with h(tau) * Z(tau)
+    #[test]
+    fn test_h_query_to_lagrange() {
+        type Fr = <Bls12_377 as Pairing>::ScalarField;
+        type G1 = <Bls12_377 as Pairing>::G1;
+
+        let rng = &mut thread_rng();
+        let domain_size = 16;
+        let tau = Fr::rand(rng);
+        let generator = <Bls12_377 as Pairing>::G1Affine::generator();
+        let z_tau = tau.pow([domain_size as u64]) - Fr::from(1u64);
+        let h_query = (0..domain_size - 1)
+            .map(|j| (generator * (tau.pow([j as u64]) * z_tau)).into_affine())
+            .collect::<Vec<_>>();
+        let lagrange = h_query_to_lagrange::<Bls12_377>(&h_query).unwrap();
+        assert_eq!(lagrange.len(), domain_size);
+
+        let h = DensePolynomial::<Fr>::rand(domain_size - 2, rng);
+        let root = Fr::get_root_of_unity(2 * domain_size as u64).unwrap();
+        let mut result = G1::zero();
+        for (i, point) in lagrange.iter().enumerate() {
+            let coset_element = root.pow([2 * i as u64 + 1]);
+            // Z(c_i) = c_i^n - 1 = -2
+            result += *point * (-Fr::from(2u64) * h.evaluate(&coset_element));
+        }
+        assert_eq!(result, generator * (h.evaluate(&tau) * z_tau));
+    }
+
+    #[test]
+    fn test_zkey_rejects_non_radix2_h_query() {
+        let h_query = vec![<Bls12_377 as Pairing>::G1Affine::generator(); 4];
+        assert!(h_query_to_lagrange::<Bls12_377>(&h_query).is_err());
+    }
+}
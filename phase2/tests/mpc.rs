@@ -22,7 +22,7 @@ use phase2::{
     parameters::{circuit_to_qap, MPCParameters, Phase2ContributionMode},
 };
 use rand::{thread_rng, Rng};
-use setup_utils::{derive_rng_from_seed, BatchExpMode, Groth16Params, UseCompression};
+use setup_utils::{derive_rng_from_seed, BatchExpMode, Groth16Params, SubgroupCheckMode, UseCompression};
 
 fn generate_mpc_parameters<E, C>(c: C, rng: &mut impl Rng) -> MPCParameters<E>
 where
@@ -203,6 +203,7 @@ where
         3,
         UseCompression::Yes,
         CheckForCorrectness::Full,
+        SubgroupCheckMode::Auto,
     )
     .unwrap();
 
@@ -0,0 +1,298 @@
+use crate::{Error, Result};
+
+use ark_std::cfg_into_iter;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{fs, path::Path};
+use tracing::info;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The beacon is hashed 2^42 times by default, which takes days, so that it cannot be predicted
+/// in time to bias the contribution
+pub const DEFAULT_BEACON_ITERATIONS_EXP: u32 = 42;
+
+/// The fewest iterations a beacon contribution is accepted with, unless low iterations are allowed
+/// explicitly, e.g. for tests
+pub const MIN_BEACON_ITERATIONS_EXP: u32 = 40;
+
+/// The most intermediate states of the beacon which are recorded, so that the work can be
+/// verified in as many parts in parallel
+const MAX_BEACON_CHECKPOINTS_EXP: u32 = 10;
+
+/// The iterated SHA-256 work over a random beacon (e.g. a bitcoin block header hash), whose result
+/// seeds a beacon contribution. The intermediate states are recorded at regular intervals, so that
+/// the work can be re-checked in parallel with `verify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeaconCheckpoints {
+    pub beacon_hash: [u8; 32],
+    /// The beacon is hashed `2^iterations_exp` times
+    pub iterations_exp: u32,
+    /// The states at the start of every interval, starting with the beacon hash itself
+    pub checkpoints: Vec<[u8; 32]>,
+    /// The state after all the iterations
+    pub result: [u8; 32],
+}
+
+/// The checkpoints as they're written to a file, in hex
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BeaconCheckpointsFile {
+    beacon_hash: String,
+    iterations_exp: u32,
+    checkpoints: Vec<String>,
+    result: String,
+}
+
+impl BeaconCheckpoints {
+    /// Hashes the beacon `2^iterations_exp` times, recording up to 1024 intermediate states. The
+    /// states are printed as they're computed, so that the progress can be followed and published
+    /// before the work is over.
+    pub fn compute(beacon_hash: [u8; 32], iterations_exp: u32) -> Result<Self> {
+        let interval = checkpoint_interval(iterations_exp)?;
+        let num_checkpoints = 1usize << iterations_exp.min(MAX_BEACON_CHECKPOINTS_EXP);
+
+        let mut checkpoints = Vec::with_capacity(num_checkpoints);
+        let mut state = beacon_hash;
+        for i in 0..num_checkpoints {
+            println!("{}: {}", i as u64 * interval, to_hex(&state));
+            checkpoints.push(state);
+            state = iterate_sha256(state, interval);
+        }
+        println!("Final result of beacon: {}", to_hex(&state));
+
+        Ok(Self {
+            beacon_hash,
+            iterations_exp,
+            checkpoints,
+            result: state,
+        })
+    }
+
+    /// Checks that the checkpoints were computed for the expected beacon and number of iterations,
+    /// since checkpoints which are consistent for another beacon, or for fewer iterations, would
+    /// still pass `verify`. Fewer than `2^MIN_BEACON_ITERATIONS_EXP` iterations are rejected unless
+    /// `allow_low_iterations` is set.
+    pub fn check_beacon(&self, beacon_hash: [u8; 32], iterations_exp: u32, allow_low_iterations: bool) -> Result<()> {
+        if (self.beacon_hash, self.iterations_exp) != (beacon_hash, iterations_exp) {
+            return Err(Error::InvalidBeacon(format!(
+                "the checkpoints are the ones of beacon {} over 2^{} iterations, expected beacon {} over 2^{} iterations",
+                to_hex(&self.beacon_hash),
+                self.iterations_exp,
+                to_hex(&beacon_hash),
+                iterations_exp
+            )));
+        }
+        if iterations_exp < MIN_BEACON_ITERATIONS_EXP && !allow_low_iterations {
+            return Err(Error::InvalidBeacon(format!(
+                "the beacon should be hashed at least 2^{} times, got 2^{}",
+                MIN_BEACON_ITERATIONS_EXP, iterations_exp
+            )));
+        }
+        Ok(())
+    }
+
+    /// Re-computes every interval between two checkpoints, in parallel, and checks that it leads
+    /// to the next checkpoint, or to the result for the last one
+    pub fn verify(&self) -> Result<()> {
+        let interval = checkpoint_interval(self.iterations_exp)?;
+        let num_checkpoints = 1usize << self.iterations_exp.min(MAX_BEACON_CHECKPOINTS_EXP);
+        if self.checkpoints.len() != num_checkpoints {
+            return Err(Error::InvalidBeacon(format!(
+                "expected {} checkpoints for 2^{} iterations, got {}",
+                num_checkpoints,
+                self.iterations_exp,
+                self.checkpoints.len()
+            )));
+        }
+        if self.checkpoints[0] != self.beacon_hash {
+            return Err(Error::InvalidBeacon(
+                "the first checkpoint is not the beacon hash".to_string(),
+            ));
+        }
+
+        let invalid = cfg_into_iter!(0..num_checkpoints)
+            .filter(|&i| {
+                let next = self.checkpoints.get(i + 1).unwrap_or(&self.result);
+                iterate_sha256(self.checkpoints[i], interval) != *next
+            })
+            .min();
+        match invalid {
+            Some(i) => Err(Error::InvalidBeacon(format!(
+                "checkpoint {} does not follow from checkpoint {}",
+                i + 1,
+                i
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads the checkpoints written by `write`
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let invalid = |reason: String| Error::InvalidBeacon(format!("{}: {}", path.display(), reason));
+        let file: BeaconCheckpointsFile =
+            serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))?;
+        let decode =
+            |hash: &str| from_hex(hash).ok_or_else(|| invalid(format!("{} is not a 32 bytes hex string", hash)));
+        Ok(Self {
+            beacon_hash: decode(&file.beacon_hash)?,
+            iterations_exp: file.iterations_exp,
+            checkpoints: file
+                .checkpoints
+                .iter()
+                .map(|checkpoint| decode(checkpoint))
+                .collect::<Result<_>>()?,
+            result: decode(&file.result)?,
+        })
+    }
+
+    /// Writes the checkpoints as JSON, with the hashes in hex
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = BeaconCheckpointsFile {
+            beacon_hash: to_hex(&self.beacon_hash),
+            iterations_exp: self.iterations_exp,
+            checkpoints: self.checkpoints.iter().map(|checkpoint| to_hex(checkpoint)).collect(),
+            result: to_hex(&self.result),
+        };
+        let contents = serde_json::to_string_pretty(&file).expect("beacon checkpoints can always be encoded");
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Reads the checkpoints from `path` if it exists, e.g. after the beacon contribution to
+    /// another chunk, or computes and writes them otherwise. Existing checkpoints must be for the
+    /// same beacon and number of iterations, and are verified before their result is used.
+    pub fn read_or_compute(path: impl AsRef<Path>, beacon_hash: [u8; 32], iterations_exp: u32) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            let checkpoints = Self::read(path)?;
+            if (checkpoints.beacon_hash, checkpoints.iterations_exp) != (beacon_hash, iterations_exp) {
+                return Err(Error::InvalidArgument(format!(
+                    "{} holds the checkpoints of beacon {} over 2^{} iterations, expected beacon {} over 2^{} iterations",
+                    path.display(),
+                    to_hex(&checkpoints.beacon_hash),
+                    checkpoints.iterations_exp,
+                    to_hex(&beacon_hash),
+                    iterations_exp
+                )));
+            }
+            checkpoints.verify()?;
+            info!(
+                "Using the beacon result in {}: {}",
+                path.display(),
+                to_hex(&checkpoints.result)
+            );
+            return Ok(checkpoints);
+        }
+        let checkpoints = Self::compute(beacon_hash, iterations_exp)?;
+        checkpoints.write(path)?;
+        Ok(checkpoints)
+    }
+}
+
+/// Parses a beacon hash given in hex
+pub fn beacon_hash_from_str(src: &str) -> Result<[u8; 32]> {
+    if src.len() != 64 {
+        return Err(Error::InvalidHash(format!(
+            "the beacon hash should be 32 bytes long, but {} is {} characters long",
+            src,
+            src.len()
+        )));
+    }
+    from_hex(src).ok_or_else(|| Error::InvalidHash(format!("the beacon hash {} is not a hex string", src)))
+}
+
+/// The number of iterations between two checkpoints
+fn checkpoint_interval(iterations_exp: u32) -> Result<u64> {
+    if iterations_exp >= u64::BITS {
+        return Err(Error::InvalidArgument(format!(
+            "the beacon can be hashed at most 2^{} times, got 2^{}",
+            u64::BITS - 1,
+            iterations_exp
+        )));
+    }
+    Ok(1u64 << iterations_exp.saturating_sub(MAX_BEACON_CHECKPOINTS_EXP))
+}
+
+fn iterate_sha256(mut state: [u8; 32], iterations: u64) -> [u8; 32] {
+    for _ in 0..iterations {
+        state = Sha256::digest(state).into();
+    }
+    state
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(src: &str) -> Option<[u8; 32]> {
+    if src.len() != 64 || !src.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0; 32];
+    for (byte, digits) in bytes.iter_mut().zip(src.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beacon_checkpoints() {
+        let beacon_hash =
+            beacon_hash_from_str("0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620").unwrap();
+        let checkpoints = BeaconCheckpoints::compute(beacon_hash, 12).unwrap();
+        assert_eq!(checkpoints.checkpoints.len(), 1024);
+        assert_eq!(checkpoints.result, iterate_sha256(beacon_hash, 1 << 12));
+        checkpoints.verify().unwrap();
+
+        let path = std::env::temp_dir().join(format!("beacon-checkpoints-{}.json", std::process::id()));
+        checkpoints.write(&path).unwrap();
+        assert_eq!(BeaconCheckpoints::read(&path).unwrap(), checkpoints);
+        assert_eq!(
+            BeaconCheckpoints::read_or_compute(&path, beacon_hash, 12).unwrap(),
+            checkpoints
+        );
+        let err = BeaconCheckpoints::read_or_compute(&path, beacon_hash, 13).unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)), "{}", err);
+        // existing checkpoints are verified before they're reused
+        let mut tampered = checkpoints.clone();
+        tampered.result[0] ^= 1;
+        tampered.write(&path).unwrap();
+        let err = BeaconCheckpoints::read_or_compute(&path, beacon_hash, 12).unwrap_err();
+        assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+        fs::remove_file(&path).unwrap();
+
+        // the checkpoints must be the ones of the expected beacon and number of iterations
+        checkpoints.check_beacon(beacon_hash, 12, true).unwrap();
+        let err = checkpoints.check_beacon(beacon_hash, 12, false).unwrap_err();
+        assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+        let err = checkpoints.check_beacon([0; 32], 12, true).unwrap_err();
+        assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+        let err = checkpoints.check_beacon(beacon_hash, 13, true).unwrap_err();
+        assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+
+        // fewer iterations than checkpoints
+        let small = BeaconCheckpoints::compute(beacon_hash, 3).unwrap();
+        assert_eq!(small.checkpoints.len(), 8);
+        assert_eq!(small.result, iterate_sha256(beacon_hash, 8));
+        small.verify().unwrap();
+
+        let mut tampered = checkpoints.clone();
+        tampered.checkpoints[500][0] ^= 1;
+        let err = tampered.verify().unwrap_err();
+        assert!(matches!(err, Error::InvalidBeacon(_)), "{}", err);
+        assert!(err.to_string().contains("checkpoint 500 does not follow"), "{}", err);
+        let mut tampered = checkpoints;
+        tampered.result[0] ^= 1;
+        tampered.verify().unwrap_err();
+
+        beacon_hash_from_str("00").unwrap_err();
+        beacon_hash_from_str(&"zz".repeat(32)).unwrap_err();
+    }
+}
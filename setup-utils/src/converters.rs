@@ -14,9 +14,38 @@ pub enum ProvingSystem {
     Marlin,
 }
 
+impl Display for ProvingSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProvingSystem::Groth16 => write!(f, "groth16"),
+            ProvingSystem::Marlin => write!(f, "marlin"),
+        }
+    }
+}
+
+/// The formats the universal parameters of KZG10 can be exported in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SrsFormat {
+    /// The serialization of `ark_poly_commit::kzg10::UniversalParams`
+    Arkworks,
+    /// The powers of tau in G1 followed by `h` and `beta_h`, without lengths
+    Raw,
+}
+
+impl Display for SrsFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SrsFormat::Arkworks => write!(f, "arkworks"),
+            SrsFormat::Raw => write!(f, "raw"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum CurveKind {
     Bls12_377,
+    Bls12_381,
+    Bn254,
     BW6,
     MNT4_753,
     MNT6_753,
@@ -26,6 +55,8 @@ impl Display for CurveKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             CurveKind::Bls12_377 => write!(f, "bls12_377"),
+            CurveKind::Bls12_381 => write!(f, "bls12_381"),
+            CurveKind::Bn254 => write!(f, "bn254"),
             CurveKind::BW6 => write!(f, "bw6"),
             CurveKind::MNT4_753 => write!(f, "mnt4_753"),
             CurveKind::MNT6_753 => write!(f, "mnt6_753"),
@@ -36,6 +67,8 @@ impl Display for CurveKind {
 pub fn curve_from_str(src: &str) -> Result<CurveKind, String> {
     let curve = match src.to_lowercase().as_str() {
         "bls12_377" => CurveKind::Bls12_377,
+        "bls12_381" => CurveKind::Bls12_381,
+        "bn254" => CurveKind::Bn254,
         "bw6" => CurveKind::BW6,
         "mnt4_753" => CurveKind::MNT4_753,
         "mnt6_753" => CurveKind::MNT6_753,
@@ -62,6 +95,15 @@ pub fn proving_system_from_str(src: &str) -> Result<ProvingSystem, String> {
     Ok(system)
 }
 
+pub fn srs_format_from_str(src: &str) -> Result<SrsFormat, String> {
+    let format = match src.to_lowercase().as_str() {
+        "arkworks" => SrsFormat::Arkworks,
+        "raw" => SrsFormat::Raw,
+        _ => return Err("unsupported SRS format. Currently supported: arkworks, raw".to_string()),
+    };
+    Ok(format)
+}
+
 pub fn batch_exp_mode_from_str(src: &str) -> Result<BatchExpMode, String> {
     let batch_exp_mode = match src.to_lowercase().as_str() {
         "auto" => BatchExpMode::Auto,
@@ -3,6 +3,7 @@ use ark_ec::AffineRepr;
 use ark_ff::PrimeField;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Validate, Write};
 use ark_std::{cfg_iter, Zero};
+use serde::Serialize;
 
 #[cfg(not(feature = "wasm"))]
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
@@ -42,7 +43,7 @@ impl From<CheckForCorrectness> for Validate {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
 pub enum ElementType {
     TauG1,
     TauG2,
@@ -2,6 +2,7 @@ use crate::ElementType;
 
 use ark_relations::r1cs::SynthesisError;
 use ark_serialize::SerializationError;
+use serde::Serialize;
 
 use std::io;
 use thiserror::Error;
@@ -35,6 +36,164 @@ pub enum Error {
     InvalidDecompressionParametersError,
     #[error("Batch was too small to support ratio checks")]
     BatchTooSmall,
+    #[error("Invalid snarkjs file: {0}")]
+    InvalidSnarkjsFile(String),
+    #[error("The partial output does not match the checkpoint after {0} batches")]
+    CheckpointMismatch(usize),
+    #[error("The size of {file} should be {expected}, but it's {got}")]
+    InvalidFileSize { file: String, expected: u64, got: u64 },
+    #[error("Hash chain failure: {0} was not based on the expected challenge")]
+    HashChainMismatch(String),
+    #[error("Invalid hash: {0}")]
+    InvalidHash(String),
+    #[error("Invalid seed: {0}")]
+    InvalidSeed(String),
+    #[error("Invalid response list: {0}")]
+    InvalidResponseList(String),
+    #[error("Cannot resume: {0}")]
+    CannotResume(String),
+    #[error("Unsupported curve: {0}")]
+    UnsupportedCurve(String),
+    #[error("Coordinator error: {0}")]
+    CoordinatorError(String),
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(String),
+    #[error("Invalid file header: {0}")]
+    InvalidFileHeader(String),
+    #[error("File header mismatch: {0}")]
+    FileHeaderMismatch(String),
+    #[error("Unrecognized file: {0}")]
+    UnrecognizedFile(String),
+    #[error("Unsupported proving system: {0}")]
+    UnsupportedProvingSystem(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Invalid beacon: {0}")]
+    InvalidBeacon(String),
+}
+
+/// The exit codes of the binaries, by kind of error
+impl Error {
+    pub const EXIT_CODE_ARGS: i32 = 2;
+    pub const EXIT_CODE_FILE_SIZE: i32 = 4;
+    pub const EXIT_CODE_HASH: i32 = 6;
+    pub const EXIT_CODE_IO: i32 = 3;
+    pub const EXIT_CODE_MALFORMED_INPUT: i32 = 5;
+    pub const EXIT_CODE_OTHER: i32 = 1;
+    pub const EXIT_CODE_RESPONSE_LIST: i32 = 7;
+    pub const EXIT_CODE_RESUME: i32 = 9;
+    pub const EXIT_CODE_VERIFICATION: i32 = 8;
+
+    /// Returns the exit code a binary should terminate with after this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::IoError(_) => Self::EXIT_CODE_IO,
+            Error::InvalidFileSize { .. } => Self::EXIT_CODE_FILE_SIZE,
+            Error::ZexeSerializationError(_)
+            | Error::InvalidLength { .. }
+            | Error::InvalidDecompressionParametersError
+            | Error::InvalidSnarkjsFile(_)
+            | Error::InvalidSeed(_)
+            | Error::InvalidFileHeader(_)
+            | Error::UnrecognizedFile(_) => Self::EXIT_CODE_MALFORMED_INPUT,
+            Error::HashChainMismatch(_) | Error::InvalidHash(_) => Self::EXIT_CODE_HASH,
+            Error::InvalidResponseList(_) => Self::EXIT_CODE_RESPONSE_LIST,
+            Error::PointAtInfinity
+            | Error::IncorrectSubgroup
+            | Error::VerificationError(_)
+            | Error::Phase2Error(_)
+            | Error::BatchTooSmall
+            | Error::InvalidBeacon(_) => Self::EXIT_CODE_VERIFICATION,
+            Error::CheckpointMismatch(_) | Error::CannotResume(_) => Self::EXIT_CODE_RESUME,
+            Error::UnsupportedCurve(_)
+            | Error::UnsupportedProvingSystem(_)
+            | Error::InvalidArgument(_)
+            | Error::InvalidManifest(_)
+            | Error::FileHeaderMismatch(_) => Self::EXIT_CODE_ARGS,
+            Error::PositionError(..)
+            | Error::InvalidChunk
+            | Error::SynthesisError(_)
+            | Error::CrossBeamError
+            | Error::CoordinatorError(_) => Self::EXIT_CODE_OTHER,
+        }
+    }
+
+    /// Records the type of the elements being verified when this error occurred
+    pub fn with_element_type(self, element_type: ElementType) -> Self {
+        self.with_context(|context| context.element_type = context.element_type.or(Some(element_type)))
+    }
+
+    /// Records the batch of elements being verified when this error occurred
+    pub fn with_batch(self, start: usize, end: usize) -> Self {
+        self.with_context(|context| context.batch = context.batch.or(Some((start, end))))
+    }
+
+    /// Records the chunk being verified when this error occurred
+    pub fn with_chunk_index(self, chunk_index: usize) -> Self {
+        self.with_context(|context| context.chunk_index = context.chunk_index.or(Some(chunk_index)))
+    }
+
+    /// Records the contribution being verified when this error occurred
+    pub fn with_contribution_index(self, contribution_index: usize) -> Self {
+        self.with_context(|context| {
+            context.contribution_index = context.contribution_index.or(Some(contribution_index))
+        })
+    }
+
+    /// Updates the context of a verification failure. Errors caused by invalid
+    /// elements are turned into `VerificationError::InvalidElements` so that
+    /// they can carry a context, and every other error is returned unchanged.
+    fn with_context(self, update: impl FnOnce(&mut VerificationContext)) -> Self {
+        let mut err = match self {
+            Error::PointAtInfinity
+            | Error::IncorrectSubgroup
+            | Error::InvalidDecompressionParametersError
+            | Error::ZexeSerializationError(_) => VerificationError::InvalidElements {
+                reason: self.to_string(),
+                context: VerificationContext::default(),
+            },
+            Error::VerificationError(err) => err,
+            _ => return self,
+        };
+        match &mut err {
+            VerificationError::InvalidRatio { context, .. } | VerificationError::InvalidElements { context, .. } => {
+                update(context)
+            }
+            VerificationError::InvalidGenerator(_)
+            | VerificationError::HashChainFailure(_)
+            | VerificationError::InvalidInitialChallenge => {}
+        }
+        Error::VerificationError(err)
+    }
+
+    /// Returns the report a binary writes after failing with this error
+    pub fn report(&self) -> FailureReport<'_> {
+        FailureReport {
+            error: self.to_string(),
+            exit_code: self.exit_code(),
+            verification: match self {
+                Error::VerificationError(err) => Some(err),
+                _ => None,
+            },
+            phase2: match self {
+                Error::Phase2Error(err) => Some(err),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// A machine readable description of why a command failed
+#[derive(Debug, Serialize)]
+pub struct FailureReport<'a> {
+    /// The error message
+    pub error: String,
+    /// The exit code of the binary
+    pub exit_code: i32,
+    /// The structured verification failure, if verification failed
+    pub verification: Option<&'a VerificationError>,
+    /// The broken Phase 2 invariant, if any
+    pub phase2: Option<&'a Phase2Error>,
 }
 
 impl From<Box<dyn std::any::Any + Send>> for Error {
@@ -43,7 +202,7 @@ impl From<Box<dyn std::any::Any + Send>> for Error {
     }
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Serialize)]
 pub enum Phase2Error {
     #[error("Parameter should not change: {0}")]
     BrokenInvariant(InvariantKind),
@@ -55,7 +214,7 @@ pub enum Phase2Error {
     InvalidTranscript,
 }
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize)]
 pub enum InvariantKind {
     Contributions,
     CsHash,
@@ -65,6 +224,7 @@ pub enum InvariantKind {
     GammaAbcG1,
     GammaG2,
     DeltaG1,
+    DeltaG2,
     Transcript,
     AlphaG1Query,
     BetaG1Query,
@@ -83,6 +243,7 @@ impl fmt::Display for InvariantKind {
             InvariantKind::GammaAbcG1 => write!(f, "GammaAbcG1"),
             InvariantKind::GammaG2 => write!(f, "GammaG2"),
             InvariantKind::DeltaG1 => write!(f, "DeltaG1"),
+            InvariantKind::DeltaG2 => write!(f, "DeltaG2"),
             InvariantKind::Transcript => write!(f, "Transcript"),
             InvariantKind::AlphaG1Query => write!(f, "AlphaG1Query"),
             InvariantKind::BetaG1Query => write!(f, "BetaG1Query"),
@@ -91,14 +252,114 @@ impl fmt::Display for InvariantKind {
     }
 }
 
-// todo: make this more detailed so that we can know which
-// exact pairing ratio check failed
-#[derive(Debug, Error)]
+/// The pairing equation of a failed ratio check
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub enum RatioCheck {
+    /// The proof of knowledge of a contribution's secret
+    ProofOfKnowledge,
+    /// The first elements were multiplied by a contribution's secret
+    BeforeAfter,
+    /// Consecutive elements are successive powers of tau
+    PowerPairs,
+    /// The Marlin powers of tau in G1 and G2 match
+    PowersOfTwoG1G2,
+    /// Consecutive Marlin alpha powers are successive powers of tau
+    AlphaPowers,
+    /// The Marlin alpha powers are consistent with alpha_g1
+    AlphaConsistency,
+    /// Delta in G1 and G2 match
+    DeltaG1G2,
+    /// The H query was updated with the inverse of delta
+    HQuery,
+    /// The L query was updated with the inverse of delta
+    LQuery,
+    /// The signature of knowledge of a Phase 2 contribution
+    SignatureOfKnowledge,
+    /// Delta in G1 was updated by a Phase 2 contribution
+    DeltaUpdate,
+}
+
+impl fmt::Display for RatioCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RatioCheck::ProofOfKnowledge => write!(f, "proof of knowledge"),
+            RatioCheck::BeforeAfter => write!(f, "before-after"),
+            RatioCheck::PowerPairs => write!(f, "power pairs"),
+            RatioCheck::PowersOfTwoG1G2 => write!(f, "powers of two G1<>G2"),
+            RatioCheck::AlphaPowers => write!(f, "alpha powers"),
+            RatioCheck::AlphaConsistency => write!(f, "alpha consistency"),
+            RatioCheck::DeltaG1G2 => write!(f, "delta G1<>G2"),
+            RatioCheck::HQuery => write!(f, "H query"),
+            RatioCheck::LQuery => write!(f, "L query"),
+            RatioCheck::SignatureOfKnowledge => write!(f, "signature of knowledge"),
+            RatioCheck::DeltaUpdate => write!(f, "delta update"),
+        }
+    }
+}
+
+/// Where a verification failure occurred. Every field is optional, since
+/// each check only knows part of it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct VerificationContext {
+    /// The type of the elements which failed the check
+    pub element_type: Option<ElementType>,
+    /// The start and end indices of the batch, as given by `iter_chunk`
+    pub batch: Option<(usize, usize)>,
+    /// The index of the chunk
+    pub chunk_index: Option<usize>,
+    /// The index of the contribution in a transcript
+    pub contribution_index: Option<usize>,
+}
+
+impl fmt::Display for VerificationContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(element_type) = self.element_type {
+            write!(f, " of {}", element_type)?;
+        }
+        if let Some((start, end)) = self.batch {
+            write!(f, " in batch {}..{}", start, end)?;
+        }
+        if let Some(chunk_index) = self.chunk_index {
+            write!(f, " of chunk {}", chunk_index)?;
+        }
+        if let Some(contribution_index) = self.contribution_index {
+            write!(f, " of contribution {}", contribution_index)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Serialize)]
 pub enum VerificationError {
-    #[error("Invalid ratio! Context: {0}")]
+    #[error("Invalid ratio in the {check} check{context}")]
     /// The ratio check via the pairing of the provided elements failed
-    InvalidRatio(String),
+    InvalidRatio {
+        check: RatioCheck,
+        context: VerificationContext,
+    },
+    #[error("Invalid elements{context}: {reason}")]
+    /// The elements could not be read, were zero or were not in the prime order subgroup
+    InvalidElements {
+        reason: String,
+        context: VerificationContext,
+    },
     #[error("Invalid generator for {0} powers")]
     /// The first power of Tau was not the generator of that group
     InvalidGenerator(ElementType),
+    #[error("Hash chain failure at transcript entry {0}")]
+    /// The contribution was not based on the challenge produced by the previous one
+    HashChainFailure(usize),
+    #[error("The initial challenge is not the accumulator created by `new`")]
+    /// The first challenge of a ceremony did not start from the generators
+    InvalidInitialChallenge,
+}
+
+impl VerificationError {
+    /// Creates a failed ratio check without any context
+    pub fn invalid_ratio(check: RatioCheck) -> Self {
+        VerificationError::InvalidRatio {
+            check,
+            context: VerificationContext::default(),
+        }
+    }
 }
@@ -0,0 +1,398 @@
+use crate::{
+    calculate_hash,
+    converters::{ContributionMode, ProvingSystem},
+    Error,
+    Result,
+    UseCompression,
+};
+
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+
+use std::io::{self, Cursor, Read};
+
+/// The version of the file header format this code reads and writes
+pub const FILE_HEADER_VERSION: u16 = 1;
+
+/// The size of a file header, which is prepended to the contents of a file
+pub const FILE_HEADER_SIZE: usize = 64;
+
+/// Marks the beginning of a file header. Headerless files start with a hash or a group element,
+/// which are practically never equal to it.
+const FILE_HEADER_MAGIC: &[u8; 8] = b"SNKSETUP";
+
+/// The optional header of challenges, responses and Phase 2 parameters, describing the ceremony
+/// they belong to so that they can't be mixed up with the files of another ceremony. It is laid
+/// out as follows, with integers in little endian:
+///
+/// | Bytes  | Contents                                         |
+/// |--------|--------------------------------------------------|
+/// | 0..8   | the magic `SNKSETUP`                             |
+/// | 8..10  | the version of the format                        |
+/// | 10..18 | the identifier of the curve, see [`curve_id`]    |
+/// | 18     | the proving system, 0 for Groth16, 1 for Marlin  |
+/// | 19     | the contribution mode, 0 for full, 1 for chunked |
+/// | 20     | 1 if the elements are compressed, 0 otherwise    |
+/// | 24..28 | the power                                        |
+/// | 28..36 | the chunk index                                  |
+/// | 36..44 | the chunk size                                   |
+///
+/// The other bytes are reserved and must be zero. Hashes of files only cover the contents after
+/// the header, so adding a header doesn't break the hash chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHeader {
+    pub version: u16,
+    pub curve_id: [u8; 8],
+    pub proving_system: ProvingSystem,
+    pub contribution_mode: ContributionMode,
+    /// Whether the elements are compressed
+    pub compressed: bool,
+    pub power: u32,
+    pub chunk_index: u64,
+    pub chunk_size: u64,
+}
+
+/// Identifies a curve by the first 8 bytes of the BLAKE2b hash of the moduli of its scalar and
+/// base fields
+pub fn curve_id<E: Pairing>() -> [u8; 8] {
+    let mut moduli = E::ScalarField::MODULUS.to_bytes_le();
+    moduli.extend(E::BaseField::MODULUS.to_bytes_le());
+    let mut id = [0; 8];
+    id.copy_from_slice(&calculate_hash(&moduli)[..8]);
+    id
+}
+
+/// Splits the contents of a file into its header, if it has one, and the rest of the file
+pub fn split_file_header(contents: &[u8]) -> Result<(Option<FileHeader>, &[u8])> {
+    match FileHeader::from_bytes(contents)? {
+        Some(header) => Ok((Some(header), &contents[FILE_HEADER_SIZE..])),
+        None => Ok((None, contents)),
+    }
+}
+
+/// The number of bytes taken by the header of a file, if it has one
+pub fn header_length(header: Option<&FileHeader>) -> usize {
+    header.map_or(0, |_| FILE_HEADER_SIZE)
+}
+
+/// Reads the header of a file, if it has one, and returns a reader of the rest of the file
+pub fn read_file_header<R: Read>(mut reader: R) -> Result<(Option<FileHeader>, io::Chain<Cursor<Vec<u8>>, R>)> {
+    let mut prefix = Vec::with_capacity(FILE_HEADER_SIZE);
+    (&mut reader).take(FILE_HEADER_SIZE as u64).read_to_end(&mut prefix)?;
+    let header = FileHeader::from_bytes(&prefix)?;
+    if header.is_some() {
+        prefix.clear();
+    }
+    Ok((header, Cursor::new(prefix).chain(reader)))
+}
+
+impl FileHeader {
+    /// Describes a file of the ceremony on the curve `E`
+    pub fn new<E: Pairing>(
+        proving_system: ProvingSystem,
+        contribution_mode: ContributionMode,
+        compressed: UseCompression,
+        power: usize,
+        chunk_index: usize,
+        chunk_size: usize,
+    ) -> Self {
+        Self {
+            version: FILE_HEADER_VERSION,
+            curve_id: curve_id::<E>(),
+            proving_system,
+            contribution_mode,
+            compressed: compressed == UseCompression::Yes,
+            power: power as u32,
+            chunk_index: chunk_index as u64,
+            chunk_size: chunk_size as u64,
+        }
+    }
+
+    /// Describes the same file with another compression
+    pub fn with_compression(self, compressed: UseCompression) -> Self {
+        Self {
+            compressed: compressed == UseCompression::Yes,
+            ..self
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; FILE_HEADER_SIZE] {
+        let mut bytes = [0; FILE_HEADER_SIZE];
+        bytes[0..8].copy_from_slice(FILE_HEADER_MAGIC);
+        bytes[8..10].copy_from_slice(&self.version.to_le_bytes());
+        bytes[10..18].copy_from_slice(&self.curve_id);
+        bytes[18] = match self.proving_system {
+            ProvingSystem::Groth16 => 0,
+            ProvingSystem::Marlin => 1,
+        };
+        bytes[19] = match self.contribution_mode {
+            ContributionMode::Full => 0,
+            ContributionMode::Chunked => 1,
+        };
+        bytes[20] = self.compressed as u8;
+        bytes[24..28].copy_from_slice(&self.power.to_le_bytes());
+        bytes[28..36].copy_from_slice(&self.chunk_index.to_le_bytes());
+        bytes[36..44].copy_from_slice(&self.chunk_size.to_le_bytes());
+        bytes
+    }
+
+    /// Parses the header at the beginning of `bytes`. Returns `None` for legacy files, which don't
+    /// start with the magic.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Option<Self>> {
+        if bytes.len() < FILE_HEADER_SIZE || &bytes[0..8] != FILE_HEADER_MAGIC {
+            return Ok(None);
+        }
+        let invalid = |message: String| Err(Error::InvalidFileHeader(message));
+
+        let version = u16::from_le_bytes([bytes[8], bytes[9]]);
+        if version != FILE_HEADER_VERSION {
+            return invalid(format!(
+                "version {} is not supported, expected {}",
+                version, FILE_HEADER_VERSION
+            ));
+        }
+        let proving_system = match bytes[18] {
+            0 => ProvingSystem::Groth16,
+            1 => ProvingSystem::Marlin,
+            other => return invalid(format!("unknown proving system {}", other)),
+        };
+        let contribution_mode = match bytes[19] {
+            0 => ContributionMode::Full,
+            1 => ContributionMode::Chunked,
+            other => return invalid(format!("unknown contribution mode {}", other)),
+        };
+        let compressed = match bytes[20] {
+            0 => false,
+            1 => true,
+            other => return invalid(format!("unknown compression flag {}", other)),
+        };
+        if bytes[21..24]
+            .iter()
+            .chain(&bytes[44..FILE_HEADER_SIZE])
+            .any(|b| *b != 0)
+        {
+            return invalid("the reserved bytes are not zero".to_string());
+        }
+
+        let mut curve_id = [0; 8];
+        curve_id.copy_from_slice(&bytes[10..18]);
+        let mut power = [0; 4];
+        power.copy_from_slice(&bytes[24..28]);
+        let mut chunk_index = [0; 8];
+        chunk_index.copy_from_slice(&bytes[28..36]);
+        let mut chunk_size = [0; 8];
+        chunk_size.copy_from_slice(&bytes[36..44]);
+
+        Ok(Some(Self {
+            version,
+            curve_id,
+            proving_system,
+            contribution_mode,
+            compressed,
+            power: u32::from_le_bytes(power),
+            chunk_index: u64::from_le_bytes(chunk_index),
+            chunk_size: u64::from_le_bytes(chunk_size),
+        }))
+    }
+
+    /// Checks that the file was written on the curve `E` with the given compression
+    pub fn check_curve_and_compression<E: Pairing>(&self, compressed: UseCompression) -> Result<()> {
+        if self.curve_id != curve_id::<E>() {
+            return Err(Error::FileHeaderMismatch(format!(
+                "the file was written for the curve {}, not the curve {}",
+                hex_id(&self.curve_id),
+                hex_id(&curve_id::<E>())
+            )));
+        }
+        let compressed = compressed == UseCompression::Yes;
+        if self.compressed != compressed {
+            return Err(Error::FileHeaderMismatch(format!(
+                "the file is {}, expected it to be {}",
+                compression_name(self.compressed),
+                compression_name(compressed)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that the file was written for the ceremony described by `expected`
+    pub fn check(&self, expected: &FileHeader) -> Result<()> {
+        let mismatch = |field: &str, got: String, expected: String| {
+            Err(Error::FileHeaderMismatch(format!(
+                "the file was written for {} {}, expected {}",
+                field, got, expected
+            )))
+        };
+        if self.curve_id != expected.curve_id {
+            return mismatch("the curve", hex_id(&self.curve_id), hex_id(&expected.curve_id));
+        }
+        if self.proving_system != expected.proving_system {
+            return mismatch(
+                "the proving system",
+                self.proving_system.to_string(),
+                expected.proving_system.to_string(),
+            );
+        }
+        if self.contribution_mode != expected.contribution_mode {
+            return mismatch(
+                "the contribution mode",
+                format!("{:?}", self.contribution_mode),
+                format!("{:?}", expected.contribution_mode),
+            );
+        }
+        if self.power != expected.power {
+            return mismatch("the power", self.power.to_string(), expected.power.to_string());
+        }
+        if self.chunk_index != expected.chunk_index {
+            return mismatch(
+                "the chunk index",
+                self.chunk_index.to_string(),
+                expected.chunk_index.to_string(),
+            );
+        }
+        if self.chunk_size != expected.chunk_size {
+            return mismatch(
+                "the chunk size",
+                self.chunk_size.to_string(),
+                expected.chunk_size.to_string(),
+            );
+        }
+        if self.compressed != expected.compressed {
+            return mismatch(
+                "the compression",
+                compression_name(self.compressed).to_string(),
+                compression_name(expected.compressed).to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn hex_id(id: &[u8; 8]) -> String {
+    id.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn compression_name(compressed: bool) -> &'static str {
+    if compressed {
+        "compressed"
+    } else {
+        "uncompressed"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+
+    fn header() -> FileHeader {
+        FileHeader::new::<Bls12_377>(
+            ProvingSystem::Groth16,
+            ContributionMode::Chunked,
+            UseCompression::No,
+            10,
+            3,
+            512,
+        )
+    }
+
+    #[test]
+    fn test_file_header_roundtrip() {
+        let header = header();
+        let bytes = header.to_bytes();
+        assert_eq!(FileHeader::from_bytes(&bytes).unwrap(), Some(header));
+
+        let mut contents = bytes.to_vec();
+        contents.extend_from_slice(b"contents");
+        let (read_header, rest) = split_file_header(&contents).unwrap();
+        assert_eq!(read_header, Some(header));
+        assert_eq!(rest, b"contents");
+
+        let (read_header, mut reader) = read_file_header(contents.as_slice()).unwrap();
+        let mut rest = vec![];
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(read_header, Some(header));
+        assert_eq!(rest, b"contents");
+    }
+
+    #[test]
+    fn test_legacy_files_have_no_header() {
+        let legacy = calculate_hash(b"previous response");
+        let (header, rest) = split_file_header(&legacy).unwrap();
+        assert_eq!(header, None);
+        assert_eq!(rest, legacy.as_slice());
+
+        // files shorter than a header are passed through as they are
+        let (header, mut reader) = read_file_header(&b"short"[..]).unwrap();
+        let mut rest = vec![];
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(header, None);
+        assert_eq!(rest, b"short");
+    }
+
+    #[test]
+    fn test_invalid_file_header() {
+        let mut bytes = header().to_bytes();
+        bytes[8] = 2;
+        let err = FileHeader::from_bytes(&bytes).unwrap_err();
+        assert!(matches!(err, Error::InvalidFileHeader(_)), "{}", err);
+        assert_eq!(err.exit_code(), Error::EXIT_CODE_MALFORMED_INPUT);
+
+        let mut bytes = header().to_bytes();
+        bytes[18] = 7;
+        assert!(matches!(
+            FileHeader::from_bytes(&bytes),
+            Err(Error::InvalidFileHeader(_))
+        ));
+
+        let mut bytes = header().to_bytes();
+        bytes[63] = 1;
+        assert!(matches!(
+            FileHeader::from_bytes(&bytes),
+            Err(Error::InvalidFileHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_file_header() {
+        let header = header();
+        assert!(header.check(&header).is_ok());
+        assert!(header
+            .check_curve_and_compression::<Bls12_377>(UseCompression::No)
+            .is_ok());
+
+        assert_ne!(curve_id::<Bls12_377>(), curve_id::<Bls12_381>());
+        assert_ne!(curve_id::<Bls12_381>(), curve_id::<Bn254>());
+        let mismatches = [
+            FileHeader {
+                curve_id: curve_id::<Bn254>(),
+                ..header
+            },
+            FileHeader {
+                proving_system: ProvingSystem::Marlin,
+                ..header
+            },
+            FileHeader { power: 11, ..header },
+            FileHeader {
+                chunk_index: 4,
+                ..header
+            },
+            FileHeader {
+                compressed: true,
+                ..header
+            },
+        ];
+        for other in mismatches {
+            let err = other.check(&header).unwrap_err();
+            assert!(matches!(err, Error::FileHeaderMismatch(_)), "{}", err);
+            assert_eq!(err.exit_code(), Error::EXIT_CODE_ARGS);
+        }
+        assert!(header.check_curve_and_compression::<Bn254>(UseCompression::No).is_err());
+        assert!(header
+            .check_curve_and_compression::<Bls12_377>(UseCompression::Yes)
+            .is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     elements::CheckForCorrectness,
-    errors::{Error, VerificationError},
+    errors::{Error, RatioCheck, VerificationError},
     Result,
 };
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, Group, VariableBaseMSM};
@@ -20,9 +20,6 @@ use std::{
 use tracing::{error, info};
 use typenum::consts::U64;
 
-#[cfg(not(feature = "wasm"))]
-use sha2::Sha256;
-
 use crate::elements::BatchExpMode;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -37,8 +34,12 @@ pub fn generate_powers_of_tau<E: Pairing>(tau: &E::ScalarField, start: usize, en
 }
 
 pub fn print_hash(hash: &[u8]) {
+    info!("\n{}", format_hash(hash));
+}
+
+/// Formats a hash the way `print_hash` logs it, in lines of 16 bytes
+pub fn format_hash(hash: &[u8]) -> String {
     let mut hash_str = String::new();
-    hash_str.push_str("\n");
     for line in hash.chunks(16) {
         hash_str.push_str("\t");
         for section in line.chunks(4) {
@@ -49,7 +50,7 @@ pub fn print_hash(hash: &[u8]) {
         }
         hash_str.push_str("\n");
     }
-    info!("{}", hash_str);
+    hash_str
 }
 
 /// Multiply a large number of points by a scalar
@@ -163,38 +164,14 @@ pub fn user_system_randomness() -> Vec<u8> {
     arr.to_vec()
 }
 
-#[allow(clippy::modulo_one)]
+/// Hashes the beacon 2^42 times, logging 1024 of the intermediate states so that the work can be
+/// verified in parallel. See `BeaconCheckpoints` to choose the number of iterations and keep the
+/// intermediate states.
 #[cfg(not(feature = "wasm"))]
-pub fn beacon_randomness_sha256_work(mut beacon_hash: [u8; 32]) -> [u8; 32] {
-    // Performs 2^n hash iterations over it
-    const N: u64 = 42;
-
-    for i in 0..(1u64 << N) {
-        // Print 1024 of the interstitial states
-        // so that verification can be
-        // parallelized
-
-        if i % (1u64 << (N - 10)) == 0 {
-            print!("{}: ", i);
-            for b in beacon_hash.iter() {
-                print!("{:02x}", b);
-            }
-            println!();
-        }
-
-        let mut h = Sha256::new();
-        h.update(&beacon_hash);
-        let result = h.finalize();
-        (&mut beacon_hash).copy_from_slice(&result);
-    }
-
-    print!("Final result of beacon: ");
-    for b in beacon_hash.iter() {
-        print!("{:02x}", b);
-    }
-    println!();
-
-    beacon_hash
+pub fn beacon_randomness_sha256_work(beacon_hash: [u8; 32]) -> [u8; 32] {
+    crate::BeaconCheckpoints::compute(beacon_hash, crate::DEFAULT_BEACON_ITERATIONS_EXP)
+        .expect("2^42 iterations are supported")
+        .result
 }
 
 /// Interpret the first 32 bytes of the digest as 8 32-bit words
@@ -302,11 +279,13 @@ mod tests {
     use super::*;
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine};
+    use ark_bn254::Bn254;
 
     #[test]
     fn test_hash_to_g2() {
         test_hash_to_g2_curve::<Bls12_381>();
         test_hash_to_g2_curve::<Bls12_377>();
+        test_hash_to_g2_curve::<Bn254>();
     }
 
     fn test_hash_to_g2_curve<E: Pairing>() {
@@ -331,6 +310,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compute_g2_s() {
+        test_compute_g2_s_curve::<Bls12_381>();
+        test_compute_g2_s_curve::<Bn254>();
+    }
+
+    fn test_compute_g2_s_curve<E: Pairing>() {
+        let rng = &mut thread_rng();
+        let digest = blank_hash();
+        let x = E::ScalarField::rand(rng);
+        let g1_s = E::G1::rand(rng).into_affine();
+        let g1_s_x = g1_s.mul(x).into_affine();
+
+        let g2_s = compute_g2_s::<E>(&digest, &g1_s, &g1_s_x, 0).unwrap();
+        assert_eq!(g2_s, compute_g2_s::<E>(&digest, &g1_s, &g1_s_x, 0).unwrap());
+        assert_ne!(g2_s, compute_g2_s::<E>(&digest, &g1_s, &g1_s_x, 1).unwrap());
+        // the point must be in the prime order subgroup, which is a proper subgroup of G2 for BN254
+        assert!(g2_s.mul_bigint(E::ScalarField::MODULUS).is_zero());
+        assert!(same_ratio::<E>(&(g1_s, g1_s_x), &(g2_s, g2_s.mul(x).into_affine())));
+    }
+
     #[test]
     fn test_same_ratio() {
         let rng = &mut thread_rng();
@@ -410,15 +410,15 @@ pub fn same_ratio<E: Pairing>(g1: &(E::G1Affine, E::G1Affine), g2: &(E::G2Affine
 pub fn check_same_ratio<E: Pairing>(
     g1: &(E::G1Affine, E::G1Affine),
     g2: &(E::G2Affine, E::G2Affine),
-    err: String,
+    check: RatioCheck,
 ) -> Result<()> {
     if g1.0.is_zero() || g1.1.is_zero() || g2.0.is_zero() || g2.1.is_zero() {
         error!("Invalid Ratio: zero");
-        return Err(VerificationError::InvalidRatio(err).into());
+        return Err(VerificationError::invalid_ratio(check).into());
     }
     if E::pairing(g1.0, g2.1) != E::pairing(g1.1, g2.0) {
         error!("Invalid Ratio: wrong pairing");
-        return Err(VerificationError::InvalidRatio(err).into());
+        return Err(VerificationError::invalid_ratio(check).into());
     }
     Ok(())
 }
@@ -1,23 +1,43 @@
 //! Utilities for writing and reading group elements to buffers compressed and uncompressed
 mod read;
-use std::{fmt::Debug, io::Write, path::Path};
+use std::{fs::File, io::Write, path::Path};
 
 pub use read::{BatchDeserializer, Deserializer};
 
 mod write;
 pub use write::{BatchSerializer, Serializer};
 
-use crate::UseCompression;
+use crate::{Error, Result, UseCompression};
 use ark_ec::AffineRepr;
 
 pub fn buffer_size<C: AffineRepr>(compression: UseCompression) -> usize {
     C::default().serialized_size(compression)
 }
 
-pub fn write_to_file<P: AsRef<Path> + Debug>(path: P, data: &[u8]) {
-    let mut f = std::fs::File::create(&path).expect(&format!("unable to open new {:?} file", path));
-    f.write_all(data).expect(&format!("unable to write to {:?}", path));
-    f.sync_all().expect(&format!("unable to flush {:?}", path));
+pub fn write_to_file<P: AsRef<Path>>(path: P, data: &[u8]) -> Result<()> {
+    let mut f = std::fs::File::create(&path)?;
+    f.write_all(data)?;
+    f.sync_all()?;
+    Ok(())
+}
+
+/// Writes a JSON report of why a command failed with `err` to `path`
+pub fn write_failure_report<P: AsRef<Path>>(path: P, err: &Error) -> Result<()> {
+    let report = serde_json::to_vec_pretty(&err.report()).map_err(std::io::Error::from)?;
+    write_to_file(path, &report)
+}
+
+/// Checks that the file named `filename` has a size of `expected` bytes
+pub fn check_file_size(file: &File, filename: &str, expected: usize) -> Result<()> {
+    let got = file.metadata()?.len();
+    if got != expected as u64 {
+        return Err(Error::InvalidFileSize {
+            file: filename.to_string(),
+            expected: expected as u64,
+            got,
+        });
+    }
+    Ok(())
 }
 
 #[cfg(test)]
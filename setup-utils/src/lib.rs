@@ -3,11 +3,24 @@
 //! Utilities for building MPC Ceremonies for large SNARKs.
 //! Provides traits for batched writing and reading group elements to buffers.
 pub mod errors;
-pub use errors::{Error, InvariantKind, Phase2Error, VerificationError};
+pub use errors::{
+    Error,
+    FailureReport,
+    InvariantKind,
+    Phase2Error,
+    RatioCheck,
+    VerificationContext,
+    VerificationError,
+};
 
 /// A convenience result type for returning errors
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[cfg(not(feature = "wasm"))]
+mod beacon;
+#[cfg(not(feature = "wasm"))]
+pub use beacon::{beacon_hash_from_str, BeaconCheckpoints, DEFAULT_BEACON_ITERATIONS_EXP, MIN_BEACON_ITERATIONS_EXP};
+
 mod groth16_utils;
 pub use groth16_utils::{domain_size, Groth16Params};
 
@@ -24,14 +37,39 @@ pub use elements::{
     UseCompression,
 };
 
+mod file_header;
+pub use file_header::{
+    curve_id,
+    header_length,
+    read_file_header,
+    split_file_header,
+    FileHeader,
+    FILE_HEADER_SIZE,
+    FILE_HEADER_VERSION,
+};
+
 mod helpers;
 pub use helpers::*;
 
+mod manifest;
+pub use manifest::{CeremonyManifest, Phase2Manifest, MANIFEST_VERSION};
+
 mod io;
-pub use io::{buffer_size, write_to_file, BatchDeserializer, BatchSerializer, Deserializer, Serializer};
+pub use io::{
+    buffer_size,
+    check_file_size,
+    write_failure_report,
+    write_to_file,
+    BatchDeserializer,
+    BatchSerializer,
+    Deserializer,
+    Serializer,
+};
 
 pub mod rayon_cfg;
 
+pub mod snarkjs;
+
 mod seed;
 pub use seed::derive_rng_from_seed;
 
@@ -0,0 +1,312 @@
+use crate::{
+    blank_hash,
+    calculate_hash,
+    converters::{
+        contribution_mode_from_str,
+        curve_from_str,
+        proving_system_from_str,
+        ContributionMode,
+        CurveKind,
+        ProvingSystem,
+    },
+    Error,
+    GenericArray,
+    Result,
+    U64,
+};
+
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// The version of the manifest format this code reads
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Marks the hash field of a challenge created from a manifest, so that it can be told apart from
+/// the hash of a previous response
+const MANIFEST_HEADER_MAGIC: &[u8; 16] = b"snark-setup-mfst";
+
+/// The settings shared by every command of a ceremony, so that they don't have to be repeated
+/// (and possibly mistyped) on each command line. Manifests are read from TOML, or from JSON if
+/// the file name ends with `.json`:
+///
+/// ```toml
+/// version = 1
+/// curve_kind = "bls12_377"
+/// proving_system = "groth16"
+/// contribution_mode = "chunked"
+/// power = 21
+/// batch_size = 256
+/// chunk_size = 65536
+///
+/// [phase2]
+/// batch_size = 16384
+/// chunk_size = 65536
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CeremonyManifest {
+    pub version: u32,
+    pub curve_kind: String,
+    pub proving_system: String,
+    pub contribution_mode: String,
+    pub power: usize,
+    pub batch_size: usize,
+    pub chunk_size: usize,
+    /// The settings of Phase 2, which splits the parameters into chunks of its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase2: Option<Phase2Manifest>,
+}
+
+/// The settings specific to Phase 2
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Phase2Manifest {
+    pub batch_size: usize,
+    pub chunk_size: usize,
+}
+
+impl CeremonyManifest {
+    /// Reads and validates a manifest
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        let is_json = path.extension().is_some_and(|extension| extension == "json");
+        let manifest: Self = if is_json {
+            serde_json::from_str(&contents).map_err(|e| Error::InvalidManifest(format!("{}: {}", path.display(), e)))?
+        } else {
+            toml::from_str(&contents).map_err(|e| Error::InvalidManifest(format!("{}: {}", path.display(), e)))?
+        };
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Checks the version of the manifest and that its settings are supported
+    pub fn validate(&self) -> Result<()> {
+        if self.version != MANIFEST_VERSION {
+            return Err(Error::InvalidManifest(format!(
+                "version {} is not supported, expected {}",
+                self.version, MANIFEST_VERSION
+            )));
+        }
+        self.curve_kind()?;
+        self.proving_system()?;
+        let contribution_mode = self.contribution_mode()?;
+        if self.power == 0 {
+            return Err(Error::InvalidManifest("the power must be positive".to_string()));
+        }
+        if self.batch_size == 0 {
+            return Err(Error::InvalidManifest("the batch size must be positive".to_string()));
+        }
+        if contribution_mode == ContributionMode::Chunked && self.chunk_size == 0 {
+            return Err(Error::InvalidManifest(
+                "the chunk size must be positive in the chunked mode".to_string(),
+            ));
+        }
+        if let Some(phase2) = &self.phase2 {
+            if phase2.batch_size == 0 || phase2.chunk_size == 0 {
+                return Err(Error::InvalidManifest(
+                    "the batch size and the chunk size of Phase 2 must be positive".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the settings of Phase 2, which are optional for manifests only used in Phase 1
+    pub fn phase2(&self) -> Result<&Phase2Manifest> {
+        self.phase2
+            .as_ref()
+            .ok_or_else(|| Error::InvalidManifest("the manifest has no [phase2] section".to_string()))
+    }
+
+    pub fn curve_kind(&self) -> Result<CurveKind> {
+        curve_from_str(&self.curve_kind).map_err(|e| Error::InvalidManifest(format!("{}: {}", self.curve_kind, e)))
+    }
+
+    pub fn proving_system(&self) -> Result<ProvingSystem> {
+        proving_system_from_str(&self.proving_system)
+            .map_err(|e| Error::InvalidManifest(format!("{}: {}", self.proving_system, e)))
+    }
+
+    pub fn contribution_mode(&self) -> Result<ContributionMode> {
+        contribution_mode_from_str(&self.contribution_mode)
+            .map_err(|e| Error::InvalidManifest(format!("{}: {}", self.contribution_mode, e)))
+    }
+
+    /// The BLAKE2b hash of the canonical JSON encoding of the manifest, which doesn't depend on
+    /// the format it was read from
+    pub fn hash(&self) -> GenericArray<u8, U64> {
+        let canonical = serde_json::to_vec(self).expect("a manifest can always be encoded");
+        calculate_hash(&canonical)
+    }
+
+    /// The value of the hash field of initial challenges created from this manifest: a magic
+    /// marker followed by the first 48 bytes of the hash of the manifest
+    pub fn challenge_header(&self) -> [u8; 64] {
+        let mut header = [0; 64];
+        header[..16].copy_from_slice(MANIFEST_HEADER_MAGIC);
+        header[16..].copy_from_slice(&self.hash()[..48]);
+        header
+    }
+
+    /// Whether the hash field of a challenge records the manifest it was created from, rather than
+    /// the hash of a previous response
+    pub fn is_challenge_header(header: &[u8]) -> bool {
+        header.starts_with(MANIFEST_HEADER_MAGIC)
+    }
+
+    /// Checks that a challenge belongs to the ceremony described by this manifest, given its hash
+    /// field. Initial challenges must have been created from the same manifest. Later challenges
+    /// carry the hash of the response they were computed from instead, so this check accepts any
+    /// of them, including the challenges of another ceremony. Only following the hash chain back
+    /// to the initial challenge ties them to the manifest.
+    pub fn check_challenge_header(&self, header: &[u8]) -> Result<()> {
+        if header == self.challenge_header() {
+            Ok(())
+        } else if Self::is_challenge_header(header) {
+            Err(Error::InvalidManifest(
+                "the challenge was created from another manifest".to_string(),
+            ))
+        } else if header == blank_hash().as_slice() {
+            Err(Error::InvalidManifest(
+                "the challenge was created without a manifest".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML_MANIFEST: &str = r#"
+version = 1
+curve_kind = "bls12_377"
+proving_system = "groth16"
+contribution_mode = "chunked"
+power = 10
+batch_size = 64
+chunk_size = 512
+
+[phase2]
+batch_size = 128
+chunk_size = 1024
+"#;
+
+    fn manifest() -> CeremonyManifest {
+        CeremonyManifest {
+            version: MANIFEST_VERSION,
+            curve_kind: "bls12_377".to_string(),
+            proving_system: "groth16".to_string(),
+            contribution_mode: "chunked".to_string(),
+            power: 10,
+            batch_size: 64,
+            chunk_size: 512,
+            phase2: Some(Phase2Manifest {
+                batch_size: 128,
+                chunk_size: 1024,
+            }),
+        }
+    }
+
+    fn write_manifest(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("setup-utils-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_manifest() {
+        let toml_path = write_manifest("manifest.toml", TOML_MANIFEST);
+        let json_path = write_manifest("manifest.json", &serde_json::to_string(&manifest()).unwrap());
+        let from_toml = CeremonyManifest::read(&toml_path).unwrap();
+        let from_json = CeremonyManifest::read(&json_path).unwrap();
+        assert_eq!(from_toml, manifest());
+        assert_eq!(from_toml.hash(), from_json.hash());
+
+        let unknown_field = write_manifest("unknown.toml", &format!("{}chunk_count = 2\n", TOML_MANIFEST));
+        assert_eq!(from_toml.phase2().unwrap().chunk_size, 1024);
+        assert!(matches!(
+            CeremonyManifest::read(&unknown_field),
+            Err(Error::InvalidManifest(_))
+        ));
+
+        for path in [toml_path, json_path, unknown_field] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_validate_manifest() {
+        assert!(manifest().validate().is_ok());
+        let invalid = [
+            CeremonyManifest {
+                version: MANIFEST_VERSION + 1,
+                ..manifest()
+            },
+            CeremonyManifest {
+                curve_kind: "bls12_378".to_string(),
+                ..manifest()
+            },
+            CeremonyManifest {
+                proving_system: "plonk".to_string(),
+                ..manifest()
+            },
+            CeremonyManifest {
+                batch_size: 0,
+                ..manifest()
+            },
+            CeremonyManifest {
+                chunk_size: 0,
+                ..manifest()
+            },
+            CeremonyManifest {
+                phase2: Some(Phase2Manifest {
+                    batch_size: 128,
+                    chunk_size: 0,
+                }),
+                ..manifest()
+            },
+        ];
+        for manifest in invalid {
+            let err = manifest.validate().unwrap_err();
+            assert!(matches!(err, Error::InvalidManifest(_)), "{}", err);
+            assert_eq!(err.exit_code(), Error::EXIT_CODE_ARGS);
+        }
+        // the chunk size is irrelevant when contributing to the full accumulator
+        let full = CeremonyManifest {
+            contribution_mode: "full".to_string(),
+            chunk_size: 0,
+            ..manifest()
+        };
+        assert!(full.validate().is_ok());
+        let phase1_only = CeremonyManifest {
+            phase2: None,
+            ..manifest()
+        };
+        assert!(phase1_only.validate().is_ok());
+        assert!(matches!(phase1_only.phase2(), Err(Error::InvalidManifest(_))));
+    }
+
+    #[test]
+    fn test_check_challenge_header() {
+        let manifest = manifest();
+        let other = CeremonyManifest {
+            power: 11,
+            ..manifest.clone()
+        };
+        assert_ne!(manifest.hash(), other.hash());
+        assert!(manifest.check_challenge_header(&manifest.challenge_header()).is_ok());
+        assert!(manifest.check_challenge_header(&other.challenge_header()).is_err());
+        assert!(manifest.check_challenge_header(&blank_hash()).is_err());
+        // the challenges after the first one start with the hash of a response, which doesn't tell
+        // which ceremony they belong to, so they are accepted even if they come from another one
+        assert!(manifest.check_challenge_header(&calculate_hash(b"response")).is_ok());
+        let other_response = [&other.challenge_header()[..], b"contribution"].concat();
+        assert!(manifest
+            .check_challenge_header(&calculate_hash(&other_response))
+            .is_ok());
+    }
+}
@@ -24,3 +24,28 @@ cfg_if::cfg_if! {
         }
     }
 }
+
+/// Keeps the first error returned by the tasks spawned in a scope, so that they
+/// can fail without panicking.
+#[derive(Debug, Default)]
+pub struct FirstError(std::sync::Mutex<Option<crate::Error>>);
+
+impl FirstError {
+    /// Runs `task`, keeping its error if no other task failed before
+    pub fn run(&self, task: impl FnOnce() -> crate::Result<()>) {
+        if let Err(err) = task() {
+            let mut first = self.0.lock().expect("should have locked the first error");
+            if first.is_none() {
+                *first = Some(err);
+            }
+        }
+    }
+
+    /// Returns the first error, if any task failed
+    pub fn into_result(self) -> crate::Result<()> {
+        match self.0.into_inner().expect("should have unlocked the first error") {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Helpers for the binary container format shared by the snarkjs `.ptau` and `.zkey` files
+//! and the circom `.r1cs` files.
+//!
+//! A file starts with a 4 byte magic, a version and the number of sections. Every section is
+//! prefixed by its id (`u32`) and its length in bytes (`u64`), all integers being little-endian.
+//!
+//! Field elements are written little-endian using 8 bytes per limb, in Montgomery form for the
+//! snarkjs files. Points are stored as their affine coordinates and the point at infinity is all
+//! zeroes.
+use crate::{CheckForCorrectness, Deserializer, Error, Result, UseCompression};
+
+use ark_ec::{
+    short_weierstrass::{Affine, SWCurveConfig},
+    AffineRepr,
+};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::CanonicalDeserialize;
+use ark_std::cfg_chunks;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use std::{collections::HashMap, io::Write};
+
+/// The version of the container format which is read and written
+pub const SNARKJS_VERSION: u32 = 1;
+
+/// Affine points which can be built from the coordinates stored in a snarkjs file.
+pub trait SnarkjsPoint: AffineRepr {
+    /// Builds the point without checking that it is on the curve or in the right subgroup
+    fn from_xy_unchecked(x: Self::BaseField, y: Self::BaseField) -> Self;
+}
+
+impl<P: SWCurveConfig> SnarkjsPoint for Affine<P> {
+    fn from_xy_unchecked(x: Self::BaseField, y: Self::BaseField) -> Self {
+        Affine::new_unchecked(x, y)
+    }
+}
+
+/// The sections of a snarkjs file, indexed by their id.
+pub struct Sections<'a> {
+    sections: HashMap<u32, &'a [u8]>,
+}
+
+impl<'a> Sections<'a> {
+    /// Splits `input` into its sections, after checking the magic and the version
+    pub fn read(input: &'a [u8], magic: &[u8; 4]) -> Result<Self> {
+        let mut reader = input;
+        if take(&mut reader, 4)? != magic {
+            return Err(Error::InvalidSnarkjsFile(format!(
+                "the file does not start with `{}`",
+                String::from_utf8_lossy(magic)
+            )));
+        }
+        let version = read_u32(&mut reader)?;
+        if version != SNARKJS_VERSION {
+            return Err(Error::InvalidSnarkjsFile(format!("unsupported version {}", version)));
+        }
+        let num_sections = read_u32(&mut reader)?;
+        let mut sections = HashMap::new();
+        for _ in 0..num_sections {
+            let id = read_u32(&mut reader)?;
+            let size = read_u64(&mut reader)? as usize;
+            sections.insert(id, take(&mut reader, size)?);
+        }
+        Ok(Self { sections })
+    }
+
+    /// Returns the contents of the section `id`, if present
+    pub fn get(&self, id: u32) -> Option<&'a [u8]> {
+        self.sections.get(&id).copied()
+    }
+
+    /// Returns the contents of the section `id`, failing if it is missing
+    pub fn require(&self, id: u32) -> Result<&'a [u8]> {
+        self.get(id)
+            .ok_or_else(|| Error::InvalidSnarkjsFile(format!("missing section {}", id)))
+    }
+}
+
+/// Writes the magic, the version and the number of sections of a file
+pub fn write_file_header<W: Write>(writer: &mut W, magic: &[u8; 4], num_sections: u32) -> Result<()> {
+    writer.write_all(magic)?;
+    writer.write_all(&SNARKJS_VERSION.to_le_bytes())?;
+    writer.write_all(&num_sections.to_le_bytes())?;
+    Ok(())
+}
+
+/// Writes the id and the length in bytes of the section which follows
+pub fn write_section_header<W: Write>(writer: &mut W, id: u32, size: usize) -> Result<()> {
+    writer.write_all(&id.to_le_bytes())?;
+    writer.write_all(&(size as u64).to_le_bytes())?;
+    Ok(())
+}
+
+/// Takes the next `len` bytes from the reader
+pub fn take<'b>(reader: &mut &'b [u8], len: usize) -> Result<&'b [u8]> {
+    if reader.len() < len {
+        return Err(Error::InvalidLength {
+            expected: len,
+            got: reader.len(),
+        });
+    }
+    let (taken, rest) = reader.split_at(len);
+    *reader = rest;
+    Ok(taken)
+}
+
+pub fn read_u32(reader: &mut &[u8]) -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(take(reader, 4)?);
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub fn read_u64(reader: &mut &[u8]) -> Result<u64> {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(take(reader, 8)?);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+type BasePrimeField<G> = <<G as AffineRepr>::BaseField as Field>::BasePrimeField;
+
+/// The number of bytes snarkjs uses for one element of a prime field (`n8`)
+pub fn field_size<F: PrimeField>() -> usize {
+    F::BigInt::NUM_LIMBS * 8
+}
+
+/// The modulus of a prime field, as written in the headers
+pub fn modulus_bytes<F: PrimeField>() -> Vec<u8> {
+    F::MODULUS.to_bytes_le()
+}
+
+/// R = 2^{64 * limbs}, the Montgomery constant snarkjs uses for a prime field
+pub fn montgomery_r<F: PrimeField>() -> F {
+    F::from(2u64).pow([64 * F::BigInt::NUM_LIMBS as u64])
+}
+
+/// Appends `element * r` to the output. With `r` being the Montgomery constant this is the
+/// Montgomery form of `element`.
+pub fn field_to_bytes<F: PrimeField>(element: &F, r: &F, output: &mut Vec<u8>) {
+    output.extend_from_slice(&(*element * r).into_bigint().to_bytes_le());
+}
+
+/// Reads a field element written little-endian in its canonical form
+pub fn field_from_bytes<F: PrimeField>(mut bytes: &[u8]) -> Result<F> {
+    let bigint = F::BigInt::deserialize_uncompressed(&mut bytes)?;
+    F::from_bigint(bigint).ok_or_else(|| Error::InvalidSnarkjsFile("field element is not reduced".to_string()))
+}
+
+/// The number of bytes snarkjs uses for one element of the base prime field of a curve
+pub fn prime_field_size<G: AffineRepr>() -> usize {
+    field_size::<BasePrimeField<G>>()
+}
+
+/// The size of an affine point in a snarkjs file
+pub fn point_size<G: AffineRepr>() -> usize {
+    2 * G::BaseField::extension_degree() as usize * prime_field_size::<G>()
+}
+
+fn point_to_bytes<G: AffineRepr>(point: &G, r: &BasePrimeField<G>, output: &mut Vec<u8>) {
+    match point.xy() {
+        Some((x, y)) => {
+            for coordinate in [x, y] {
+                for element in coordinate.to_base_prime_field_elements() {
+                    field_to_bytes(&element, r, output);
+                }
+            }
+        }
+        None => output.resize(output.len() + point_size::<G>(), 0),
+    }
+}
+
+pub fn write_point<W: Write, G: AffineRepr>(writer: &mut W, point: &G) -> Result<()> {
+    write_points(writer, std::slice::from_ref(point))
+}
+
+pub fn write_points<W: Write, G: AffineRepr>(writer: &mut W, points: &[G]) -> Result<()> {
+    let r = montgomery_r::<BasePrimeField<G>>();
+    let mut output = Vec::with_capacity(points.len() * point_size::<G>());
+    for point in points {
+        point_to_bytes(point, &r, &mut output);
+    }
+    writer.write_all(&output)?;
+    Ok(())
+}
+
+/// Decodes a point from its snarkjs representation. The point is re-serialized with its
+/// own encoding before being read back, so that the usual correctness checks apply.
+fn point_from_bytes<G: SnarkjsPoint>(
+    bytes: &[u8],
+    r_inv: &BasePrimeField<G>,
+    check_for_correctness: CheckForCorrectness,
+) -> Result<G> {
+    let n8 = prime_field_size::<G>();
+    if bytes.iter().all(|b| *b == 0) {
+        if check_for_correctness == CheckForCorrectness::Full
+            || check_for_correctness == CheckForCorrectness::OnlyNonZero
+        {
+            return Err(Error::PointAtInfinity);
+        }
+        return Ok(G::zero());
+    }
+
+    let mut coordinates = bytes.chunks(bytes.len() / 2).map(|coordinate| {
+        let elements = coordinate
+            .chunks(n8)
+            .map(|chunk| Ok(field_from_bytes::<BasePrimeField<G>>(chunk)? * r_inv))
+            .collect::<Result<Vec<_>>>()?;
+        G::BaseField::from_base_prime_field_elems(&elements)
+            .ok_or_else(|| Error::InvalidSnarkjsFile("invalid coordinate".to_string()))
+    });
+    let x = coordinates.next().expect("a point has two coordinates")?;
+    let y = coordinates.next().expect("a point has two coordinates")?;
+
+    let mut uncompressed = Vec::with_capacity(G::default().uncompressed_size());
+    G::from_xy_unchecked(x, y).serialize_uncompressed(&mut uncompressed)?;
+    (&uncompressed[..]).read_element(UseCompression::No, check_for_correctness)
+}
+
+pub fn read_point<G: SnarkjsPoint>(reader: &mut &[u8], check_for_correctness: CheckForCorrectness) -> Result<G> {
+    let r_inv = montgomery_r::<BasePrimeField<G>>().inverse().expect("R is invertible");
+    point_from_bytes(take(reader, point_size::<G>())?, &r_inv, check_for_correctness)
+}
+
+/// Reads a section made of exactly `expected` points
+pub fn read_points<G: SnarkjsPoint>(
+    section: &[u8],
+    expected: usize,
+    check_for_correctness: CheckForCorrectness,
+) -> Result<Vec<G>> {
+    let size = point_size::<G>();
+    if section.len() != expected * size {
+        return Err(Error::InvalidLength {
+            expected: expected * size,
+            got: section.len(),
+        });
+    }
+    let r_inv = montgomery_r::<BasePrimeField<G>>().inverse().expect("R is invertible");
+    cfg_chunks!(section, size)
+        .map(|bytes| point_from_bytes(bytes, &r_inv, check_for_correctness))
+        .collect()
+}